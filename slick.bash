@@ -0,0 +1,124 @@
+#!/usr/bin/env bash
+# Canonical sourceable loader for slick prompt in bash
+
+if [[ $- != *i* ]]; then
+    return 0 2>/dev/null || exit 0
+fi
+
+# Detect slick binary location (use absolute paths)
+if [[ -n "${SLICK_PATH:-}" && -x "${SLICK_PATH}" ]]; then
+    :
+elif command -v slick &> /dev/null; then
+    SLICK_PATH="$(command -v slick)"
+elif [[ -x "$HOME/.cargo/bin/slick" ]]; then
+    SLICK_PATH="$HOME/.cargo/bin/slick"
+else
+    return 0 2>/dev/null || exit 0
+fi
+
+# Global variables
+slick_prompt_data=${slick_prompt_data:-}
+slick_prompt_data_dir=${slick_prompt_data_dir:-}
+slick_prompt_fd=${slick_prompt_fd:-}
+slick_prompt_timestamp=${slick_prompt_timestamp:-}
+slick_prompt_elapsed=${slick_prompt_elapsed:-}
+slick_prompt_exit_status=${slick_prompt_exit_status:-0}
+
+function slick_prompt_render {
+    local exit_status=${1:-0}
+    local promptvars=0
+    local -a args
+
+    args=(
+        "$SLICK_PATH"
+        prompt
+        --backend bash
+        -k main
+        -r "$exit_status"
+        -d "${slick_prompt_data:-}"
+        --cached
+    )
+
+    if [[ -n "${slick_prompt_elapsed:-}" ]]; then
+        args+=(-e "$slick_prompt_elapsed")
+    fi
+
+    shopt -q promptvars && promptvars=1
+    _SLICK_PROMPT_BASH_PROMPTVARS=$promptvars \
+    _SLICK_PROMPT_LOADER_VERSION="${slick_prompt_loader_version:-}" \
+        "${args[@]}"
+}
+
+function slick_prompt_close_fd {
+    if [[ -n "${slick_prompt_fd:-}" ]]; then
+        exec {slick_prompt_fd}<&-
+        slick_prompt_fd=""
+    fi
+}
+
+# Keep the latest phase `slick precmd` has printed. With $1 set, wait up to $1 seconds
+# for the next phase; after that, only read phases already in the pipe. The rest stay
+# there for the next prompt.
+function slick_prompt_read_phases {
+    local timeout=${1:-}
+    local line
+
+    while [[ -n "${slick_prompt_fd:-}" ]]; do
+        # `read -t 0` only reports whether a line is waiting, without consuming it
+        if [[ -z "$timeout" ]]; then
+            read -t 0 -u "$slick_prompt_fd" || return 0
+        fi
+        if IFS= read -r -t "${timeout:-1}" -u "$slick_prompt_fd" line; then
+            slick_prompt_data=$line
+        elif (( $? > 128 )); then
+            # Timed out; the rest arrives later
+            return 0
+        else
+            # No more phases
+            slick_prompt_close_fd
+        fi
+        timeout=""
+    done
+}
+
+function slick_prompt_precmd {
+    slick_prompt_exit_status=$?
+
+    # Elapsed time of the last command, if one ran since the previous prompt
+    if [[ -n "${slick_prompt_timestamp:-}" ]]; then
+        slick_prompt_elapsed=$(( SECONDS - slick_prompt_timestamp ))
+        # Ensure elapsed time is never negative
+        (( slick_prompt_elapsed < 0 )) && slick_prompt_elapsed=0
+        slick_prompt_timestamp=""
+    else
+        slick_prompt_elapsed=""
+    fi
+
+    # Bash cannot redraw the prompt when a late phase (such as the remote fetch)
+    # arrives, so it is picked up here for the next prompt in the same directory.
+    if [[ "$PWD" == "${slick_prompt_data_dir:-}" ]]; then
+        slick_prompt_read_phases
+    else
+        slick_prompt_data=""
+    fi
+    slick_prompt_close_fd
+
+    # Bash cannot redraw a waiting prompt, so watch mode would only hold up the read
+    exec {slick_prompt_fd}< <(SLICK_PROMPT_GIT_WATCH=0 "$SLICK_PATH" precmd)
+    slick_prompt_data_dir=$PWD
+    # The local phase is printed at once; later ones must not hold up the prompt
+    slick_prompt_read_phases 0.5
+
+    PS1=$(slick_prompt_render "$slick_prompt_exit_status")
+
+    return "$slick_prompt_exit_status"
+}
+
+# Register the hooks once (safe for re-sourcing). PS0 is expanded right before a
+# command runs; the arithmetic records the start time and prints nothing.
+if [[ "${PROMPT_COMMAND[*]:-}" != *slick_prompt_precmd* ]]; then
+    PROMPT_COMMAND="slick_prompt_precmd${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+fi
+if [[ "${PS0:-}" != *slick_prompt_timestamp* ]]; then
+    PS0="${PS0:-}"'${slick_prompt_timestamp:0:$((slick_prompt_timestamp = SECONDS, 0))}'
+fi
@@ -0,0 +1,122 @@
+#!/usr/bin/env fish
+# Canonical sourceable loader for slick prompt in fish
+
+if not status is-interactive
+    return 0
+end
+
+# Detect slick binary location (use absolute paths)
+if set -q SLICK_PATH; and test -x "$SLICK_PATH"
+    :
+else if command -q slick
+    set -g SLICK_PATH (command -s slick)
+else if test -x "$HOME/.cargo/bin/slick"
+    set -g SLICK_PATH "$HOME/.cargo/bin/slick"
+else
+    return 0
+end
+
+# Global variables
+set -g slick_prompt_data ""
+set -g slick_prompt_elapsed ""
+set -g slick_prompt_exit_status 0
+set -q slick_prompt_generation; or set -g slick_prompt_generation 0
+set -g slick_prompt_dir (set -q TMPDIR; and echo $TMPDIR; or echo /tmp)/slick-fish.$fish_pid
+command mkdir -p $slick_prompt_dir
+
+# Map fish's vi bind modes to the keymaps `slick prompt -k` knows.
+function slick_prompt_keymap
+    switch "$fish_bind_mode"
+        case default
+            echo vicmd
+        case visual
+            echo visual
+        case '*'
+            echo main
+    end
+end
+
+function slick_prompt_render
+    set -l args prompt --backend fish -k (slick_prompt_keymap) -r $slick_prompt_exit_status -d "$slick_prompt_data" --cached
+
+    if test -n "$slick_prompt_elapsed"
+        set -a args -e $slick_prompt_elapsed
+    end
+
+    set -lx _SLICK_PROMPT_LOADER_VERSION $slick_prompt_loader_version
+    command $SLICK_PATH $args $argv
+end
+
+function fish_prompt
+    slick_prompt_render
+end
+
+function fish_right_prompt
+    slick_prompt_render --right
+end
+
+# The prompt symbol and the cursor shape already show the vi mode. With no mode
+# prompt, `repaint-mode` redraws fish_prompt instead.
+function fish_mode_prompt
+end
+
+function slick_prompt_file
+    echo $slick_prompt_dir/$slick_prompt_generation
+end
+
+# Stop the running `slick precmd`, if any, and ignore anything it still sends.
+function slick_prompt_cancel
+    if set -q slick_prompt_pid
+        command kill -- -$slick_prompt_pid 2>/dev/null
+        or command kill $slick_prompt_pid 2>/dev/null
+        set -e slick_prompt_pid
+    end
+    command rm -f (slick_prompt_file)
+    set -g slick_prompt_generation (math $slick_prompt_generation + 1)
+end
+
+function slick_prompt_postexec --on-event fish_postexec
+    set -g slick_prompt_exit_status $status
+    set -g slick_prompt_elapsed (math --scale=0 "floor($CMD_DURATION / 1000)")
+    set -g slick_prompt_command_ran 1
+end
+
+function slick_prompt_preexec --on-event fish_preexec
+    # Kill the async prompt so it does not repaint over command output
+    slick_prompt_cancel
+end
+
+function slick_prompt_precmd --on-event fish_prompt
+    slick_prompt_cancel
+    set -g slick_prompt_data ""
+
+    # Only show the elapsed time right after a command ran (not after an empty line)
+    if set -q slick_prompt_command_ran
+        set -e slick_prompt_command_ran
+    else
+        set -g slick_prompt_elapsed ""
+    end
+
+    # Each phase replaces the file and signals this shell, which repaints with it.
+    command sh -c '
+        "$1" precmd | while IFS= read -r line; do
+            printf "%s\n" "$line" > "$2.tmp" && mv -f "$2.tmp" "$2" && kill -USR1 "$3"
+        done
+    ' sh $SLICK_PATH (slick_prompt_file) $fish_pid &
+    set -g slick_prompt_pid $last_pid
+    disown $slick_prompt_pid 2>/dev/null
+end
+
+function slick_prompt_refresh --on-signal SIGUSR1
+    set -l file (slick_prompt_file)
+    if test -r $file
+        read -l line <$file
+        set -g slick_prompt_data $line
+        commandline -f repaint
+    end
+end
+
+function slick_prompt_exit --on-event fish_exit
+    slick_prompt_cancel
+    command rm -rf $slick_prompt_dir
+end
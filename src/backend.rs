@@ -0,0 +1,233 @@
+//! Output backends.
+//!
+//! The prompt is laid out as [`Token`]s: pieces of styled text tagged with the role
+//! they play, and the spaces and line breaks between them. A backend prints those
+//! tokens for one consumer, so the layout, powerline and segment logic is shared by
+//! all of them:
+//!
+//! - `zsh`: prompt escapes for `PROMPT` and `RPROMPT`.
+//! - `bash`: `PS1` text with `\[`/`\]` around escape sequences.
+//! - `ansi`: plain ANSI escape sequences, for terminals and other programs.
+//! - `fish`: the `ansi` output, which `fish_prompt` prints as-is.
+//! - `json`: the segments themselves, for editors, status bars and scripts.
+use crate::{
+    layout::{Piece, Text, Token},
+    powerline,
+    settings::PowerlineSettings,
+    style::Style,
+};
+use std::{
+    env,
+    ffi::CStr,
+    path::{Path, PathBuf},
+};
+
+pub mod ansi;
+pub mod bash;
+pub mod json;
+pub mod zsh;
+
+/// Backend names accepted by `slick prompt --backend`.
+pub const BACKENDS: &[&str] = &["zsh", "bash", "fish", "ansi", "json"];
+
+/// Returns the backend called `name`.
+#[must_use]
+pub fn from_name(name: &str) -> Option<Box<dyn Backend>> {
+    match name {
+        "zsh" => Some(Box::new(zsh::Zsh::from_env())),
+        "bash" => Some(Box::new(bash::Bash::from_env())),
+        // fish prints the prompt functions' output without expanding it and measures
+        // escape sequences itself, so plain ANSI is all it needs.
+        "ansi" | "fish" => Some(Box::new(ansi::Ansi::from_env())),
+        "json" => Some(Box::new(json::Json)),
+        _ => None,
+    }
+}
+
+/// Prints laid-out tokens for one kind of consumer.
+pub trait Backend {
+    /// Makes text from the repository, the environment or a template safe to print.
+    fn escape(&self, text: &str) -> String;
+
+    /// Wraps an escape sequence that takes no room on screen.
+    fn non_printing(&self, sequence: &str) -> String;
+
+    /// The sequence that switches `style` on.
+    fn style_start(&self, style: &Style) -> String;
+
+    /// The sequence that switches off everything `style_start` did but the foreground.
+    ///
+    /// The foreground is left alone, as it always has been, so a plain color renders
+    /// as a single color switch.
+    fn style_end(&self, style: &Style) -> String;
+
+    /// The sequence that resets the foreground color.
+    fn reset_color(&self) -> String;
+
+    /// Prints a value the prompt fills in when it is shown.
+    ///
+    /// By default the current value is looked up; shells that expand these
+    /// themselves print their own escape instead.
+    fn value(&self, text: &Text) -> String {
+        match text {
+            Text::UserName => user_name(),
+            Text::HostName => host_name(),
+            Text::Directory => directory(),
+            Text::Clock => clock(),
+            Text::Literal(_) | Text::Verbatim(_) | Text::ResetColor => String::new(),
+        }
+    }
+
+    /// Prints the text of a span, without its style.
+    fn text(&self, text: &Text) -> String {
+        match text {
+            Text::Literal(text) => self.escape(text),
+            Text::Verbatim(text) => text.clone(),
+            Text::ResetColor => self.reset_color(),
+            value => self.value(value),
+        }
+    }
+
+    /// Prints `text` in `style`.
+    fn paint(&self, style: &Style, text: &str) -> String {
+        format!("{}{text}{}", self.style_start(style), self.style_end(style))
+    }
+
+    /// Prints every span of `piece` in its own style; neighbouring spans that share a
+    /// style are painted together.
+    fn piece(&self, piece: &Piece) -> String {
+        let mut output = String::new();
+        let mut run: Option<(&Style, String)> = None;
+        for span in &piece.spans {
+            match &mut run {
+                Some((style, text)) if **style == span.style => {
+                    text.push_str(&self.text(&span.text));
+                }
+                _ => {
+                    if let Some((style, text)) = run.take() {
+                        output.push_str(&self.paint(style, &text));
+                    }
+                    run = Some((&span.style, self.text(&span.text)));
+                }
+            }
+        }
+        if let Some((style, text)) = run {
+            output.push_str(&self.paint(style, &text));
+        }
+        output
+    }
+
+    /// The sequence that sets the terminal cursor shape.
+    fn cursor_shape(&self, shape: u8) -> String {
+        self.non_printing(&format!("\x1b[{shape} q"))
+    }
+
+    /// Prints the tokens as styled text.
+    fn render(&self, tokens: &[Token]) -> String {
+        let mut output = String::with_capacity(256);
+        for token in tokens {
+            match token {
+                Token::Piece(piece) => output.push_str(&self.piece(piece)),
+                Token::Space(space) => output.push_str(space),
+                Token::Newline => output.push('\n'),
+            }
+        }
+        output
+    }
+
+    /// Prints the tokens as powerline blocks.
+    fn render_powerline(&self, tokens: &[Token], settings: &PowerlineSettings) -> String {
+        powerline::render(self, tokens, &settings.separator, &settings.text_color)
+    }
+}
+
+fn user_name() -> String {
+    uzers::get_current_username().map_or_else(
+        || env::var("USER").unwrap_or_default(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// The host name up to the first dot, like zsh's `%m`.
+fn host_name() -> String {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, and the last byte stays zero
+    // so the name is always terminated.
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len() - 1) };
+    if result != 0 {
+        return String::new();
+    }
+
+    CStr::from_bytes_until_nul(&buffer)
+        .map(|name| name.to_string_lossy())
+        .map(|name| name.split('.').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+/// The current directory with the home directory replaced by `~`, like zsh's `%~`.
+fn directory() -> String {
+    let current_dir = env::var_os("PWD")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    home_relative(&current_dir, home.as_deref())
+}
+
+fn home_relative(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(relative) if relative.as_os_str().is_empty() => "~".to_string(),
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// The local time as `HH:MM:SS`, like zsh's `%*`.
+fn clock() -> String {
+    // SAFETY: `time` accepts a null pointer, and `localtime_r` only writes to the
+    // `tm` it is given.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&raw const now, &raw mut tm).is_null() {
+            return String::new();
+        }
+        tm
+    };
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BACKENDS, clock, from_name, home_relative};
+    use std::path::Path;
+
+    #[test]
+    fn test_every_backend_name_resolves() {
+        for name in BACKENDS {
+            assert!(from_name(name).is_some(), "{name}");
+        }
+        assert!(from_name("tcsh").is_none());
+    }
+
+    #[test]
+    fn test_home_relative() {
+        let home = Path::new("/home/dev");
+        assert_eq!(home_relative(Path::new("/home/dev"), Some(home)), "~");
+        assert_eq!(
+            home_relative(Path::new("/home/dev/src/slick"), Some(home)),
+            "~/src/slick"
+        );
+        assert_eq!(home_relative(Path::new("/tmp"), Some(home)), "/tmp");
+        assert_eq!(home_relative(Path::new("/tmp"), None), "/tmp");
+    }
+
+    #[test]
+    fn test_clock_is_hh_mm_ss() {
+        let time = clock();
+        assert_eq!(time.len(), 8, "{time}");
+        assert_eq!(time.matches(':').count(), 2, "{time}");
+    }
+}
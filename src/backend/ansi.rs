@@ -0,0 +1,143 @@
+//! The ANSI backend: SGR escape sequences with every value filled in.
+//!
+//! The output can be printed to a terminal as-is, so control characters are removed
+//! from literal text.
+use super::Backend;
+use crate::style::{self, Color, Style, nearest_ansi256};
+
+/// Prints ANSI escape sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ansi {
+    truecolor: bool,
+}
+
+impl Ansi {
+    #[must_use]
+    pub const fn new(truecolor: bool) -> Self {
+        Self { truecolor }
+    }
+
+    /// The backend for the terminal's colors.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new(style::truecolor())
+    }
+
+    /// The SGR parameters selecting `color`; `base` is 30 for foregrounds and 40 for
+    /// backgrounds.
+    fn color(self, color: &Color, base: u8) -> String {
+        let extended = base + 8;
+        match (color.index(), color.rgb()) {
+            (Some(index), _) if index < 8 => (base + index).to_string(),
+            (Some(index), _) => format!("{extended};5;{index}"),
+            (None, Some((r, g, b))) if self.truecolor => format!("{extended};2;{r};{g};{b}"),
+            (None, Some((r, g, b))) => format!("{extended};5;{}", nearest_ansi256(r, g, b)),
+            (None, None) => (base + 9).to_string(),
+        }
+    }
+}
+
+fn sgr(parameters: &[String]) -> String {
+    if parameters.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", parameters.join(";"))
+    }
+}
+
+impl Backend for Ansi {
+    fn escape(&self, text: &str) -> String {
+        text.chars().filter(|c| !c.is_control()).collect()
+    }
+
+    fn non_printing(&self, sequence: &str) -> String {
+        sequence.to_string()
+    }
+
+    fn style_start(&self, style: &Style) -> String {
+        let mut parameters = Vec::new();
+        if let Some(fg) = &style.fg {
+            parameters.push(self.color(fg, 30));
+        }
+        if let Some(bg) = &style.bg {
+            parameters.push(self.color(bg, 40));
+        }
+        for (enabled, parameter) in [
+            (style.bold, "1"),
+            (style.underline, "4"),
+            (style.italic, "3"),
+        ] {
+            if enabled {
+                parameters.push(parameter.to_string());
+            }
+        }
+        sgr(&parameters)
+    }
+
+    fn style_end(&self, style: &Style) -> String {
+        let mut parameters = Vec::new();
+        for (enabled, parameter) in [
+            (style.italic, "23"),
+            (style.underline, "24"),
+            (style.bold, "22"),
+            (style.bg.is_some(), "49"),
+        ] {
+            if enabled {
+                parameters.push(parameter.to_string());
+            }
+        }
+        sgr(&parameters)
+    }
+
+    fn reset_color(&self) -> String {
+        sgr(&["39".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ansi;
+    use crate::{
+        backend::Backend,
+        layout::{Piece, Role, Text, Token},
+        style::Style,
+    };
+
+    fn style(spec: &str) -> Style {
+        Style::parse(spec).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_styles_become_sgr_sequences() {
+        let ansi = Ansi::new(false);
+        assert_eq!(ansi.style_start(&style("74")), "\x1b[38;5;74m");
+        assert_eq!(ansi.style_start(&style("red on 4")), "\x1b[31;44m");
+        assert_eq!(ansi.style_start(&style("default")), "\x1b[39m");
+        assert_eq!(
+            ansi.style_start(&style("bold italic #ff8800 on 236")),
+            "\x1b[38;5;208;48;5;236;1;3m"
+        );
+        assert_eq!(
+            Ansi::new(true).style_start(&style("#ff8800")),
+            "\x1b[38;2;255;136;0m"
+        );
+        assert_eq!(ansi.style_end(&style("bold 2 on 3")), "\x1b[22;49m");
+        assert_eq!(ansi.style_end(&style("74")), "");
+    }
+
+    #[test]
+    fn test_literal_text_loses_control_characters() {
+        let tokens = [
+            Token::Piece(Piece::new(Role::GitBranch, &style("3"), "ma\x1b[2Jin")),
+            Token::Space(" ".to_string()),
+            Token::Piece(
+                Piece::plain(Role::Symbol, Text::Verbatim("$".to_string()))
+                    .with(&Style::default(), Text::ResetColor),
+            ),
+        ];
+        assert_eq!(
+            Ansi::new(false).render(&tokens),
+            "\x1b[33mma[2Jin $\x1b[39m"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! The bash backend: `PS1` text.
+//!
+//! Escape sequences are wrapped in `\[` and `\]` so readline knows they take no room.
+//! Bash decodes backslash escapes in `PS1` and then, with `promptvars` set (the
+//! default), expands it like a double-quoted string, so literal `$`, backticks and
+//! backslashes are escaped for both steps. `slick.bash` exports whether `promptvars`
+//! is set.
+use super::{Backend, ansi::Ansi};
+use crate::{get_env_var, layout::Text, style::Style};
+
+const INTERNAL_PROMPTVARS_ENV: &str = "_SLICK_PROMPT_BASH_PROMPTVARS";
+
+/// Prints bash `PS1` text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bash {
+    ansi: Ansi,
+    promptvars: bool,
+}
+
+impl Bash {
+    #[must_use]
+    pub const fn new(promptvars: bool, truecolor: bool) -> Self {
+        Self {
+            ansi: Ansi::new(truecolor),
+            promptvars,
+        }
+    }
+
+    /// The backend for the `promptvars` state `slick.bash` exported and the terminal's
+    /// colors.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new(
+            get_env_var(INTERNAL_PROMPTVARS_ENV) != "0",
+            crate::style::truecolor(),
+        )
+    }
+}
+
+impl Backend for Bash {
+    fn escape(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for character in text.chars() {
+            match character {
+                // `\\` decodes to a backslash, which expansion then removes again.
+                '\\' if self.promptvars => escaped.push_str(r"\\\\"),
+                '\\' => escaped.push_str(r"\\"),
+                '$' | '`' if self.promptvars => {
+                    escaped.push_str(r"\\");
+                    escaped.push(character);
+                }
+                character if character.is_control() => {}
+                character => escaped.push(character),
+            }
+        }
+        escaped
+    }
+
+    fn non_printing(&self, sequence: &str) -> String {
+        if sequence.is_empty() {
+            String::new()
+        } else {
+            format!(r"\[{sequence}\]")
+        }
+    }
+
+    fn style_start(&self, style: &Style) -> String {
+        self.non_printing(&self.ansi.style_start(style))
+    }
+
+    fn style_end(&self, style: &Style) -> String {
+        self.non_printing(&self.ansi.style_end(style))
+    }
+
+    fn reset_color(&self) -> String {
+        self.non_printing(&self.ansi.reset_color())
+    }
+
+    fn value(&self, text: &Text) -> String {
+        match text {
+            Text::UserName => r"\u",
+            Text::HostName => r"\h",
+            Text::Directory => r"\w",
+            Text::Clock => r"\t",
+            Text::Literal(_) | Text::Verbatim(_) | Text::ResetColor => "",
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bash;
+    use crate::{backend::Backend, style::Style};
+
+    #[test]
+    fn test_escape_for_both_promptvars_states() {
+        let cases = [
+            ("plain %n text", "plain %n text", "plain %n text"),
+            ("$(id)", r"\\$(id)", "$(id)"),
+            ("`id`", r"\\`id\\`", "`id`"),
+            (r"a\b", r"a\\\\b", r"a\\b"),
+            ("new\nline", "newline", "newline"),
+        ];
+
+        for (input, promptvars, no_promptvars) in cases {
+            assert_eq!(
+                Bash::new(true, false).escape(input),
+                promptvars,
+                "{input:?}"
+            );
+            assert_eq!(
+                Bash::new(false, false).escape(input),
+                no_promptvars,
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_escape_sequences_are_marked_non_printing() {
+        let bash = Bash::new(true, false);
+        let style = Style::parse("bold 74").unwrap_or_default();
+
+        assert_eq!(bash.style_start(&style), "\\[\x1b[38;5;74;1m\\]");
+        assert_eq!(bash.style_end(&style), "\\[\x1b[22m\\]");
+        assert_eq!(bash.style_end(&Style::default()), "");
+        assert_eq!(bash.cursor_shape(6), "\\[\x1b[6 q\\]");
+    }
+}
@@ -0,0 +1,130 @@
+//! The JSON backend: the laid-out segments as data.
+//!
+//! The prompt is printed as an array with one object per token:
+//!
+//! ```json
+//! [
+//!   {"role":"path","text":"~/src/slick","style":"74","block":true,
+//!    "spans":[{"text":"~/src/slick","style":"74"}]},
+//!   {"role":"space","text":" "},
+//!   {"role":"newline","text":"\n"}
+//! ]
+//! ```
+//!
+//! `style` is the normalized style spec of the last styled span (empty for unstyled
+//! text) and `text` has no escapes; values such as the user name and the current directory are filled in.
+use super::Backend;
+use crate::{
+    layout::{Piece, Token},
+    settings::PowerlineSettings,
+    style::Style,
+};
+use serde_json::{Value, json};
+
+/// Prints the segments as JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Json;
+
+impl Json {
+    fn segment(self, piece: &Piece) -> Value {
+        let spans: Vec<(String, &Style)> = piece
+            .spans
+            .iter()
+            .map(|span| (self.text(&span.text), &span.style))
+            .filter(|(text, _)| !text.is_empty())
+            .collect();
+
+        json!({
+            "role": piece.role,
+            "text": spans.iter().map(|(text, _)| text.as_str()).collect::<String>(),
+            "style": spans
+                .iter()
+                .rev()
+                .find(|(_, style)| **style != Style::default())
+                .map(|(_, style)| style.to_string())
+                .unwrap_or_default(),
+            "block": piece.block,
+            "spans": spans
+                .iter()
+                .map(|(text, style)| json!({"text": text, "style": style.to_string()}))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Backend for Json {
+    fn escape(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn non_printing(&self, _sequence: &str) -> String {
+        String::new()
+    }
+
+    fn style_start(&self, _style: &Style) -> String {
+        String::new()
+    }
+
+    fn style_end(&self, _style: &Style) -> String {
+        String::new()
+    }
+
+    fn reset_color(&self) -> String {
+        String::new()
+    }
+
+    fn render(&self, tokens: &[Token]) -> String {
+        let values: Vec<Value> = tokens
+            .iter()
+            .map(|token| match token {
+                Token::Piece(piece) => self.segment(piece),
+                Token::Space(space) => json!({"role": "space", "text": space}),
+                Token::Newline => json!({"role": "newline", "text": "\n"}),
+            })
+            .collect();
+        Value::Array(values).to_string()
+    }
+
+    /// Blocks are a matter of drawing, so the segments are the same as without them.
+    fn render_powerline(&self, tokens: &[Token], _settings: &PowerlineSettings) -> String {
+        self.render(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+    use crate::{
+        backend::Backend,
+        layout::{Piece, Role, Text, Token},
+        style::Style,
+    };
+
+    #[test]
+    fn test_tokens_become_json_objects() {
+        let green = Style::parse("2").unwrap_or_default();
+        let yellow = Style::parse("bold 3").unwrap_or_default();
+        let red = Style::parse("1").unwrap_or_default();
+        let tokens = [
+            Token::Piece(
+                Piece::new(Role::GitBranch, &green, Text::Verbatim("> ".to_string()))
+                    .with(&yellow, "$(main)"),
+            ),
+            Token::Newline,
+            Token::Piece(
+                Piece::new(Role::Symbol, &red, Text::Verbatim("$".to_string()))
+                    .with(&Style::default(), Text::ResetColor),
+            ),
+        ];
+
+        assert_eq!(
+            Json.render(&tokens),
+            concat!(
+                r#"[{"block":true,"role":"git_branch","spans":[{"style":"2","text":"> "},"#,
+                r#"{"style":"bold 3","text":"$(main)"}],"style":"bold 3","text":"> $(main)"},"#,
+                r#"{"role":"newline","text":"\n"},"#,
+                r#"{"block":true,"role":"symbol","spans":[{"style":"1","text":"$"}],"style":"1","text":"$"}]"#,
+            )
+        );
+    }
+}
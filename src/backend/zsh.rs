@@ -0,0 +1,269 @@
+//! The zsh backend: prompt escapes for `PROMPT` and `RPROMPT`.
+//!
+//! `slick.zsh` renders the prompt with `PROMPT_SUBST` set, so literal text must not
+//! expand. The loader exports the `psvar` slots holding `$`, a backtick and a
+//! backslash, and literal text refers to those slots (`%11v`); without them the
+//! characters are escaped with a backslash.
+use super::Backend;
+use crate::{
+    get_env_var,
+    layout::Text,
+    style::{self, Style},
+};
+use std::fmt::Write as _;
+
+const INTERNAL_DOLLAR_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_DOLLAR";
+const INTERNAL_BACKTICK_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_BACKTICK";
+const INTERNAL_BACKSLASH_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_BACKSLASH";
+
+const ITALIC_ON: &str = "\x1b[3m";
+const ITALIC_OFF: &str = "\x1b[23m";
+
+/// How `$`, backticks and backslashes in literal text are kept from expanding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptLiteralEncoding {
+    Backslash,
+    Psvar {
+        dollar: usize,
+        backtick: usize,
+        backslash: usize,
+    },
+}
+
+fn parse_psvar_index(value: &str) -> Option<usize> {
+    value.parse::<usize>().ok().filter(|index| *index > 0)
+}
+
+fn prompt_literal_encoding(dollar: &str, backtick: &str, backslash: &str) -> PromptLiteralEncoding {
+    match (
+        parse_psvar_index(dollar),
+        parse_psvar_index(backtick),
+        parse_psvar_index(backslash),
+    ) {
+        (Some(dollar), Some(backtick), Some(backslash)) => PromptLiteralEncoding::Psvar {
+            dollar,
+            backtick,
+            backslash,
+        },
+        _ => PromptLiteralEncoding::Backslash,
+    }
+}
+
+fn current_prompt_literal_encoding() -> PromptLiteralEncoding {
+    prompt_literal_encoding(
+        &get_env_var(INTERNAL_DOLLAR_PSVAR_ENV),
+        &get_env_var(INTERNAL_BACKTICK_PSVAR_ENV),
+        &get_env_var(INTERNAL_BACKSLASH_PSVAR_ENV),
+    )
+}
+
+pub(crate) fn escape_prompt_literal(segment: &str, encoding: PromptLiteralEncoding) -> String {
+    let mut escaped = String::with_capacity(segment.len());
+    for character in segment.chars() {
+        match character {
+            '%' => escaped.push_str("%%"),
+            '\\' | '$' | '`' if encoding == PromptLiteralEncoding::Backslash => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '$' => {
+                if let PromptLiteralEncoding::Psvar { dollar, .. } = encoding {
+                    let _ = write!(escaped, "%{dollar}v");
+                }
+            }
+            '`' => {
+                if let PromptLiteralEncoding::Psvar { backtick, .. } = encoding {
+                    let _ = write!(escaped, "%{backtick}v");
+                }
+            }
+            '\\' => {
+                if let PromptLiteralEncoding::Psvar { backslash, .. } = encoding {
+                    let _ = write!(escaped, "%{backslash}v");
+                }
+            }
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Prints zsh prompt escapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zsh {
+    encoding: PromptLiteralEncoding,
+    truecolor: bool,
+}
+
+impl Zsh {
+    #[must_use]
+    pub const fn new(encoding: PromptLiteralEncoding, truecolor: bool) -> Self {
+        Self {
+            encoding,
+            truecolor,
+        }
+    }
+
+    /// The backend for the encoding `slick.zsh` exported and the terminal's colors.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new(current_prompt_literal_encoding(), style::truecolor())
+    }
+}
+
+impl Backend for Zsh {
+    fn escape(&self, text: &str) -> String {
+        escape_prompt_literal(text, self.encoding)
+    }
+
+    fn non_printing(&self, sequence: &str) -> String {
+        format!("%{{{sequence}%}}")
+    }
+
+    fn style_start(&self, style: &Style) -> String {
+        let mut start = String::new();
+        if let Some(fg) = &style.fg {
+            let _ = write!(start, "%F{{{}}}", fg.render(self.truecolor));
+        }
+        if let Some(bg) = &style.bg {
+            let _ = write!(start, "%K{{{}}}", bg.render(self.truecolor));
+        }
+        if style.bold {
+            start.push_str("%B");
+        }
+        if style.underline {
+            start.push_str("%U");
+        }
+        if style.italic {
+            start.push_str(&self.non_printing(ITALIC_ON));
+        }
+        start
+    }
+
+    fn style_end(&self, style: &Style) -> String {
+        let mut end = String::new();
+        if style.italic {
+            end.push_str(&self.non_printing(ITALIC_OFF));
+        }
+        if style.underline {
+            end.push_str("%u");
+        }
+        if style.bold {
+            end.push_str("%b");
+        }
+        if style.bg.is_some() {
+            end.push_str("%k");
+        }
+        end
+    }
+
+    fn reset_color(&self) -> String {
+        "%f".to_string()
+    }
+
+    fn value(&self, text: &Text) -> String {
+        match text {
+            Text::UserName => "%n",
+            Text::HostName => "%m",
+            Text::Directory => "%~",
+            Text::Clock => "%*",
+            Text::Literal(_) | Text::Verbatim(_) | Text::ResetColor => "",
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PromptLiteralEncoding, Zsh, escape_prompt_literal, prompt_literal_encoding};
+    use crate::{backend::Backend, style::Style};
+
+    fn style(spec: &str) -> Style {
+        Style::parse(spec).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_escape_prompt_literal() {
+        let psvar = PromptLiteralEncoding::Psvar {
+            dollar: 11,
+            backtick: 12,
+            backslash: 13,
+        };
+        let cases = [
+            ("plain text", "plain text", "plain text"),
+            ("%n", "%%n", "%%n"),
+            ("$(id)", r"\$(id)", "%11v(id)"),
+            ("`id`", r"\`id\`", "%12vid%12v"),
+            (r"\$(id)", r"\\\$(id)", "%13v%11v(id)"),
+            (
+                r"before %n $(id) `id` \ after",
+                r"before %%n \$(id) \`id\` \\ after",
+                r"before %%n %11v(id) %12vid%12v %13v after",
+            ),
+        ];
+
+        for (input, direct, canonical) in cases {
+            assert_eq!(
+                escape_prompt_literal(input, PromptLiteralEncoding::Backslash),
+                direct,
+                "direct caller, input: {input:?}"
+            );
+            assert_eq!(
+                escape_prompt_literal(input, psvar),
+                canonical,
+                "canonical loader, input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_prompt_literal_encoding_defaults_safely() {
+        let cases = [
+            ("", "", "", PromptLiteralEncoding::Backslash),
+            ("1", "2", "", PromptLiteralEncoding::Backslash),
+            ("1", "invalid", "3", PromptLiteralEncoding::Backslash),
+            (
+                "11",
+                "12",
+                "13",
+                PromptLiteralEncoding::Psvar {
+                    dollar: 11,
+                    backtick: 12,
+                    backslash: 13,
+                },
+            ),
+        ];
+
+        for (dollar, backtick, backslash, expected) in cases {
+            assert_eq!(
+                prompt_literal_encoding(dollar, backtick, backslash),
+                expected,
+                "values: {dollar:?}, {backtick:?}, {backslash:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_color_renders_like_before() {
+        let zsh = Zsh::new(PromptLiteralEncoding::Backslash, false);
+        let plain = style("74");
+        assert_eq!(zsh.style_start(&plain), "%F{74}");
+        assert_eq!(zsh.style_end(&plain), "");
+    }
+
+    #[test]
+    fn test_style_renders_zsh_sequences() {
+        let loud = style("bold underline italic #ff8800 on 52");
+        assert_eq!(
+            Zsh::new(PromptLiteralEncoding::Backslash, true).style_start(&loud),
+            "%F{#ff8800}%K{52}%B%U%{\x1b[3m%}"
+        );
+        assert_eq!(
+            Zsh::new(PromptLiteralEncoding::Backslash, true).style_end(&loud),
+            "%{\x1b[23m%}%u%b%k"
+        );
+        assert_eq!(
+            Zsh::new(PromptLiteralEncoding::Backslash, false).style_start(&loud),
+            "%F{208}%K{52}%B%U%{\x1b[3m%}"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use clap::{Arg, ArgAction, Command};
-use slick::{precmd, prompt};
+use slick::{config, daemon, init, precmd, prompt, serve};
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -12,84 +12,55 @@ async fn main() {
                 .override_help("Executed before each prompt."),
         )
         .subcommand(
-            Command::new("prompt")
-                .about("prompt")
-                .override_help(
-                    r#"Builds the prompt, render is affected by this environment vars:
-
-The default values are:
-
-    SLICK_PROMPT_AWS_COLOR=7
-    SLICK_PROMPT_CMD_MAX_EXEC_TIME=5
-    SLICK_PROMPT_CURSOR_SHAPE=dynamic (2 in command/visual mode, 6 otherwise; empty disables)
-    SLICK_PROMPT_DEVPOD_COLOR=7
-    SLICK_PROMPT_DEVPOD_SYMBOL=
-    SLICK_PROMPT_ERROR_COLOR=196
-    SLICK_PROMPT_GIT_ACTION_COLOR=3
-    SLICK_PROMPT_GIT_AUTH_COLOR=red
-    SLICK_PROMPT_GIT_AUTH_SYMBOL=🔒
-    SLICK_PROMPT_GIT_BRANCH_COLOR=3
-    SLICK_PROMPT_GIT_BRANCH_SYMBOL= (set to "" to disable)
-    SLICK_PROMPT_GIT_BRANCH_SYMBOL_COLOR=2
-    SLICK_PROMPT_GIT_FETCH=1 (if set to 0 disables git fetch)
-    SLICK_PROMPT_GIT_FETCH_TIMEOUT=5 (seconds to wait for the background git fetch)
-    SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR=160
-    SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR=160 (deprecated fallback)
-    SLICK_PROMPT_GIT_OFFLINE_COLOR=3
-    SLICK_PROMPT_GIT_OFFLINE_SYMBOL=⚠ (shown when the remote cannot be reached)
-    SLICK_PROMPT_GIT_REMOTE_COLOR=6
-    SLICK_PROMPT_GIT_REMOTE_AHEAD=⇡
-    SLICK_PROMPT_GIT_REMOTE_BEHIND=⇣
-    SLICK_PROMPT_GIT_STAGED_COLOR=7
-    SLICK_PROMPT_GIT_STATUS_COLOR=5
-    SLICK_PROMPT_K8S_COLOR=7
-    SLICK_PROMPT_GIT_UNAME_COLOR=8
-    SLICK_PROMPT_NON_BREAKING_SPACE="\u{a0}"
-    SLICK_PROMPT_PATH_COLOR=74
-    SLICK_PROMPT_PYTHON_ENV_COLOR=7
-    SLICK_PROMPT_ROOT_COLOR=1
-    SLICK_PROMPT_ROOT_SYMBOL=#
-    SLICK_PROMPT_SSH_COLOR=8
-    SLICK_PROMPT_SHORT_CONTEXT=0 (if set to 1 shortens context markers to icons/labels)
-    SLICK_PROMPT_SHORT_PATH=0 (if set to 1 uses a compact path like ~/p/r/slick)
-    SLICK_PROMPT_SHORT_TIMESTAMP=0 (if set to 1 uses %H:%M:%S in transient prompt)
-    SLICK_PROMPT_SYMBOL="$"
-    SLICK_PROMPT_SYMBOL_COLOR=5
-    SLICK_PROMPT_TIME_ELAPSED_COLOR=3
-    SLICK_PROMPT_TRANSIENT=1
-    SLICK_PROMPT_TOOLBOX_COLOR=3
-    SLICK_PROMPT_TOOLBOX_SYMBOL=▣
-    SLICK_PROMPT_VICMD_COLOR=3
-    SLICK_PROMPT_VICMD_SYMBOL=">"
-    PIPENV_ACTIVE_COLOR=7 (legacy fallback)
-"#,
-                )
+            Command::new("config")
+                .about("Print the effective configuration and where each value came from")
                 .arg(
-                    Arg::new("transient")
-                        .long("transient")
-                        .action(ArgAction::SetTrue),
+                    Arg::new("name")
+                        .value_name("NAME")
+                        .help("Only print this setting (e.g. SLICK_PROMPT_GIT_FETCH)"),
+                ),
+        )
+        .subcommand(
+            Command::new("init")
+                .about("Print the shell integration, e.g. eval \"$(slick init zsh)\"")
+                .arg(
+                    Arg::new("shell")
+                        .value_name("SHELL")
+                        .required(true)
+                        .value_parser(init::SHELLS.to_vec()),
                 )
                 .arg(
-                    Arg::new("transient_timestamp")
-                        .long("transient-timestamp")
-                        .num_args(1),
+                    Arg::new("no_transient")
+                        .long("no-transient")
+                        .action(ArgAction::SetTrue)
+                        .help("Disable the transient prompt"),
                 )
-                .arg(Arg::new("last_return_code").short('r').num_args(1))
-                .arg(Arg::new("keymap").short('k').num_args(1))
-                .arg(Arg::new("data").short('d').num_args(1))
-                .arg(Arg::new("time").short('t').num_args(1))
                 .arg(
-                    Arg::new("elapsed")
-                        .short('e')
-                        .num_args(1)
-                        .allow_negative_numbers(true),
+                    Arg::new("no_cursor")
+                        .long("no-cursor")
+                        .action(ArgAction::SetTrue)
+                        .help("Leave the cursor shape alone"),
                 ),
         )
+        .subcommand(
+            Command::new("daemon")
+                .about("Share repository state between shells over a Unix socket"),
+        )
+        .subcommand(prompt::command())
+        .subcommand(
+            Command::new("serve")
+                .about("Render prompts for one shell over stdin and stdout")
+                .override_help(serve::HELP),
+        )
         .get_matches();
 
     match matches.subcommand() {
+        Some(("config", sub_m)) => config::display(sub_m),
+        Some(("daemon", _)) => daemon::run(),
+        Some(("init", sub_m)) => init::display(sub_m),
         Some(("precmd", _)) => precmd::render().await,
         Some(("prompt", sub_m)) => prompt::display(sub_m),
+        Some(("serve", _)) => serve::run(),
         _ => (),
     }
 }
@@ -0,0 +1,941 @@
+//! Layered prompt configuration.
+//!
+//! Every setting can come from three places, in order of precedence:
+//!
+//! 1. its `SLICK_PROMPT_*` environment variable,
+//! 2. `config.toml` in `$XDG_CONFIG_HOME/slick` (or `~/.config/slick`),
+//! 3. the built-in default listed in [`SETTINGS`].
+//!
+//! The file uses the same names as the environment variables, lowercased and
+//! without the `SLICK_PROMPT_` prefix, grouped into per-segment tables:
+//!
+//! ```toml
+//! symbol = "❯"
+//!
+//! [git]
+//! fetch = false
+//! branch_color = 3
+//!
+//! [path]
+//! short = true
+//! ```
+use crate::{
+    get_env, get_env_source, get_env_var, settings,
+    theme::{self, THEMES, Theme, find_theme},
+};
+use clap::ArgMatches;
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Write as _},
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+use toml::{Table, Value};
+
+/// A single prompt setting and where to find it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setting {
+    /// The environment variable that overrides every other source.
+    pub name: &'static str,
+    /// The dotted key of the setting in `config.toml` (e.g. `git.fetch`).
+    pub key: &'static str,
+    /// The value used when neither the environment nor the file set it.
+    pub default: &'static str,
+    /// A short note shown next to the default in `slick prompt --help`, if any.
+    pub description: &'static str,
+}
+
+const fn setting(
+    name: &'static str,
+    key: &'static str,
+    default: &'static str,
+    description: &'static str,
+) -> Setting {
+    Setting {
+        name,
+        key,
+        default,
+        description,
+    }
+}
+
+/// Every setting slick understands, sorted by environment variable name.
+pub const SETTINGS: &[Setting] = &[
+    setting("SLICK_PROMPT_AWS_COLOR", "context.aws_color", "7", ""),
+    setting(
+        "SLICK_PROMPT_CMD_MAX_EXEC_TIME",
+        "cmd_max_exec_time",
+        "5",
+        "seconds a command must run before its duration is shown",
+    ),
+    setting(
+        "SLICK_PROMPT_CURSOR_SHAPE",
+        "cursor_shape",
+        "dynamic",
+        "2 in command/visual mode, 6 otherwise; empty disables",
+    ),
+    setting("SLICK_PROMPT_DEVPOD_COLOR", "context.devpod_color", "7", ""),
+    setting(
+        "SLICK_PROMPT_DEVPOD_SYMBOL",
+        "context.devpod_symbol",
+        "\u{f487}",
+        "",
+    ),
+    setting("SLICK_PROMPT_ERROR_COLOR", "error_color", "196", ""),
+    setting(
+        "SLICK_PROMPT_FORMAT",
+        "format",
+        "{identity} {context} {git_user} {path} {git} {elapsed}{newline}{symbol}",
+        "layout of the prompt",
+    ),
+    setting("SLICK_PROMPT_GIT_ACTION_COLOR", "git.action_color", "3", ""),
+    setting("SLICK_PROMPT_GIT_AUTH_COLOR", "git.auth_color", "red", ""),
+    setting("SLICK_PROMPT_GIT_AUTH_SYMBOL", "git.auth_symbol", "🔒", ""),
+    setting("SLICK_PROMPT_GIT_BRANCH_COLOR", "git.branch_color", "3", ""),
+    setting(
+        "SLICK_PROMPT_GIT_BRANCH_SYMBOL",
+        "git.branch_symbol",
+        "\u{e0a0}",
+        "set to \"\" to disable",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_BRANCH_SYMBOL_COLOR",
+        "git.branch_symbol_color",
+        "2",
+        "",
+    ),
+    setting("SLICK_PROMPT_GIT_CACHED_COLOR", "git.cached_color", "8", ""),
+    setting(
+        "SLICK_PROMPT_GIT_CACHED_SYMBOL",
+        "git.cached_symbol",
+        "\u{25cc}",
+        "shown with the git segments while they show the last saved data",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DATA_ERROR_SYMBOL",
+        "git.data_error_symbol",
+        "\u{2049}",
+        "shown with the git segments when the precmd data cannot be read",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIFFSTAT",
+        "git.diffstat",
+        "0",
+        "if set to 1 shows the lines added and removed since HEAD",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIFFSTAT_COLOR",
+        "git.diffstat_color",
+        "8",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIFFSTAT_DELETIONS_SYMBOL",
+        "git.diffstat_deletions_symbol",
+        "\u{2212}",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIFFSTAT_INSERTIONS_SYMBOL",
+        "git.diffstat_insertions_symbol",
+        "+",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIFFSTAT_MAX_FILES",
+        "git.diffstat_max_files",
+        "1000",
+        "changed files past which the line counts are skipped, 0 for no limit",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIRTY_SYMBOL",
+        "git.dirty_symbol",
+        "\u{25cf}",
+        "shown instead of the status counts in dirty-only mode",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_DIRTY_THRESHOLD",
+        "git.dirty_threshold",
+        "0",
+        "tracked files from which git status only looks for the first change, 0 never does",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_FETCH",
+        "git.fetch",
+        "1",
+        "if set to 0 disables git fetch",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_FETCH_TIMEOUT",
+        "git.fetch_timeout",
+        "5",
+        "seconds to wait for the background git fetch",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR",
+        "git.main_branch_color",
+        "160",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_OFFLINE_COLOR",
+        "git.offline_color",
+        "3",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_OFFLINE_SYMBOL",
+        "git.offline_symbol",
+        "\u{26a0}",
+        "shown when the remote cannot be reached",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_REMOTE_AHEAD",
+        "git.remote_ahead",
+        "\u{21e1}",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_REMOTE_BEHIND",
+        "git.remote_behind",
+        "\u{21e3}",
+        "",
+    ),
+    setting("SLICK_PROMPT_GIT_REMOTE_COLOR", "git.remote_color", "6", ""),
+    setting("SLICK_PROMPT_GIT_STAGED_COLOR", "git.staged_color", "7", ""),
+    setting(
+        "SLICK_PROMPT_GIT_STAGED_FORMAT",
+        "git.staged_format",
+        "M {modified} D {deleted} R {renamed} T {typechange} A {added}",
+        "layout of the staged counts",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_ADDED_COLOR",
+        "git.status_added_color",
+        "",
+        "added files; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_CACHE",
+        "git.status_cache",
+        "1",
+        "if set to 0, git status scans the work tree on every prompt; repositories with submodules always do",
+    ),
+    setting("SLICK_PROMPT_GIT_STATUS_COLOR", "git.status_color", "5", ""),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_CONFLICTED_COLOR",
+        "git.status_conflicted_color",
+        "",
+        "conflicted files; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_DELETED_COLOR",
+        "git.status_deleted_color",
+        "",
+        "deleted files; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_FALLBACK",
+        "git.status_fallback",
+        "0",
+        "if set to 1, a repository whose status misses the deadline skips untracked files for a day",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_FORMAT",
+        "git.status_format",
+        "UU {conflicted} M {modified} D {deleted} R {renamed} T {typechange} A {added} ?? {untracked}",
+        "layout of the unstaged, untracked and conflicted counts",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_MODIFIED_COLOR",
+        "git.status_modified_color",
+        "",
+        "modified files; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_RENAMED_COLOR",
+        "git.status_renamed_color",
+        "",
+        "renamed files; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_TIMEOUT",
+        "git.status_timeout",
+        "5",
+        "seconds to wait for git status, 0 waits as long as it takes",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_TIMEOUT_SYMBOL",
+        "git.status_timeout_symbol",
+        "\u{2026}",
+        "shown instead of the status when it missed the deadline",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_TYPECHANGE_COLOR",
+        "git.status_typechange_color",
+        "",
+        "files that changed type; empty uses the segment color",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_STATUS_UNTRACKED_COLOR",
+        "git.status_untracked_color",
+        "",
+        "untracked files; empty uses the segment color",
+    ),
+    setting("SLICK_PROMPT_GIT_UNAME_COLOR", "git.uname_color", "8", ""),
+    setting(
+        "SLICK_PROMPT_GIT_WATCH",
+        "git.watch",
+        "0",
+        "if set to 1 keeps refreshing the git segment while the prompt waits",
+    ),
+    setting(
+        "SLICK_PROMPT_GIT_WATCH_TIMEOUT",
+        "git.watch_timeout",
+        "600",
+        "seconds without repository changes before the watch stops",
+    ),
+    setting("SLICK_PROMPT_K8S_COLOR", "context.k8s_color", "7", ""),
+    setting(
+        "SLICK_PROMPT_NON_BREAKING_SPACE",
+        "non_breaking_space",
+        "\u{a0}",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_NO_GIT_UNAME",
+        "git.hide_uname",
+        "0",
+        "if set to 1 hides the git user name",
+    ),
+    setting("SLICK_PROMPT_PATH_COLOR", "path.color", "74", ""),
+    setting(
+        "SLICK_PROMPT_POWERLINE",
+        "powerline.enabled",
+        "0",
+        "if set to 1 draws segments as colored blocks",
+    ),
+    setting(
+        "SLICK_PROMPT_POWERLINE_SEPARATOR",
+        "powerline.separator",
+        "\u{e0b0}",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_POWERLINE_TEXT_COLOR",
+        "powerline.text_color",
+        "0",
+        "text color inside powerline blocks",
+    ),
+    setting(
+        "SLICK_PROMPT_PYTHON_ENV_COLOR",
+        "context.python_env_color",
+        "7",
+        "falls back to PIPENV_ACTIVE_COLOR inside pipenv",
+    ),
+    setting(
+        "SLICK_PROMPT_RIGHT_FORMAT",
+        "right_format",
+        "",
+        "layout of the right-hand prompt; empty disables",
+    ),
+    setting("SLICK_PROMPT_ROOT_COLOR", "root_color", "1", ""),
+    setting("SLICK_PROMPT_ROOT_SYMBOL", "root_symbol", "#", ""),
+    setting(
+        "SLICK_PROMPT_SERVE",
+        "serve",
+        "0",
+        "if set to 1 zsh renders through one slick serve per shell; environment only",
+    ),
+    setting(
+        "SLICK_PROMPT_SHORT_CONTEXT",
+        "context.short",
+        "0",
+        "if set to 1 shortens context markers to icons/labels",
+    ),
+    setting(
+        "SLICK_PROMPT_SHORT_PATH",
+        "path.short",
+        "0",
+        "if set to 1 uses a compact path like ~/p/r/slick",
+    ),
+    setting(
+        "SLICK_PROMPT_SHORT_TIMESTAMP",
+        "transient.short_timestamp",
+        "0",
+        "if set to 1 uses %H:%M:%S in transient prompt",
+    ),
+    setting("SLICK_PROMPT_SSH_COLOR", "ssh_color", "8", ""),
+    setting("SLICK_PROMPT_SYMBOL", "symbol", "$", ""),
+    setting("SLICK_PROMPT_SYMBOL_COLOR", "symbol_color", "5", ""),
+    setting(
+        "SLICK_PROMPT_THEME",
+        "theme",
+        "default",
+        "default, ascii, nerd, minimal or high-contrast",
+    ),
+    setting(
+        "SLICK_PROMPT_TIME_ELAPSED_COLOR",
+        "time_elapsed_color",
+        "3",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_TOOLBOX_COLOR",
+        "context.toolbox_color",
+        "3",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_TOOLBOX_SYMBOL",
+        "context.toolbox_symbol",
+        "▣",
+        "",
+    ),
+    setting(
+        "SLICK_PROMPT_TRANSIENT",
+        "transient.enabled",
+        "1",
+        "if set to 0 disables the transient prompt",
+    ),
+    setting(
+        "SLICK_PROMPT_TRANSIENT_FORMAT",
+        "transient.format",
+        "{identity} {timestamp} {context} {path} {git_branch} {symbol}",
+        "layout of the transient prompt",
+    ),
+    setting("SLICK_PROMPT_VICMD_COLOR", "vicmd_color", "3", ""),
+    setting("SLICK_PROMPT_VICMD_SYMBOL", "vicmd_symbol", ">", ""),
+];
+
+/// Deprecated environment variables that are still honored for a setting.
+const ENV_ALIASES: &[(&str, &str)] = &[(
+    "SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR",
+    "SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR",
+)];
+
+/// Returns the setting registered under `name`, following deprecated aliases.
+#[must_use]
+pub fn find_setting(name: &str) -> Option<(usize, &'static Setting)> {
+    let name = ENV_ALIASES
+        .iter()
+        .find_map(|(current, alias)| (*alias == name).then_some(*current))
+        .unwrap_or(name);
+
+    SETTINGS
+        .iter()
+        .enumerate()
+        .find(|(_, setting)| setting.name == name)
+}
+
+/// Where the effective value of a setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The built-in default.
+    Default,
+    /// The setting's environment variable.
+    Env,
+    /// A deprecated environment variable that is still honored for the setting.
+    DeprecatedEnv(&'static str),
+    /// The config file.
+    ConfigFile,
+    /// The named theme preset.
+    Theme(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::Env => f.write_str("env"),
+            Self::DeprecatedEnv(alias) => write!(f, "env {alias}, deprecated"),
+            Self::ConfigFile => f.write_str("config file"),
+            Self::Theme(name) => write!(f, "theme {name}"),
+        }
+    }
+}
+
+/// A configuration problem that was worked around instead of failing the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The setting's environment variable, or the config file path for file-level problems.
+    pub subject: String,
+    pub message: String,
+}
+
+impl Warning {
+    /// A value that could not be parsed and was replaced by `fallback`.
+    #[must_use]
+    pub fn invalid_value(
+        name: &str,
+        source: Source,
+        value: &str,
+        expected: &str,
+        fallback: &str,
+    ) -> Self {
+        Self {
+            subject: name.to_string(),
+            message: format!(
+                "invalid value {value:?} ({source}), expected {expected}; using {fallback}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.subject, self.message)
+    }
+}
+
+/// The values found in a parsed config file, keyed by environment variable name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigFile {
+    values: HashMap<&'static str, String>,
+    ignored: Vec<String>,
+}
+
+impl ConfigFile {
+    /// Parses the contents of a `config.toml` file.
+    ///
+    /// Keys that do not match a known setting and values that are not strings,
+    /// integers, floats or booleans are skipped and listed in [`ConfigFile::ignored`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the TOML parser message when `text` is not valid TOML.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = toml::from_str(text).map_err(|error| error.message().to_string())?;
+
+        let mut values = HashMap::new();
+        let mut ignored = Vec::new();
+        for (key, value) in flatten(&table) {
+            match SETTINGS.iter().find(|setting| setting.key == key) {
+                Some(setting) => match scalar_to_string(value) {
+                    Some(value) => {
+                        values.insert(setting.name, value);
+                    }
+                    None => {
+                        ignored.push(format!("unsupported {} value for {key}", value.type_str()));
+                    }
+                },
+                None => ignored.push(format!("unknown key {key}")),
+            }
+        }
+
+        Ok(Self { values, ignored })
+    }
+
+    /// Reads and parses the config file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message when the file cannot be read or is not valid TOML.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        Self::parse(&text)
+    }
+
+    /// Why each skipped entry of the file was skipped.
+    #[must_use]
+    pub fn ignored(&self) -> &[String] {
+        &self.ignored
+    }
+
+    /// The raw value the file sets for the setting `name`, if any.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// Flattens top-level keys and one level of tables into dotted keys.
+fn flatten(table: &Table) -> Vec<(String, &Value)> {
+    let mut entries = Vec::with_capacity(table.len());
+    for (key, value) in table {
+        if let Value::Table(section) = value {
+            for (nested, value) in section {
+                entries.push((format!("{key}.{nested}"), value));
+            }
+        } else {
+            entries.push((key.clone(), value));
+        }
+    }
+    entries
+}
+
+/// Converts a TOML scalar into the string form the environment variables use.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Integer(value) => Some(value.to_string()),
+        Value::Float(value) => Some(value.to_string()),
+        Value::Boolean(value) => Some(if *value { "1" } else { "0" }.to_string()),
+        Value::Datetime(_) | Value::Array(_) | Value::Table(_) => None,
+    }
+}
+
+/// Returns the path of the config file, whether or not it exists.
+///
+/// Uses `$XDG_CONFIG_HOME/slick/config.toml`, falling back to
+/// `$HOME/.config/slick/config.toml`.
+#[must_use]
+pub fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(base.join("slick").join("config.toml"))
+}
+
+/// Loads the config file if there is one.
+///
+/// A missing file is treated as empty. A malformed one is treated as empty too, so
+/// a broken config can never break the prompt itself, and a warning says why; so
+/// does every entry the file had to skip.
+#[must_use]
+pub fn load() -> (ConfigFile, Vec<Warning>) {
+    let Some(path) = config_path().filter(|path| path.is_file()) else {
+        return (ConfigFile::default(), Vec::new());
+    };
+
+    let subject = path.display().to_string();
+    match ConfigFile::load(&path) {
+        Ok(file) => {
+            let warnings = file
+                .ignored()
+                .iter()
+                .map(|message| Warning {
+                    subject: subject.clone(),
+                    message: message.clone(),
+                })
+                .collect();
+            (file, warnings)
+        }
+        Err(error) => (
+            ConfigFile::default(),
+            vec![Warning {
+                subject,
+                message: format!("ignored: {}", error.trim_end()),
+            }],
+        ),
+    }
+}
+
+/// Resolves the theme selected by `SLICK_PROMPT_THEME` or the file's `theme` key.
+///
+/// Unknown names select the default theme; [`crate::settings::Settings`] reports them.
+pub fn resolve_theme(
+    lookup_env: impl Fn(&str) -> Option<String>,
+    file: &ConfigFile,
+) -> &'static Theme {
+    find_setting("SLICK_PROMPT_THEME")
+        .map(|(_, setting)| resolve(setting, lookup_env, file, &theme::DEFAULT).0)
+        .and_then(|name| find_theme(&name))
+        .unwrap_or(&theme::DEFAULT)
+}
+
+/// Resolves the effective value of `setting`.
+///
+/// `lookup_env` returns the value of an environment variable, if set. The theme
+/// only replaces the built-in default.
+pub fn resolve(
+    setting: &Setting,
+    lookup_env: impl Fn(&str) -> Option<String>,
+    file: &ConfigFile,
+    theme: &Theme,
+) -> (String, Source) {
+    if let Some(value) = lookup_env(setting.name) {
+        return (value, Source::Env);
+    }
+
+    if let Some((value, alias)) = ENV_ALIASES
+        .iter()
+        .filter(|(current, _)| *current == setting.name)
+        .find_map(|(_, alias)| lookup_env(alias).map(|value| (value, *alias)))
+    {
+        return (value, Source::DeprecatedEnv(alias));
+    }
+
+    if let Some(value) = file.get(setting.name) {
+        return (value.to_string(), Source::ConfigFile);
+    }
+
+    if let Some(value) = theme.get(setting.name) {
+        return (value.to_string(), Source::Theme(theme.name));
+    }
+
+    (setting.default.to_string(), Source::Default)
+}
+
+/// Quotes `value` when it would be ambiguous unquoted (empty, or containing whitespace).
+fn quote_value(value: &str) -> String {
+    if value.is_empty() || value.chars().any(char::is_whitespace) {
+        format!("{value:?}")
+    } else {
+        value.to_string()
+    }
+}
+
+/// The help text of `slick prompt`, generated from [`SETTINGS`].
+#[must_use]
+pub fn prompt_help() -> String {
+    let mut help = String::from(
+        "Builds the prompt, render is affected by this environment vars:\n\nThe default values are:\n\n",
+    );
+    for setting in SETTINGS {
+        let _ = write!(
+            help,
+            "    {}={}",
+            setting.name,
+            quote_value(setting.default)
+        );
+        if !setting.description.is_empty() {
+            let _ = write!(help, " ({})", setting.description);
+        }
+        help.push('\n');
+    }
+    for (current, alias) in ENV_ALIASES {
+        let _ = writeln!(help, "    {alias} (deprecated fallback for {current})");
+    }
+    help.push_str("    PIPENV_ACTIVE_COLOR (legacy fallback for SLICK_PROMPT_PYTHON_ENV_COLOR)\n");
+    help.push_str("\nThemes (SLICK_PROMPT_THEME) replace some of the defaults above:\n\n");
+    for theme in THEMES {
+        let _ = writeln!(help, "    {:<14}{}", theme.name, theme.description);
+    }
+    let _ = write!(
+        help,
+        "\nEvery setting can also be set in {}; run `slick config` to see the effective values.\n",
+        config_path().map_or_else(
+            || "$XDG_CONFIG_HOME/slick/config.toml".to_string(),
+            |path| path.display().to_string()
+        )
+    );
+    help
+}
+
+/// Returns the effective value of `setting` and a description of where it came from.
+fn effective_value(setting: &Setting) -> (String, String) {
+    let value = get_env(setting.name);
+    let source = get_env_source(setting.name).unwrap_or(Source::Default);
+
+    // `PIPENV_ACTIVE_COLOR` only replaces the default, and only inside pipenv.
+    if setting.name == "SLICK_PROMPT_PYTHON_ENV_COLOR" && source == Source::Default {
+        let pipenv_color = get_env_var("PIPENV_ACTIVE_COLOR");
+        if !pipenv_color.is_empty() {
+            return (
+                pipenv_color,
+                "env PIPENV_ACTIVE_COLOR, deprecated; pipenv environments only".to_string(),
+            );
+        }
+    }
+
+    (value.to_string(), source.to_string())
+}
+
+/// Prints the effective configuration: every setting (or only `NAME`) with its value
+/// and source, followed by any warnings on stderr.
+///
+/// Exits with status 1 when a value was rejected or `NAME` is not a known setting.
+pub fn display(matches: &ArgMatches) {
+    let selected = match matches.get_one::<String>("name") {
+        Some(name) => {
+            let Some((_, setting)) = find_setting(name) else {
+                eprintln!("slick: unknown setting {name}");
+                exit(1)
+            };
+            Some(setting)
+        }
+        None => None,
+    };
+
+    let mut output = String::with_capacity(4096);
+    if selected.is_none() {
+        let _ = match config_path() {
+            Some(path) if path.is_file() => writeln!(output, "# config file: {}", path.display()),
+            Some(path) => writeln!(output, "# config file: {} (not found)", path.display()),
+            None => writeln!(output, "# config file: none ($HOME is not set)"),
+        };
+    }
+
+    for setting in SETTINGS
+        .iter()
+        .filter(|setting| selected.is_none_or(|selected| selected.name == setting.name))
+    {
+        let (value, source) = effective_value(setting);
+        let _ = writeln!(
+            output,
+            "{}={}  # {source}",
+            setting.name,
+            quote_value(&value)
+        );
+    }
+    print!("{output}");
+
+    let warnings: Vec<&Warning> = settings()
+        .warnings
+        .iter()
+        .filter(|warning| selected.is_none_or(|selected| selected.name == warning.subject))
+        .collect();
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    if !warnings.is_empty() {
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use super::{ConfigFile, SETTINGS, Source, find_setting, resolve, resolve_theme};
+    use crate::theme::{DEFAULT, find_theme};
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_settings_are_sorted_and_unique() {
+        for pair in SETTINGS.windows(2) {
+            if let [first, second] = pair {
+                assert!(
+                    first.name < second.name,
+                    "{} >= {}",
+                    first.name,
+                    second.name
+                );
+            }
+        }
+
+        for setting in SETTINGS {
+            let keys = SETTINGS.iter().filter(|other| other.key == setting.key);
+            assert_eq!(keys.count(), 1, "duplicate key {}", setting.key);
+        }
+    }
+
+    #[test]
+    fn test_parse_maps_tables_to_settings() {
+        let file = ConfigFile::parse(
+            r#"
+symbol = "❯"
+cmd_max_exec_time = 10
+
+[git]
+fetch = false
+branch_color = "yellow"
+
+[path]
+short = true
+
+[transient]
+enabled = false
+"#,
+        )
+        .expect("config should parse");
+
+        assert_eq!(file.get("SLICK_PROMPT_SYMBOL"), Some("❯"));
+        assert_eq!(file.get("SLICK_PROMPT_CMD_MAX_EXEC_TIME"), Some("10"));
+        assert_eq!(file.get("SLICK_PROMPT_GIT_FETCH"), Some("0"));
+        assert_eq!(file.get("SLICK_PROMPT_GIT_BRANCH_COLOR"), Some("yellow"));
+        assert_eq!(file.get("SLICK_PROMPT_SHORT_PATH"), Some("1"));
+        assert_eq!(file.get("SLICK_PROMPT_TRANSIENT"), Some("0"));
+        assert_eq!(file.get("SLICK_PROMPT_PATH_COLOR"), None);
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_keys_and_rejects_invalid_toml() {
+        let file = ConfigFile::parse("unknown = 1\n[git]\nnope = true\nfetch = [1]\n")
+            .expect("unknown keys should not fail parsing");
+        assert_eq!(file.get("SLICK_PROMPT_GIT_FETCH"), None);
+        assert_eq!(
+            file.ignored(),
+            [
+                "unsupported array value for git.fetch",
+                "unknown key git.nope",
+                "unknown key unknown",
+            ]
+        );
+
+        assert!(ConfigFile::parse("symbol = ").is_err());
+    }
+
+    #[test]
+    fn test_resolve_prefers_env_then_file_then_default() {
+        let (_, setting) = find_setting("SLICK_PROMPT_SYMBOL").expect("setting exists");
+        let file = ConfigFile::parse("symbol = \"❯\"").expect("config should parse");
+
+        assert_eq!(
+            resolve(setting, |_| Some(">".to_string()), &file, &DEFAULT),
+            (">".to_string(), Source::Env)
+        );
+        assert_eq!(
+            resolve(setting, no_env, &file, &DEFAULT),
+            ("❯".to_string(), Source::ConfigFile)
+        );
+        assert_eq!(
+            resolve(setting, no_env, &ConfigFile::default(), &DEFAULT),
+            ("$".to_string(), Source::Default)
+        );
+    }
+
+    #[test]
+    fn test_resolve_honors_deprecated_env_alias_over_file() {
+        let (_, setting) =
+            find_setting("SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR").expect("alias resolves");
+        assert_eq!(setting.name, "SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR");
+
+        let file = ConfigFile::parse("[git]\nmain_branch_color = 1").expect("config should parse");
+        let lookup =
+            |name: &str| (name == "SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR").then(|| "99".to_string());
+
+        assert_eq!(
+            resolve(setting, lookup, &file, &DEFAULT),
+            (
+                "99".to_string(),
+                Source::DeprecatedEnv("SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR")
+            )
+        );
+    }
+
+    #[test]
+    fn test_theme_sits_between_file_and_default() {
+        let nerd = find_theme("nerd").expect("nerd theme exists");
+        let (_, symbol) = find_setting("SLICK_PROMPT_SYMBOL").expect("setting exists");
+        let (_, path_color) = find_setting("SLICK_PROMPT_PATH_COLOR").expect("setting exists");
+        let file = ConfigFile::parse("symbol = \"❯\"").expect("config should parse");
+
+        assert_eq!(
+            resolve(symbol, |_| Some(">".to_string()), &file, nerd),
+            (">".to_string(), Source::Env)
+        );
+        assert_eq!(
+            resolve(symbol, no_env, &file, nerd),
+            ("❯".to_string(), Source::ConfigFile)
+        );
+        assert_eq!(
+            resolve(symbol, no_env, &ConfigFile::default(), nerd),
+            ("\u{f460}".to_string(), Source::Theme("nerd"))
+        );
+        assert_eq!(
+            resolve(path_color, no_env, &ConfigFile::default(), nerd),
+            ("74".to_string(), Source::Default)
+        );
+    }
+
+    #[test]
+    fn test_resolve_theme() {
+        let file = ConfigFile::parse("theme = \"minimal\"").expect("config should parse");
+        assert_eq!(resolve_theme(no_env, &file).name, "minimal");
+
+        let env = |name: &str| (name == "SLICK_PROMPT_THEME").then(|| "ascii".to_string());
+        assert_eq!(resolve_theme(env, &file).name, "ascii");
+
+        let unknown = |name: &str| (name == "SLICK_PROMPT_THEME").then(|| "nope".to_string());
+        assert_eq!(resolve_theme(unknown, &file), &DEFAULT);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{get_env, get_env_var, get_env_var_or};
+use crate::{
+    config::Source, get_env, get_env_source, get_env_var, get_env_var_or, settings, style::Style,
+};
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -6,7 +8,7 @@ use std::{
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ContextMarker {
-    pub color: String,
+    pub style: Style,
     pub text: String,
 }
 
@@ -131,23 +133,22 @@ fn get_python_env() -> Option<(String, PythonEnvSource)> {
 }
 
 #[must_use]
-pub fn get_python_env_color(source: PythonEnvSource) -> String {
-    let python_env_color = get_env_var("SLICK_PROMPT_PYTHON_ENV_COLOR");
-    if !python_env_color.is_empty() {
+pub fn get_python_env_color(source: PythonEnvSource) -> Style {
+    let python_env_color = settings().context.python_env_color.clone();
+    if get_env_source("SLICK_PROMPT_PYTHON_ENV_COLOR") != Some(Source::Default)
+        && !get_env("SLICK_PROMPT_PYTHON_ENV_COLOR").is_empty()
+    {
         return python_env_color;
     }
 
     match source {
         PythonEnvSource::VirtualEnv {
             pipenv_active: true,
-        } => get_env_var_or(
-            "PIPENV_ACTIVE_COLOR",
-            get_env("SLICK_PROMPT_PYTHON_ENV_COLOR"),
-        ),
+        } => Style::parse(&get_env_var("PIPENV_ACTIVE_COLOR")).unwrap_or(python_env_color),
         PythonEnvSource::VirtualEnv {
             pipenv_active: false,
         }
-        | PythonEnvSource::Pyenv => get_env("SLICK_PROMPT_PYTHON_ENV_COLOR").to_string(),
+        | PythonEnvSource::Pyenv => python_env_color,
     }
 }
 
@@ -217,29 +218,29 @@ pub fn collect_context_markers(short: bool) -> Vec<ContextMarker> {
 
     if let Some(toolbox_name) = get_toolbox_name() {
         markers.push(ContextMarker {
-            color: get_env("SLICK_PROMPT_TOOLBOX_COLOR").to_string(),
+            style: settings().context.toolbox_color.clone(),
             text: if short {
-                format!("({})", get_env("SLICK_PROMPT_TOOLBOX_SYMBOL"))
+                format!("({})", &settings().context.toolbox_symbol)
             } else {
-                format_context_marker(get_env("SLICK_PROMPT_TOOLBOX_SYMBOL"), &toolbox_name)
+                format_context_marker(&settings().context.toolbox_symbol, &toolbox_name)
             },
         });
     }
 
     if let Some(devpod_name) = get_devpod_name() {
         markers.push(ContextMarker {
-            color: get_env("SLICK_PROMPT_DEVPOD_COLOR").to_string(),
+            style: settings().context.devpod_color.clone(),
             text: if short {
-                format!("({})", get_env("SLICK_PROMPT_DEVPOD_SYMBOL"))
+                format!("({})", &settings().context.devpod_symbol)
             } else {
-                format_context_marker(get_env("SLICK_PROMPT_DEVPOD_SYMBOL"), &devpod_name)
+                format_context_marker(&settings().context.devpod_symbol, &devpod_name)
             },
         });
     }
 
     if let Some(aws_label) = get_aws_label() {
         markers.push(ContextMarker {
-            color: get_env("SLICK_PROMPT_AWS_COLOR").to_string(),
+            style: settings().context.aws_color.clone(),
             text: if short {
                 "(aws)".to_string()
             } else {
@@ -250,7 +251,7 @@ pub fn collect_context_markers(short: bool) -> Vec<ContextMarker> {
 
     if let Some(k8s_label) = get_k8s_label() {
         markers.push(ContextMarker {
-            color: get_env("SLICK_PROMPT_K8S_COLOR").to_string(),
+            style: settings().context.k8s_color.clone(),
             text: if short {
                 "(k8s)".to_string()
             } else {
@@ -261,7 +262,7 @@ pub fn collect_context_markers(short: bool) -> Vec<ContextMarker> {
 
     if let Some((python_env, source)) = get_python_env() {
         markers.push(ContextMarker {
-            color: get_python_env_color(source),
+            style: get_python_env_color(source),
             text: if short {
                 "(py)".to_string()
             } else {
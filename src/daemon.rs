@@ -0,0 +1,533 @@
+//! `slick daemon`: one process per user that shares repository state between shells.
+//!
+//! With several shells open in the same repository, every prompt would otherwise run
+//! its own `git status` scan and its own `git fetch`. The daemon listens on a Unix
+//! socket under `$XDG_RUNTIME_DIR` and keeps, per repository, the last status with a
+//! fingerprint of `index`, `HEAD` and the refs, and the last fetch. `slick precmd` asks
+//! it first and does the work itself when no daemon answers.
+//!
+//! A status follows the settings of the shell that asked for it (see
+//! [`StatusSettings`]), not the environment the daemon started in, and is only shared
+//! between shells with the same settings. A fetch likewise runs with the variables
+//! `git fetch` reads to reach a remote, such as `SSH_AUTH_SOCK`, taken from the asking
+//! shell, and is only shared between shells that agree on them.
+//!
+//! The protocol is one request line per connection, answered by one line:
+//!
+//! - `status <dirty threshold> <cache> <fallback> <git dir>`, with `0` or `1` for the
+//!   flags: `ok <status report as JSON>` or `error`
+//! - `fetch <seconds> <environment as a JSON object> <git dir>`: `completed`,
+//!   `timed-out` or `spawn-failed`
+//! - `stats`: counters as a JSON object, for tests and troubleshooting
+use crate::{
+    git::{self, StatusReport, StatusSettings},
+    precmd::{self, GitFetchOutcome},
+    status_cache,
+};
+use git2::Repository;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    process::exit,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How long a cached status is served while the fingerprint is unchanged.
+///
+/// Edits in the work tree do not touch `index`, `HEAD` or the refs, so the status is
+/// only shared between prompts drawn close together, such as one in every shell.
+const STATUS_TTL: Duration = Duration::from_secs(2);
+
+/// How long the result of a fetch is reused instead of fetching again.
+const FETCH_REUSE: Duration = Duration::from_secs(30);
+
+/// How long `slick precmd` waits for the status, beyond which it scans by itself.
+const STATUS_CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Variables `git fetch` reads to reach a remote: the SSH agent and programs, where
+/// git finds its config and credential helpers, and proxies. Names starting with
+/// `GIT_CONFIG_` are included as well. `GIT_SSH_COMMAND` and `GIT_ASKPASS` are not,
+/// since the fetch always replaces them with non-interactive ones.
+const FETCH_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "XDG_CONFIG_HOME",
+    "SSH_AUTH_SOCK",
+    "SSH_ASKPASS",
+    "GIT_SSH",
+    "GIT_SSH_VARIANT",
+    "http_proxy",
+    "https_proxy",
+    "HTTPS_PROXY",
+    "all_proxy",
+    "ALL_PROXY",
+    "no_proxy",
+    "NO_PROXY",
+];
+
+/// The values of the [`FETCH_ENV`] variables a shell has set.
+pub(crate) type FetchEnv = BTreeMap<String, String>;
+
+fn is_fetch_var(name: &str) -> bool {
+    FETCH_ENV.contains(&name) || name.starts_with("GIT_CONFIG_")
+}
+
+/// The fetch variables of this process, or `None` when one is not valid UTF-8 and
+/// cannot be sent to the daemon.
+fn current_fetch_env() -> Option<FetchEnv> {
+    env::vars_os()
+        .filter(|(name, _)| name.to_str().is_some_and(is_fetch_var))
+        .map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect()
+}
+
+/// Makes `command` see the fetch variables of `fetch_env` instead of the daemon's own.
+pub(crate) fn apply_fetch_env(command: &mut tokio::process::Command, fetch_env: &FetchEnv) {
+    for (name, _) in env::vars_os() {
+        if name.to_str().is_some_and(is_fetch_var) {
+            command.env_remove(name);
+        }
+    }
+    command.envs(fetch_env);
+}
+
+/// The socket the daemon listens on: `$XDG_RUNTIME_DIR/slick/daemon.sock`.
+#[must_use]
+pub fn socket_path() -> Option<PathBuf> {
+    env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join("slick").join("daemon.sock"))
+}
+
+/// Sends one request to the daemon and returns its answer, or `None` when no daemon
+/// is listening or it does not answer within `timeout`.
+fn request(line: &str, timeout: Duration) -> Option<String> {
+    let mut stream = UnixStream::connect(socket_path()?).ok()?;
+    stream.set_read_timeout(Some(timeout)).ok()?;
+    stream.set_write_timeout(Some(timeout)).ok()?;
+    writeln!(stream, "{line}").ok()?;
+
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer).ok()?;
+    answer.strip_suffix('\n').map(str::to_string)
+}
+
+/// Asks a running daemon for the status of the repository at `git_dir`, scanned with
+/// `settings`.
+#[must_use]
+pub fn status(git_dir: &Path, settings: &StatusSettings) -> Option<StatusReport> {
+    let git_dir = git_dir.to_str().filter(|path| !path.contains('\n'))?;
+    let answer = request(
+        &format!(
+            "status {} {} {} {git_dir}",
+            settings.dirty_threshold,
+            u8::from(settings.cache),
+            u8::from(settings.fallback)
+        ),
+        STATUS_CLIENT_TIMEOUT,
+    )?;
+    serde_json::from_str(answer.strip_prefix("ok ")?).ok()
+}
+
+/// Reads the argument of a `status` request.
+fn parse_status_request(argument: &str) -> Option<(StatusSettings, &Path)> {
+    let flag = |value: Option<&str>| match value? {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+    let mut parts = argument.splitn(4, ' ');
+    let dirty_threshold = parts.next()?.parse().ok()?;
+    let cache = flag(parts.next())?;
+    let fallback = flag(parts.next())?;
+    let settings = StatusSettings {
+        dirty_threshold,
+        cache,
+        fallback,
+    };
+    Some((settings, Path::new(parts.next()?)))
+}
+
+/// Asks a running daemon to fetch the repository at `git_dir` with this shell's fetch
+/// variables, joining a fetch that is already running for it with the same ones.
+pub(crate) fn fetch(git_dir: &Path, timeout: Duration) -> Option<GitFetchOutcome> {
+    let git_dir = git_dir.to_str().filter(|path| !path.contains('\n'))?;
+    let fetch_env = serde_json::to_string(&current_fetch_env()?).ok()?;
+    let answer = request(
+        &format!("fetch {} {fetch_env} {git_dir}", timeout.as_secs()),
+        // The daemon may first wait for a fetch another shell started
+        timeout.saturating_mul(2) + Duration::from_secs(1),
+    )?;
+    match answer.as_str() {
+        "completed" => Some(GitFetchOutcome::Completed),
+        "timed-out" => Some(GitFetchOutcome::TimedOut),
+        "spawn-failed" => Some(GitFetchOutcome::SpawnFailed),
+        _ => None,
+    }
+}
+
+/// Reads the argument of a `fetch` request.
+fn parse_fetch_request(argument: &str) -> Option<(Duration, FetchEnv, &Path)> {
+    let (seconds, rest) = argument.split_once(' ')?;
+    let mut values = serde_json::Deserializer::from_str(rest).into_iter::<FetchEnv>();
+    let fetch_env = values.next()?.ok()?;
+    let git_dir = rest.get(values.byte_offset()..)?.strip_prefix(' ')?;
+    Some((
+        Duration::from_secs(seconds.parse().ok()?),
+        fetch_env,
+        Path::new(git_dir),
+    ))
+}
+
+/// Modification time and size of the files a status depends on, besides the work
+/// tree: `index`, `HEAD`, `packed-refs` and every loose ref.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint(Vec<(PathBuf, Option<(SystemTime, u64)>)>);
+
+impl Fingerprint {
+    fn of(repo: &Repository) -> Self {
+        let stat = |path: PathBuf| {
+            let metadata = fs::metadata(&path).ok();
+            let stamp =
+                metadata.and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+            (path, stamp)
+        };
+
+        let mut files = vec![
+            stat(repo.path().join("index")),
+            stat(repo.path().join("HEAD")),
+            stat(repo.commondir().join("packed-refs")),
+        ];
+        let mut dirs = vec![repo.commondir().join("refs")];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    dirs.push(entry.path());
+                } else {
+                    files.push(stat(entry.path()));
+                }
+            }
+        }
+        files.sort();
+        Self(files)
+    }
+}
+
+struct CachedStatus {
+    fingerprint: Fingerprint,
+    at: Instant,
+    report: StatusReport,
+}
+
+#[derive(Clone, Copy)]
+enum FetchState {
+    Idle,
+    Running,
+    Done(Instant, GitFetchOutcome),
+}
+
+struct RepoState {
+    /// The last status scanned with each set of settings.
+    status: Mutex<HashMap<StatusSettings, CachedStatus>>,
+    /// The last fetch run with each set of fetch variables.
+    fetch: Mutex<HashMap<FetchEnv, FetchState>>,
+    fetch_done: Condvar,
+}
+
+impl Default for RepoState {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(HashMap::new()),
+            fetch: Mutex::new(HashMap::new()),
+            fetch_done: Condvar::new(),
+        }
+    }
+}
+
+/// Marks the fetch of a repository as over when dropped, even when the fetch panicked,
+/// and wakes the shells waiting for it.
+struct FetchGuard<'a> {
+    state: &'a RepoState,
+    fetch_env: &'a FetchEnv,
+    outcome: Option<GitFetchOutcome>,
+}
+
+impl Drop for FetchGuard<'_> {
+    fn drop(&mut self) {
+        let done = self.outcome.map_or(FetchState::Idle, |outcome| {
+            FetchState::Done(Instant::now(), outcome)
+        });
+        lock(&self.state.fetch).insert(self.fetch_env.clone(), done);
+        self.state.fetch_done.notify_all();
+    }
+}
+
+/// What the daemon has done since it started.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+struct Stats {
+    repos: usize,
+    status_computed: u64,
+    status_cached: u64,
+    fetches: u64,
+    fetches_shared: u64,
+}
+
+#[derive(Default)]
+struct Daemon {
+    repos: Mutex<HashMap<PathBuf, Arc<RepoState>>>,
+    stats: Mutex<Stats>,
+}
+
+/// Locks `mutex`, carrying on with the data if another thread panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Daemon {
+    fn repo(&self, git_dir: &Path) -> Arc<RepoState> {
+        let mut repos = lock(&self.repos);
+        let state = repos.entry(git_dir.to_path_buf()).or_default().clone();
+        lock(&self.stats).repos = repos.len();
+        state
+    }
+
+    fn status(&self, git_dir: &Path, settings: &StatusSettings) -> Option<StatusReport> {
+        let repo = Repository::open(git_dir).ok()?;
+        let state = self.repo(repo.path());
+
+        // Held while scanning, so shells asking at the same time share one scan
+        let mut cached = lock(&state.status);
+        if let Some(cached) = cached.get(settings)
+            && cached.at.elapsed() < STATUS_TTL
+            && cached.fingerprint == Fingerprint::of(&repo)
+        {
+            lock(&self.stats).status_cached += 1;
+            return Some(cached.report.clone());
+        }
+
+        let overrides = git::RepoOverrides::read_with(&repo, settings);
+        let report = status_cache::get_status(&repo, &overrides, settings).ok()?;
+        lock(&self.stats).status_computed += 1;
+        // Taken after the scan, which may refresh the index
+        cached.insert(
+            *settings,
+            CachedStatus {
+                fingerprint: Fingerprint::of(&repo),
+                at: Instant::now(),
+                report: report.clone(),
+            },
+        );
+        Some(report)
+    }
+
+    fn fetch(
+        &self,
+        git_dir: &Path,
+        timeout: Duration,
+        fetch_env: &FetchEnv,
+    ) -> Option<GitFetchOutcome> {
+        let repo = Repository::open(git_dir).ok()?;
+        let state = self.repo(repo.path());
+
+        // A fetch another shell started is waited for as long as this one would run
+        let deadline = Instant::now() + timeout;
+        let mut fetch = lock(&state.fetch);
+        loop {
+            match fetch.get(fetch_env).copied().unwrap_or(FetchState::Idle) {
+                FetchState::Running => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Some(GitFetchOutcome::TimedOut);
+                    }
+                    fetch = state
+                        .fetch_done
+                        .wait_timeout(fetch, left)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+                FetchState::Done(at, outcome) if at.elapsed() < FETCH_REUSE => {
+                    lock(&self.stats).fetches_shared += 1;
+                    return Some(outcome);
+                }
+                FetchState::Idle | FetchState::Done(..) => break,
+            }
+        }
+        fetch.insert(fetch_env.clone(), FetchState::Running);
+        drop(fetch);
+        let mut guard = FetchGuard {
+            state: &state,
+            fetch_env,
+            outcome: None,
+        };
+
+        let cache_path = git::get_auth_cache_path(&repo);
+        let fetch_path = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+        let outcome = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_or(GitFetchOutcome::SpawnFailed, |runtime| {
+                runtime.block_on(precmd::fetch_repository(
+                    cache_path,
+                    &fetch_path,
+                    timeout,
+                    Some(fetch_env),
+                ))
+            });
+        lock(&self.stats).fetches += 1;
+
+        guard.outcome = Some(outcome);
+        Some(outcome)
+    }
+
+    fn answer(&self, line: &str) -> String {
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "status" => parse_status_request(argument)
+                .and_then(|(settings, git_dir)| self.status(git_dir, &settings))
+                .and_then(|report| serde_json::to_string(&report).ok())
+                .map_or_else(|| "error".to_string(), |report| format!("ok {report}")),
+            "fetch" => match parse_fetch_request(argument)
+                .and_then(|(timeout, fetch_env, git_dir)| self.fetch(git_dir, timeout, &fetch_env))
+            {
+                Some(GitFetchOutcome::Completed) => "completed",
+                Some(GitFetchOutcome::TimedOut) => "timed-out",
+                Some(GitFetchOutcome::SpawnFailed) => "spawn-failed",
+                None => "error",
+            }
+            .to_string(),
+            "stats" => serde_json::to_string(&*lock(&self.stats)).unwrap_or_default(),
+            _ => "error".to_string(),
+        }
+    }
+
+    fn handle(&self, stream: &UnixStream) {
+        let mut line = String::new();
+        if BufReader::new(stream).read_line(&mut line).is_err() {
+            return;
+        }
+        let answer = self.answer(line.trim_end_matches('\n'));
+        let _ = writeln!(&*stream, "{answer}");
+    }
+}
+
+/// Binds the socket, replacing one left behind by a daemon that is gone.
+fn bind(path: &Path) -> io::Result<UnixListener> {
+    if let Some(dir) = path.parent() {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "another slick daemon is already running",
+        ));
+    }
+    let _ = fs::remove_file(path);
+
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+pub fn run() {
+    let Some(path) = socket_path() else {
+        eprintln!("slick: XDG_RUNTIME_DIR is not set");
+        exit(1)
+    };
+    let listener = match bind(&path) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("slick: cannot listen on {}: {error}", path.display());
+            exit(1)
+        }
+    };
+
+    let daemon = Arc::new(Daemon::default());
+    for stream in listener.incoming().flatten() {
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || daemon.handle(&stream));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+
+    use super::{
+        FetchEnv, FetchGuard, FetchState, RepoState, lock, parse_fetch_request,
+        parse_status_request,
+    };
+    use std::{path::Path, thread, time::Duration};
+
+    #[test]
+    fn test_parse_status_request() {
+        let (settings, git_dir) =
+            parse_status_request("5000 1 0 /src/my repo/.git/").expect("request should parse");
+        assert_eq!(settings.dirty_threshold, 5000);
+        assert!(settings.cache);
+        assert!(!settings.fallback);
+        assert_eq!(git_dir, Path::new("/src/my repo/.git/"));
+
+        assert!(parse_status_request("/src/repo/.git/").is_none());
+        assert!(parse_status_request("0 yes 0 /src/repo/.git/").is_none());
+    }
+
+    #[test]
+    fn test_parse_fetch_request() {
+        let (timeout, fetch_env, git_dir) = parse_fetch_request(
+            r#"5 {"GIT_SSH":"ssh -i key","SSH_AUTH_SOCK":"/tmp/agent {1}"} /src/my repo/.git/"#,
+        )
+        .expect("request should parse");
+        assert_eq!(timeout, Duration::from_secs(5));
+        assert_eq!(
+            fetch_env.get("SSH_AUTH_SOCK").map(String::as_str),
+            Some("/tmp/agent {1}")
+        );
+        assert_eq!(fetch_env.len(), 2);
+        assert_eq!(git_dir, Path::new("/src/my repo/.git/"));
+
+        assert!(parse_fetch_request("5 /src/repo/.git/").is_none());
+        assert!(parse_fetch_request("5 {}").is_none());
+    }
+
+    #[test]
+    fn test_a_fetch_that_panics_is_no_longer_running() {
+        let state = RepoState::default();
+        let fetch_env = FetchEnv::new();
+        lock(&state.fetch).insert(fetch_env.clone(), FetchState::Running);
+
+        let result = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _guard = FetchGuard {
+                        state: &state,
+                        fetch_env: &fetch_env,
+                        outcome: None,
+                    };
+                    panic!("fetch failed");
+                })
+                .join()
+        });
+
+        assert!(result.is_err());
+        assert!(matches!(
+            lock(&state.fetch).get(&fetch_env),
+            Some(FetchState::Idle)
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! Lines added and removed since `HEAD`, for the opt-in `{git_diffstat}` segment.
+//!
+//! The counts cover every uncommitted change to tracked files, staged or not, like
+//! `git diff HEAD --shortstat`. Counting lines means reading both sides of each changed
+//! file, which the status scan never does, so the segment is off by default
+//! (`SLICK_PROMPT_GIT_DIFFSTAT`) and `slick precmd` prints it after the status. It gives
+//! up on more than `SLICK_PROMPT_GIT_DIFFSTAT_MAX_FILES` changed files, and files over
+//! [`MAX_FILE_SIZE`] are diffed as binary, without lines.
+use git2::{DiffOptions, ErrorCode, Repository};
+use serde::{Deserialize, Serialize};
+
+/// Files larger than this, in bytes, count as binary.
+pub const MAX_FILE_SIZE: i64 = 1024 * 1024;
+
+/// Lines added and removed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct DiffStat {
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Counts the lines changed between `HEAD` and the work tree, or `None` when more than
+/// `max_files` files changed (0 for no limit).
+///
+/// # Errors
+///
+/// Returns an error if `HEAD`, the index or a file cannot be read, or for a bare
+/// repository.
+pub fn diffstat(repo: &Repository, max_files: usize) -> Result<Option<DiffStat>, git2::Error> {
+    let head = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+        Err(error) => return Err(error),
+    };
+    let mut options = DiffOptions::new();
+    options.ignore_submodules(true).max_size(MAX_FILE_SIZE);
+    let diff = repo.diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut options))?;
+
+    // Listing the changed files is cheap next to reading them for the line counts
+    if max_files > 0 && diff.deltas().len() > max_files {
+        return Ok(None);
+    }
+    let stats = diff.stats()?;
+    Ok(Some(DiffStat {
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    }))
+}
@@ -0,0 +1,126 @@
+//! Dirty-only status: whether a repository has any change to tracked files, without
+//! counting them.
+//!
+//! A full `git status` scan visits every file, compares it with the index and walks the
+//! untracked directories. In a large repository a single "dirty" marker is usually
+//! enough, and the scan can stop at the first change: the index is compared with
+//! `HEAD` by object id, then each tracked file is `lstat`ed and compared with the
+//! stat data the index recorded for it. Only a file whose stamp changed but whose size
+//! did not has its contents compared, through libgit2 so filters such as `autocrlf`
+//! still apply. Untracked files are not looked for.
+//!
+//! Chosen with `slick.dirtyOnly` or `SLICK_PROMPT_GIT_DIRTY_THRESHOLD`, see
+//! [`crate::git::RepoOverrides::dirty_only`].
+use git2::{ErrorCode, IndexEntry, IndexEntryExtendedFlag, IndexEntryFlag, Repository, Status};
+use std::{
+    ffi::OsStr,
+    fs::{self, Metadata},
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::Path,
+};
+
+/// File type bits of `git_index_entry` modes.
+const TYPE_MASK: u32 = 0o170_000;
+const SYMLINK_MODE: u32 = 0o120_000;
+const GITLINK_MODE: u32 = 0o160_000;
+const EXECUTABLE_MODE: u32 = 0o100_755;
+
+/// Whether `repo` has a staged change, a conflict, or a tracked file that differs from
+/// the index. Stops at the first one found.
+///
+/// # Errors
+///
+/// Returns an error if the index, `HEAD` or a file's status cannot be read.
+pub fn is_dirty(repo: &Repository) -> Result<bool, git2::Error> {
+    let mut index = repo.index()?;
+    // The repository keeps the index it loaded first; pick up the one on disk
+    index.read(false)?;
+    if index.has_conflicts() {
+        return Ok(true);
+    }
+
+    let head = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+        Err(error) => return Err(error),
+    };
+    if repo
+        .diff_tree_to_index(head.as_ref(), Some(&index), None)?
+        .deltas()
+        .len()
+        > 0
+    {
+        return Ok(true);
+    }
+
+    let Some(workdir) = repo.workdir() else {
+        return Ok(false);
+    };
+    // Entries written in the same second as the index may have changed unnoticed
+    let index_mtime = fs::metadata(repo.path().join("index")).map_or(i64::MAX, |m| m.mtime());
+    let file_mode = repo
+        .config()
+        .and_then(|config| config.get_bool("core.fileMode"))
+        .unwrap_or(true);
+
+    for entry in index.iter() {
+        if entry.mode == GITLINK_MODE
+            || IndexEntryFlag::from_bits_truncate(entry.flags).is_valid()
+            || IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended).is_skip_worktree()
+        {
+            continue;
+        }
+        let path = Path::new(OsStr::from_bytes(&entry.path));
+        let Ok(metadata) = fs::symlink_metadata(workdir.join(path)) else {
+            return Ok(true);
+        };
+        match compare(&entry, &metadata, file_mode) {
+            Stat::Same if i64::from(entry.mtime.seconds()) < index_mtime => {}
+            Stat::Changed => return Ok(true),
+            Stat::Same | Stat::Touched => {
+                let status = repo.status_file(path)?;
+                if status.intersects(
+                    Status::WT_MODIFIED
+                        | Status::WT_DELETED
+                        | Status::WT_TYPECHANGE
+                        | Status::WT_RENAMED,
+                ) {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// How a work-tree file compares with the stat data its index entry recorded.
+enum Stat {
+    /// Same size, type, mode and modification time.
+    Same,
+    /// Different size, type or mode: the file changed.
+    Changed,
+    /// Only the modification time differs: the contents may be the same.
+    Touched,
+}
+
+fn compare(entry: &IndexEntry, metadata: &Metadata, file_mode: bool) -> Stat {
+    let symlink = entry.mode & TYPE_MASK == SYMLINK_MODE;
+    let executable = metadata.mode() & 0o111 != 0;
+    // The index keeps the low 32 bits of the size
+    #[allow(clippy::cast_possible_truncation)]
+    let size = metadata.size() as u32;
+    if symlink != metadata.file_type().is_symlink()
+        || (!symlink && !metadata.is_file())
+        || size != entry.file_size
+        || (file_mode && !symlink && executable != (entry.mode == EXECUTABLE_MODE))
+    {
+        return Stat::Changed;
+    }
+    if i64::from(entry.mtime.seconds()) == metadata.mtime()
+        && i64::from(entry.mtime.nanoseconds()) == metadata.mtime_nsec()
+    {
+        Stat::Same
+    } else {
+        Stat::Touched
+    }
+}
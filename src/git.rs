@@ -1,13 +1,17 @@
 // src/git.rs
-use crate::get_env; // Assuming get_env is in lib.rs or another common module
-use git2::{DiffOptions, Error, ErrorCode, Repository, Status, StatusOptions, StatusShow};
+use crate::{diffstat::DiffStat, dirty, settings, settings::parse_seconds, style::Style};
+use git2::{
+    Config, DiffOptions, Error, ErrorCode, Repository, Status, StatusOptions, StatusShow,
+    SubmoduleIgnore, SubmoduleStatus,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     env,
     fmt::Write as _,
     fs,
-    path::PathBuf,
-    time::{SystemTime, UNIX_EPOCH},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 /// Returns the current Unix timestamp (seconds since `UNIX_EPOCH`).
@@ -34,54 +38,160 @@ pub const ACTION_CHERRY: &str = "cherry";
 pub const ACTION_CHERRY_OR_REVERT: &str = "cherry-or-revert";
 pub const NO_BRANCH: &str = "(no branch)";
 
-#[derive(Default)]
-struct StatusCounts {
-    conflicted: u32,
-    added_modified: u32,
-    modified_modified: u32,
-    modified: u32,
-    deleted: u32,
-    renamed: u32,
-    typechanged: u32,
-    added: u32,
-    untracked: u32,
-    ignored: u32,
-    fallback: u32,
+/// Changes on one side of `git status`: the index against `HEAD` (staged) or the work
+/// tree against the index (unstaged), like the X and Y columns of
+/// `git status --porcelain=v2`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChangeCounts {
+    pub added: u32,
+    pub modified: u32,
+    pub deleted: u32,
+    pub renamed: u32,
+    pub typechange: u32,
+}
+
+impl ChangeCounts {
+    /// Whether nothing changed on this side.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Counts `status` with the flags of one side, in [`ChangeCounts`] field order.
+    fn increment(&mut self, status: Status, flags: [Status; 5]) {
+        let [added, modified, deleted, renamed, typechange] = flags;
+        for (flag, count) in [
+            (added, &mut self.added),
+            (modified, &mut self.modified),
+            (deleted, &mut self.deleted),
+            (renamed, &mut self.renamed),
+            (typechange, &mut self.typechange),
+        ] {
+            if status.intersects(flag) {
+                *count += 1;
+            }
+        }
+    }
+}
+
+/// The files `git status` reports, counted per side. A file with staged and unstaged
+/// changes counts on both sides; a conflicted file only as conflicted.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct StatusCounts {
+    pub staged: ChangeCounts,
+    pub unstaged: ChangeCounts,
+    pub untracked: u32,
+    pub conflicted: u32,
 }
 
 impl StatusCounts {
-    const fn increment(&mut self, status: Status) {
+    /// Whether the scan found nothing to count.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn increment(&mut self, status: Status) {
         if status.contains(Status::CONFLICTED) {
             self.conflicted += 1;
-        } else if status.contains(Status::INDEX_RENAMED) || status.contains(Status::WT_RENAMED) {
-            self.renamed += 1;
-        } else if status.contains(Status::INDEX_NEW) && status.contains(Status::WT_MODIFIED) {
-            self.added_modified += 1;
-        } else if status.contains(Status::INDEX_MODIFIED) && status.contains(Status::WT_MODIFIED) {
-            self.modified_modified += 1;
-        } else if status.contains(Status::INDEX_MODIFIED) || status.contains(Status::WT_MODIFIED) {
-            self.modified += 1;
-        } else if status.contains(Status::INDEX_DELETED) || status.contains(Status::WT_DELETED) {
-            self.deleted += 1;
-        } else if status.contains(Status::INDEX_TYPECHANGE)
-            || status.contains(Status::WT_TYPECHANGE)
-        {
-            self.typechanged += 1;
-        } else if status.contains(Status::INDEX_NEW) {
-            self.added += 1;
-        } else if status.contains(Status::WT_NEW) {
+            return;
+        }
+        self.staged.increment(
+            status,
+            [
+                Status::INDEX_NEW,
+                Status::INDEX_MODIFIED,
+                Status::INDEX_DELETED,
+                Status::INDEX_RENAMED,
+                Status::INDEX_TYPECHANGE,
+            ],
+        );
+        // A new file in the work tree is untracked rather than added
+        self.unstaged.increment(
+            status,
+            [
+                Status::empty(),
+                Status::WT_MODIFIED,
+                Status::WT_DELETED,
+                Status::WT_RENAMED,
+                Status::WT_TYPECHANGE,
+            ],
+        );
+        if status.contains(Status::WT_NEW) {
             self.untracked += 1;
-        } else if status.contains(Status::IGNORED) {
-            self.ignored += 1;
-        } else {
-            self.fallback += 1;
+        }
+    }
+}
+
+/// What a status scan found: the counts per side the prompt shows, or in dirty-only
+/// mode, which leaves them at zero, whether there is a change at all.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct StatusReport {
+    pub dirty: bool,
+    pub counts: StatusCounts,
+}
+
+/// The version of the JSON lines `slick precmd` prints for `slick prompt`.
+///
+/// Bump it whenever a field changes meaning, so a prompt rendered by one binary from
+/// the output of another shows the mismatch instead of guessing.
+pub const PROMPT_SCHEMA: u32 = 2;
+
+/// Which `slick precmd` phase a line comes from.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Branch, remote and user name, read without touching the work tree.
+    #[default]
+    Local,
+    /// The work-tree status has been added.
+    Status,
+    /// The ahead/behind counts and fetch result after `git fetch`.
+    PostFetch,
+    /// The last data saved for the repository, shown by `slick prompt --cached` until
+    /// the first phase arrives. `slick precmd` never prints it.
+    Cached,
+}
+
+/// Why the data passed to `slick prompt -d` could not be used as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataError {
+    /// Not a JSON object, for example a line cut short.
+    Malformed,
+    /// Written for another schema version by a different `slick` binary, or 0 for a
+    /// line from a binary that predates the field.
+    Schema(u32),
+}
+
+impl std::fmt::Display for DataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed precmd data"),
+            Self::Schema(0) => write!(f, "precmd data without a schema, expected {PROMPT_SCHEMA}"),
+            Self::Schema(schema) => {
+                write!(f, "precmd data schema {schema}, expected {PROMPT_SCHEMA}")
+            }
         }
     }
 }
 
 /// Represents the collected Git information for rendering the prompt.
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+///
+/// `slick precmd` prints one JSON line of it per phase and `slick prompt -d` reads it
+/// back with [`Prompt::decode`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)] // the JSON fields the shell integrations read
 pub struct Prompt {
+    /// [`PROMPT_SCHEMA`] for data this binary wrote. Lines from before the field
+    /// existed have none and are read as schema 0, which never matches.
+    #[serde(default)]
+    pub schema: u32,
+    /// The phase this line was printed in.
+    pub phase: Phase,
     /// The current Git action (e.g., "rebase", "merge", "am/rebase").
     pub action: String,
     /// The current branch name (or "(no branch)" for detached HEAD).
@@ -90,15 +200,351 @@ pub struct Prompt {
     pub remote: Vec<String>,
     /// True if there are staged changes.
     pub staged: bool,
-    /// A summary of the repository's status (e.g., "M 1", "?? 2").
-    pub status: String,
+    /// True if the repository has changes in dirty-only mode, which doesn't count them.
+    pub dirty: bool,
+    /// The status counted per side. All zero until the status phase, in dirty-only
+    /// mode and when the scan missed its deadline.
+    pub counts: StatusCounts,
+    /// Lines added and removed since `HEAD`, once counted (see [`crate::diffstat`]).
+    pub diffstat: Option<DiffStat>,
     /// The Git user name from the repository configuration.
     pub u_name: String,
     /// True if the last `git fetch` resulted in an authentication failure.
     pub auth_failed: bool,
     /// True if the last `git fetch` failed to reach the remote (network/DNS/host error).
-    #[serde(default)]
     pub fetch_failed: bool,
+    /// True if the status scan missed its deadline, so `counts` are unknown rather than clean.
+    pub status_timed_out: bool,
+    /// The branch style set by `slick.branchColor` for this repository, empty for the default.
+    pub branch_color: String,
+}
+
+impl Default for Prompt {
+    fn default() -> Self {
+        Self {
+            schema: PROMPT_SCHEMA,
+            phase: Phase::default(),
+            action: String::new(),
+            branch: String::new(),
+            remote: Vec::new(),
+            staged: false,
+            dirty: false,
+            counts: StatusCounts::default(),
+            diffstat: None,
+            u_name: String::new(),
+            auth_failed: false,
+            fetch_failed: false,
+            status_timed_out: false,
+            branch_color: String::new(),
+        }
+    }
+}
+
+impl Prompt {
+    /// Fills in the result of a status scan.
+    pub fn set_status(&mut self, report: &StatusReport) {
+        self.dirty = report.dirty;
+        self.counts = report.counts;
+    }
+
+    /// Reads one line of `slick precmd` output.
+    ///
+    /// Empty data (no phase has arrived yet) is an empty prompt. Data from another
+    /// schema is still read as far as the fields allow, and returned with the error so
+    /// the prompt can flag it; malformed data yields an empty prompt and the error.
+    #[must_use]
+    pub fn decode(data: &str) -> (Self, Option<DataError>) {
+        if data.trim().is_empty() {
+            return (Self::default(), None);
+        }
+
+        match serde_json::from_str::<Self>(data) {
+            Ok(prompt) if prompt.schema == PROMPT_SCHEMA => (prompt, None),
+            Ok(prompt) => {
+                let schema = prompt.schema;
+                (prompt, Some(DataError::Schema(schema)))
+            }
+            Err(_) => (Self::default(), Some(DataError::Malformed)),
+        }
+    }
+}
+
+/// Per-repository overrides read from `slick.*` keys in git config.
+///
+/// The keys can live in the repository's `.git/config`, the global or system config,
+/// or a file pulled in with `includeIf`, so large repositories can opt out of slow
+/// features without changing the environment everywhere else. When set, they win
+/// over the `SLICK_PROMPT_*` settings. Values git cannot parse are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RepoOverrides {
+    /// `slick.disable`: hide all git information for this repository.
+    pub disable: bool,
+    /// `slick.fetch`: overrides `SLICK_PROMPT_GIT_FETCH`.
+    pub fetch: Option<bool>,
+    /// `slick.fetchTimeout`: overrides `SLICK_PROMPT_GIT_FETCH_TIMEOUT`, in seconds.
+    pub fetch_timeout: Option<Duration>,
+    /// `slick.showUntracked`: set to false to skip the untracked-file scan.
+    pub show_untracked: Option<bool>,
+    /// `slick.hideUserName`: overrides `SLICK_PROMPT_NO_GIT_UNAME`.
+    pub hide_user_name: Option<bool>,
+    /// `slick.branchColor`: overrides the branch style, including on `main`/`master`.
+    pub branch_color: Option<Style>,
+    /// `slick.statusTimeout`: overrides `SLICK_PROMPT_GIT_STATUS_TIMEOUT`, in seconds.
+    pub status_timeout: Option<Duration>,
+    /// `slick.dirtyOnly`: overrides `SLICK_PROMPT_GIT_DIRTY_THRESHOLD`.
+    pub dirty_only: Option<bool>,
+    /// Set by [`RepoOverrides::read`] when `SLICK_PROMPT_GIT_STATUS_FALLBACK` is on and a
+    /// recent status scan missed its deadline (see [`mark_status_slow`]), not from git config.
+    pub slow_status: bool,
+    /// `SLICK_PROMPT_GIT_DIRTY_THRESHOLD`, set by [`RepoOverrides::read`] rather than
+    /// from git config.
+    pub dirty_threshold: usize,
+}
+
+/// The settings of the shell that shape a status scan, besides the repository's git
+/// config. `slick precmd` sends them to `slick daemon`, which scans with them instead
+/// of the settings it started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusSettings {
+    /// `SLICK_PROMPT_GIT_DIRTY_THRESHOLD`.
+    pub dirty_threshold: usize,
+    /// `SLICK_PROMPT_GIT_STATUS_CACHE`.
+    pub cache: bool,
+    /// `SLICK_PROMPT_GIT_STATUS_FALLBACK`.
+    pub fallback: bool,
+}
+
+impl StatusSettings {
+    /// The settings of this process.
+    #[must_use]
+    pub fn current() -> Self {
+        let git = &settings().git;
+        Self {
+            dirty_threshold: git.dirty_threshold,
+            cache: git.status_cache,
+            fallback: git.status_fallback,
+        }
+    }
+}
+
+impl RepoOverrides {
+    /// Reads the overrides from `repo`'s git config, or returns none if it cannot be opened.
+    #[must_use]
+    pub fn read(repo: &Repository) -> Self {
+        Self::read_with(repo, &StatusSettings::current())
+    }
+
+    /// Like [`RepoOverrides::read`], with the status settings of another process.
+    #[must_use]
+    pub fn read_with(repo: &Repository, status: &StatusSettings) -> Self {
+        let mut overrides = repo
+            .config()
+            .map(|config| Self::from_config(&config))
+            .unwrap_or_default();
+        overrides.slow_status = status.fallback && is_status_slow(repo);
+        overrides.dirty_threshold = status.dirty_threshold;
+        overrides
+    }
+
+    /// Reads the overrides from an already opened git config.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            disable: config.get_bool("slick.disable").unwrap_or(false),
+            fetch: config.get_bool("slick.fetch").ok(),
+            fetch_timeout: config
+                .get_string("slick.fetchTimeout")
+                .ok()
+                .and_then(|value| parse_seconds(&value)),
+            show_untracked: config.get_bool("slick.showUntracked").ok(),
+            hide_user_name: config.get_bool("slick.hideUserName").ok(),
+            branch_color: config
+                .get_string("slick.branchColor")
+                .ok()
+                .and_then(|value| Style::parse(&value)),
+            status_timeout: config
+                .get_string("slick.statusTimeout")
+                .ok()
+                .and_then(|value| parse_seconds(&value)),
+            dirty_only: config.get_bool("slick.dirtyOnly").ok(),
+            slow_status: false,
+            dirty_threshold: 0,
+        }
+    }
+
+    /// Whether to run the background `git fetch`.
+    #[must_use]
+    pub fn fetch(&self) -> bool {
+        self.fetch.unwrap_or(settings().git.fetch)
+    }
+
+    /// How long to wait for the background `git fetch`.
+    #[must_use]
+    pub fn fetch_timeout(&self) -> Duration {
+        self.fetch_timeout.unwrap_or(settings().git.fetch_timeout)
+    }
+
+    /// How long to wait for `git status`; zero waits as long as it takes.
+    #[must_use]
+    pub fn status_timeout(&self) -> Duration {
+        self.status_timeout.unwrap_or(settings().git.status_timeout)
+    }
+
+    /// Whether `git status` should look for untracked files. A repository that fell
+    /// back to the cheaper scan skips them unless `slick.showUntracked` is set.
+    #[must_use]
+    pub fn show_untracked(&self) -> bool {
+        self.show_untracked.unwrap_or(!self.slow_status)
+    }
+
+    /// Whether `git status` should only look for the first change (see [`crate::dirty`])
+    /// in a repository tracking `files` files, instead of counting every change.
+    #[must_use]
+    pub fn dirty_only(&self, files: usize) -> bool {
+        self.dirty_only
+            .unwrap_or(self.dirty_threshold > 0 && files >= self.dirty_threshold)
+    }
+
+    /// How to list untracked files, given the repository's `status.showUntrackedFiles`.
+    /// `slick.showUntracked = true` keeps git's mode but lists them even when it is `no`.
+    #[must_use]
+    pub fn untracked_files(&self, configured: UntrackedFiles) -> UntrackedFiles {
+        match self.show_untracked {
+            Some(true) if configured == UntrackedFiles::No => UntrackedFiles::Normal,
+            Some(true) => configured,
+            _ if self.show_untracked() => configured,
+            _ => UntrackedFiles::No,
+        }
+    }
+
+    /// Whether to leave the git user name out of the prompt.
+    #[must_use]
+    pub fn hide_user_name(&self) -> bool {
+        self.hide_user_name.unwrap_or(settings().git.hide_uname)
+    }
+}
+
+/// How `git status` lists untracked files (`status.showUntrackedFiles`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UntrackedFiles {
+    /// `no`: skip them.
+    No,
+    /// `normal`: a directory without tracked files counts once, however many files it holds.
+    #[default]
+    Normal,
+    /// `all`: count every file, also inside untracked directories.
+    All,
+}
+
+/// The settings `git status` itself reads to decide what it reports.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StatusConfig {
+    /// `status.showUntrackedFiles`.
+    pub untracked_files: UntrackedFiles,
+    /// `diff.ignoreSubmodules`: when set, it replaces every submodule's own setting.
+    pub ignore_submodules: Option<SubmoduleIgnore>,
+    /// `submodule.<name>.ignore` from git config, by submodule name. Those in
+    /// `.gitmodules` are left to libgit2.
+    pub submodule_ignores: Vec<(String, SubmoduleIgnore)>,
+}
+
+impl StatusConfig {
+    /// Reads the settings from `repo`'s git config, or returns git's defaults if it
+    /// cannot be opened.
+    #[must_use]
+    pub fn read(repo: &Repository) -> Self {
+        repo.config()
+            .map(|config| Self::from_config(&config))
+            .unwrap_or_default()
+    }
+
+    /// Reads the settings from an already opened git config. Values git would reject
+    /// fall back to its defaults.
+    ///
+    /// `status.submoduleSummary` is not read: it only appends a log of submodule
+    /// commits to git's long output and never changes what is counted.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        let untracked_files = match config.get_string("status.showUntrackedFiles") {
+            Ok(value) if value.eq_ignore_ascii_case("all") => UntrackedFiles::All,
+            Ok(value) if value.eq_ignore_ascii_case("no") => UntrackedFiles::No,
+            Ok(_) if config.get_bool("status.showUntrackedFiles").ok() == Some(false) => {
+                UntrackedFiles::No
+            }
+            _ => UntrackedFiles::Normal,
+        };
+        let mut submodule_ignores = Vec::new();
+        if let Ok(mut entries) = config.entries(Some(r"^submodule\..*\.ignore$")) {
+            while let Some(Ok(entry)) = entries.next() {
+                if let Some(name) = entry
+                    .name()
+                    .ok()
+                    .and_then(|key| key.strip_prefix("submodule."))
+                    .and_then(|key| key.strip_suffix(".ignore"))
+                    && let Some(ignore) = entry.value().ok().and_then(parse_submodule_ignore)
+                {
+                    submodule_ignores.push((name.to_string(), ignore));
+                }
+            }
+        }
+        Self {
+            untracked_files,
+            ignore_submodules: config
+                .get_string("diff.ignoreSubmodules")
+                .ok()
+                .and_then(|value| parse_submodule_ignore(&value)),
+            submodule_ignores,
+        }
+    }
+
+    /// How much of the submodule called `name` to check, following git's order:
+    /// `diff.ignoreSubmodules`, then `submodule.<name>.ignore` from git config, then
+    /// `.gitmodules`.
+    #[must_use]
+    pub fn submodule_ignore(&self, name: &str) -> SubmoduleIgnore {
+        self.ignore_submodules
+            .or_else(|| {
+                // The last occurrence wins, as in git
+                self.submodule_ignores
+                    .iter()
+                    .rev()
+                    .find(|(submodule, _)| submodule == name)
+                    .map(|(_, ignore)| *ignore)
+            })
+            .unwrap_or(SubmoduleIgnore::Unspecified)
+    }
+}
+
+fn parse_submodule_ignore(value: &str) -> Option<SubmoduleIgnore> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Some(SubmoduleIgnore::None),
+        "untracked" => Some(SubmoduleIgnore::Untracked),
+        "dirty" => Some(SubmoduleIgnore::Dirty),
+        "all" => Some(SubmoduleIgnore::All),
+        _ => None,
+    }
+}
+
+/// The status entry of a submodule checked with [`StatusConfig::submodule_ignore`],
+/// mapped the way libgit2 reports submodules in a status scan.
+fn submodule_entry(status: SubmoduleStatus) -> Status {
+    let mut entry = Status::CURRENT;
+    for (submodule, flag) in [
+        (SubmoduleStatus::INDEX_ADDED, Status::INDEX_NEW),
+        (SubmoduleStatus::INDEX_DELETED, Status::INDEX_DELETED),
+        (SubmoduleStatus::INDEX_MODIFIED, Status::INDEX_MODIFIED),
+        (SubmoduleStatus::WD_ADDED, Status::WT_NEW),
+        (SubmoduleStatus::WD_DELETED, Status::WT_DELETED),
+        (SubmoduleStatus::WD_MODIFIED, Status::WT_MODIFIED),
+        (SubmoduleStatus::WD_INDEX_MODIFIED, Status::WT_MODIFIED),
+        (SubmoduleStatus::WD_WD_MODIFIED, Status::WT_MODIFIED),
+        (SubmoduleStatus::WD_UNTRACKED, Status::WT_MODIFIED),
+    ] {
+        if status.contains(submodule) {
+            entry |= flag;
+        }
+    }
+    entry
 }
 
 /// Outcome of the most recent `git fetch`, as persisted in the cache file.
@@ -160,6 +606,15 @@ impl FetchStatus {
 /// A `Prompt` struct populated with available fast Git information.
 #[must_use]
 pub fn build_prompt_fast(repo: &Repository) -> Prompt {
+    build_prompt_fast_with(repo, &RepoOverrides::read(repo))
+}
+
+/// Like [`build_prompt_fast`], but applies already-read per-repository overrides.
+///
+/// A hidden user name is left empty and `slick.branchColor` is carried in
+/// [`Prompt::branch_color`] so the renderer can apply it.
+#[must_use]
+pub fn build_prompt_fast_with(repo: &Repository, overrides: &RepoOverrides) -> Prompt {
     // get branch (instant - just reading HEAD)
     let branch = match repo.head() {
         Ok(head) => head
@@ -184,12 +639,18 @@ pub fn build_prompt_fast(repo: &Repository) -> Prompt {
     };
 
     // get user.name (fast - just reading git config)
-    if let Ok(config) = repo.config() {
+    if !overrides.hide_user_name()
+        && let Ok(config) = repo.config()
+    {
         prompt.u_name = config
             .get_string("user.name")
             .unwrap_or_else(|_| String::new());
     }
 
+    if let Some(color) = &overrides.branch_color {
+        prompt.branch_color = color.to_string();
+    }
+
     // Check for cached fetch status (synchronous, fast - just reads cache file)
     let fetch_status = read_fetch_status(repo);
     prompt.auth_failed = fetch_status == FetchStatus::AuthFailed;
@@ -232,63 +693,132 @@ pub fn remote_markers(repo: &Repository) -> Vec<String> {
     if behind > 0 {
         let mut s = String::with_capacity(8);
         // Writing to String never fails - ignore result
-        let _ = write!(s, "{}{}", get_env("SLICK_PROMPT_GIT_REMOTE_BEHIND"), behind);
+        let _ = write!(s, "{}{}", settings().git.remote_behind, behind);
         markers.push(s);
     }
     if ahead > 0 {
         let mut s = String::with_capacity(8);
         // Writing to String never fails - ignore result
-        let _ = write!(s, "{}{}", get_env("SLICK_PROMPT_GIT_REMOTE_AHEAD"), ahead);
+        let _ = write!(s, "{}{}", settings().git.remote_ahead, ahead);
         markers.push(s);
     }
 
     markers
 }
 
-/// Returns a string summarizing the git status of the repository.
+/// Returns a string summarizing the git status of the repository, such as `A 1 M 2`:
+/// the staged counts through `SLICK_PROMPT_GIT_STAGED_FORMAT` followed by the rest
+/// through `SLICK_PROMPT_GIT_STATUS_FORMAT`, as the prompt lays them out.
 ///
 /// # Errors
 ///
 /// This function will return a `git2::Error` if it fails to get the repository statuses.
 pub fn get_status(repo: &Repository) -> Result<String, Error> {
-    // Pre-allocate with estimated capacity for common status types
-    let mut status: Vec<String> = Vec::with_capacity(8);
+    let report = get_status_report(repo, &RepoOverrides::default())?;
+    if report.dirty {
+        return Ok(settings().git.dirty_symbol.clone());
+    }
+
+    let counts = &report.counts;
+    let staged = settings()
+        .git
+        .staged_format
+        .render(|category| category.count(&counts.staged, counts, true));
+    let rest = settings()
+        .git
+        .status_format
+        .render(|category| category.count(&counts.unstaged, counts, false));
+    Ok([staged, rest]
+        .into_iter()
+        .filter(|side| !side.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// The status of the repository with the counts per side, as the prompt shows it.
+///
+/// # Errors
+///
+/// This function will return a `git2::Error` if it fails to get the repository statuses.
+pub fn get_status_report(
+    repo: &Repository,
+    overrides: &RepoOverrides,
+) -> Result<StatusReport, Error> {
+    scan_status(repo, overrides, false, |_, _| {})
+}
+
+/// Like [`get_status_report`], and also calls `visit` with the status and path of every
+/// entry, for the status cache. With `include_ignored`, the scan also lists ignored
+/// files and directories, without entering ignored directories.
+pub(crate) fn scan_status(
+    repo: &Repository,
+    overrides: &RepoOverrides,
+    include_ignored: bool,
+    mut visit: impl FnMut(Status, &str),
+) -> Result<StatusReport, Error> {
+    if overrides.dirty_only(repo.index()?.len()) {
+        return Ok(StatusReport {
+            dirty: dirty::is_dirty(repo)?,
+            counts: StatusCounts::default(),
+        });
+    }
+
+    let mut counts = StatusCounts::default();
+    for_each_status(repo, overrides, include_ignored, |status, path| {
+        counts.increment(status);
+        if let Some(path) = path {
+            visit(status, path);
+        }
+    })?;
+
+    Ok(StatusReport {
+        dirty: false,
+        counts,
+    })
+}
+
+/// Calls `visit` with the status and path of every change `git status` would list,
+/// and with the status of every changed submodule, which has no path.
+fn for_each_status(
+    repo: &Repository,
+    overrides: &RepoOverrides,
+    include_ignored: bool,
+    mut visit: impl FnMut(Status, Option<&str>),
+) -> Result<(), Error> {
+    let config = StatusConfig::read(repo);
+    let untracked = overrides.untracked_files(config.untracked_files);
     let mut status_opt = StatusOptions::new();
     status_opt
         .show(StatusShow::IndexAndWorkdir)
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
+        .include_untracked(untracked != UntrackedFiles::No)
+        .recurse_untracked_dirs(untracked == UntrackedFiles::All)
+        // The status cache learns from ignored entries where new files could appear
+        .include_ignored(include_ignored && untracked != UntrackedFiles::No)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true)
         .include_unmodified(false)
         .renames_head_to_index(true)
         .renames_index_to_workdir(true)
         .no_refresh(false); // Keep false to get real-time status
 
     let statuses = repo.statuses(Some(&mut status_opt))?;
-    if !statuses.is_empty() {
-        let mut counts = StatusCounts::default();
-        for entry in statuses.iter() {
-            counts.increment(entry.status());
-        }
+    for entry in statuses.iter() {
+        visit(entry.status(), entry.path().ok());
+    }
 
-        for (label, count) in [
-            ("UU", counts.conflicted),
-            ("AM", counts.added_modified),
-            ("MM", counts.modified_modified),
-            ("M", counts.modified),
-            ("D", counts.deleted),
-            ("R", counts.renamed),
-            ("T", counts.typechanged),
-            ("A", counts.added),
-            ("??", counts.untracked),
-            ("!", counts.ignored),
-            ("X", counts.fallback),
-        ] {
-            if count > 0 {
-                status.push(format!("{label} {count}"));
+    // A status scan ignores `diff.ignoreSubmodules` and `submodule.<name>.ignore` in git
+    // config, so submodules are checked one by one instead
+    for submodule in repo.submodules()? {
+        if let Ok(name) = submodule.name()
+            && let Ok(submodule_status) = repo.submodule_status(name, config.submodule_ignore(name))
+        {
+            let entry = submodule_entry(submodule_status);
+            if !entry.is_empty() {
+                visit(entry, None);
             }
         }
     }
-    Ok(status.join(" "))
+    Ok(())
 }
 
 /// Generates the path for the Git authentication cache file for a given repository.
@@ -307,6 +837,18 @@ pub fn get_status(repo: &Repository) -> Result<String, Error> {
 /// otherwise `None`.
 #[must_use]
 pub fn get_auth_cache_path(repo: &Repository) -> Option<PathBuf> {
+    cache_file(repo, "auth")
+}
+
+/// Generates the path of the file holding the last prompt data `slick precmd` printed
+/// for a repository, next to its authentication cache.
+#[must_use]
+pub fn get_prompt_cache_path(repo: &Repository) -> Option<PathBuf> {
+    cache_file(repo, "prompt")
+}
+
+/// `<cache dir>/slick/<prefix>_<hash of the repository root>`.
+pub(crate) fn cache_file(repo: &Repository, prefix: &str) -> Option<PathBuf> {
     // Use workdir (repo root) instead of .git path for stable cache key
     // Canonicalize to get absolute path and resolve symlinks
     let repo_path = repo
@@ -326,7 +868,80 @@ pub fn get_auth_cache_path(repo: &Repository) -> Option<PathBuf> {
     });
 
     let cache_path = PathBuf::from(cache_dir).join("slick");
-    Some(cache_path.join(format!("auth_{hash:x}")))
+    Some(cache_path.join(format!("{prefix}_{hash:x}")))
+}
+
+/// How long a repository keeps the cheaper status scan after one missed its deadline.
+const SLOW_STATUS_TTL: u64 = 24 * 60 * 60;
+
+/// Records that a status scan of `repo` missed its deadline, so the next scans skip
+/// untracked files when `SLICK_PROMPT_GIT_STATUS_FALLBACK` is on.
+pub fn mark_status_slow(repo: &Repository) {
+    let Some(path) = cache_file(repo, "slow") else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, unix_timestamp().to_string());
+}
+
+/// Whether a status scan of `repo` missed its deadline within [`SLOW_STATUS_TTL`].
+fn is_status_slow(repo: &Repository) -> bool {
+    cache_file(repo, "slow")
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| content.trim().parse::<u64>().ok())
+        .is_some_and(|marked| unix_timestamp().saturating_sub(marked) < SLOW_STATUS_TTL)
+}
+
+/// Saves `prompt` as the last prompt data for `repo`, unless it is already saved.
+///
+/// The file is replaced atomically, so a shell reading it never sees half a line. A
+/// status that missed its deadline is not saved over the last known one.
+pub fn write_cached_prompt(repo: &Repository, prompt: &Prompt) {
+    if prompt.status_timed_out {
+        return;
+    }
+    let Some(path) = get_prompt_cache_path(repo) else {
+        return;
+    };
+    let Ok(serialized) = serde_json::to_string(prompt) else {
+        return;
+    };
+    if fs::read_to_string(&path).is_ok_and(|saved| saved == serialized) {
+        return;
+    }
+    write_cache_file(&path, &serialized);
+}
+
+/// Replaces `path` through a temporary file, so a prompt reading it concurrently never
+/// sees half of it.
+pub(crate) fn write_cache_file(path: &Path, contents: &str) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let temporary = path.with_extension(process::id().to_string());
+    if fs::write(&temporary, contents).is_ok() && fs::rename(&temporary, path).is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+}
+
+/// Reads the last prompt data saved for the repository containing `dir`, marked as
+/// [`Phase::Cached`]. Returns `None` outside a repository, in one with `slick.disable`
+/// set, or when nothing readable was saved.
+#[must_use]
+pub fn read_cached_prompt(dir: &Path) -> Option<Prompt> {
+    let repo = Repository::discover(dir).ok()?;
+    if RepoOverrides::read(&repo).disable {
+        return None;
+    }
+    let saved = fs::read_to_string(get_prompt_cache_path(&repo)?).ok()?;
+    let (mut prompt, error) = Prompt::decode(&saved);
+    if error.is_some() {
+        return None;
+    }
+    prompt.phase = Phase::Cached;
+    Some(prompt)
 }
 
 /// Reads the cached Git fetch status for a given repository.
@@ -0,0 +1,205 @@
+//! `slick init <shell>`: the shell integration, embedded in the binary.
+//!
+//! The scripts are the same `slick.zsh`, `slick.bash` and `slick.fish` shipped in the
+//! repository, preceded by a few lines that pin the binary they were printed by,
+//! bake in the chosen options and stamp the version. The loaders pass the stamp to
+//! `slick prompt`, which reports a loader left over from another version (for
+//! example in a shell started before an upgrade) instead of rendering with it.
+use clap::ArgMatches;
+use std::env;
+
+const SLICK_ZSH: &str = include_str!("../slick.zsh");
+const SLICK_BASH: &str = include_str!("../slick.bash");
+const SLICK_FISH: &str = include_str!("../slick.fish");
+
+/// The environment variable the loaders pass the version stamp in.
+pub const LOADER_VERSION_ENV: &str = "_SLICK_PROMPT_LOADER_VERSION";
+
+/// Shells `slick init` prints an integration for.
+pub const SHELLS: &[&str] = &["zsh", "bash", "fish"];
+
+/// What to bake into the printed script.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// The binary the loader runs unless `SLICK_PATH` is already set.
+    pub slick_path: Option<String>,
+    /// Sets `SLICK_PROMPT_TRANSIENT=0`.
+    pub no_transient: bool,
+    /// Sets an empty `SLICK_PROMPT_CURSOR_SHAPE`.
+    pub no_cursor: bool,
+}
+
+/// Quotes `value` for `shell`.
+fn quote(shell: &str, value: &str) -> String {
+    if shell == "fish" {
+        format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Returns the lines that set up `options` in `shell`.
+fn header(shell: &str, options: &Options) -> Vec<String> {
+    let mut exports = Vec::new();
+    if options.no_transient {
+        exports.push(("SLICK_PROMPT_TRANSIENT", "0"));
+    }
+    if options.no_cursor {
+        exports.push(("SLICK_PROMPT_CURSOR_SHAPE", ""));
+    }
+    let version = env!("CARGO_PKG_VERSION");
+
+    let mut lines = vec![format!("# Generated by `slick init {shell}` {version}")];
+    if shell == "fish" {
+        if let Some(path) = &options.slick_path {
+            lines.push(format!(
+                "set -q SLICK_PATH; or set -g SLICK_PATH {}",
+                quote(shell, path)
+            ));
+        }
+        for (name, value) in exports {
+            lines.push(format!("set -gx {name} {}", quote(shell, value)));
+        }
+        lines.push(format!("set -g slick_prompt_loader_version {version}"));
+    } else {
+        if let Some(path) = &options.slick_path {
+            lines.push(format!(
+                "[[ -n \"${{SLICK_PATH:-}}\" ]] || SLICK_PATH={}",
+                quote(shell, path)
+            ));
+        }
+        for (name, value) in exports {
+            lines.push(format!("export {name}={}", quote(shell, value)));
+        }
+        lines.push(format!("slick_prompt_loader_version={version}"));
+    }
+    lines
+}
+
+/// Returns the integration script for `shell`, or `None` for an unknown shell.
+#[must_use]
+pub fn script(shell: &str, options: &Options) -> Option<String> {
+    let loader = match shell {
+        "zsh" => SLICK_ZSH,
+        "bash" => SLICK_BASH,
+        "fish" => SLICK_FISH,
+        _ => return None,
+    };
+
+    // Keep the shebang first so the output also works as a file.
+    let (shebang, body) = loader.split_once('\n').unwrap_or(("", loader));
+    let mut script = String::with_capacity(loader.len() + 256);
+    script.push_str(shebang);
+    script.push('\n');
+    for line in header(shell, options) {
+        script.push_str(&line);
+        script.push('\n');
+    }
+    script.push_str(body);
+    Some(script)
+}
+
+/// The warning `slick prompt` shows when the loader was printed by another version.
+#[must_use]
+pub fn loader_mismatch(loader_version: &str, shell: &str) -> Option<String> {
+    let version = env!("CARGO_PKG_VERSION");
+    if loader_version.is_empty() || loader_version == version {
+        return None;
+    }
+
+    let reload = if shell == "fish" {
+        format!("slick init {shell} | source")
+    } else {
+        format!("eval \"$(slick init {shell})\"")
+    };
+    Some(format!(
+        "slick {version} was loaded by a {loader_version} integration; reload it with: {reload}"
+    ))
+}
+
+pub fn display(matches: &ArgMatches) {
+    let shell = matches
+        .get_one::<String>("shell")
+        .map_or("zsh", String::as_str);
+    let options = Options {
+        slick_path: env::current_exe()
+            .ok()
+            .and_then(|path| path.to_str().map(str::to_string)),
+        no_transient: matches.get_flag("no_transient"),
+        no_cursor: matches.get_flag("no_cursor"),
+    };
+
+    if let Some(script) = script(shell, &options) {
+        print!("{script}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Options, SHELLS, loader_mismatch, script};
+
+    #[test]
+    fn test_scripts_keep_the_shebang_first_and_stamp_the_version() {
+        for shell in SHELLS {
+            let script = script(shell, &Options::default()).unwrap_or_default();
+            assert!(
+                script.starts_with(&format!("#!/usr/bin/env {shell}\n# Generated by")),
+                "{shell}"
+            );
+            assert!(
+                script.contains(&format!(
+                    "slick_prompt_loader_version {}",
+                    env!("CARGO_PKG_VERSION")
+                )) || script.contains(&format!(
+                    "slick_prompt_loader_version={}",
+                    env!("CARGO_PKG_VERSION")
+                )),
+                "{shell}"
+            );
+        }
+        assert!(script("tcsh", &Options::default()).is_none());
+    }
+
+    #[test]
+    fn test_options_are_baked_in() {
+        let options = Options {
+            slick_path: Some("/opt/it's/slick".to_string()),
+            no_transient: true,
+            no_cursor: true,
+        };
+
+        let zsh = script("zsh", &options).unwrap_or_default();
+        assert!(
+            zsh.contains("\n[[ -n \"${SLICK_PATH:-}\" ]] || SLICK_PATH='/opt/it'\\''s/slick'\n"),
+            "{zsh}"
+        );
+        assert!(
+            zsh.contains("\nexport SLICK_PROMPT_TRANSIENT='0'\n"),
+            "{zsh}"
+        );
+        assert!(
+            zsh.contains("\nexport SLICK_PROMPT_CURSOR_SHAPE=''\n"),
+            "{zsh}"
+        );
+
+        let fish = script("fish", &options).unwrap_or_default();
+        assert!(
+            fish.contains("\nset -q SLICK_PATH; or set -g SLICK_PATH '/opt/it\\'s/slick'\n"),
+            "{fish}"
+        );
+        assert!(
+            fish.contains("\nset -gx SLICK_PROMPT_TRANSIENT '0'\n"),
+            "{fish}"
+        );
+    }
+
+    #[test]
+    fn test_loader_mismatch() {
+        assert_eq!(loader_mismatch("", "zsh"), None);
+        assert_eq!(loader_mismatch(env!("CARGO_PKG_VERSION"), "zsh"), None);
+
+        let warning = loader_mismatch("0.0.1", "fish").unwrap_or_default();
+        assert!(warning.contains("0.0.1 integration"), "{warning}");
+        assert!(warning.ends_with("slick init fish | source"), "{warning}");
+    }
+}
@@ -0,0 +1,394 @@
+//! Prompt layout templates.
+//!
+//! `SLICK_PROMPT_FORMAT`, `SLICK_PROMPT_TRANSIENT_FORMAT` and `SLICK_PROMPT_RIGHT_FORMAT`
+//! arrange the prompt segments with placeholders such as `{path}` and `{git_branch}`.
+//! Literal text is printed as-is, `{{` and `}}` print a brace, and `{newline}` (or a
+//! newline in the template) starts a new line.
+//!
+//! Spaces only separate segments that rendered something: spaces next to an empty
+//! segment, at the start of a line or at its end are dropped, so
+//! `{path} {git_status} {symbol}` never leaves a double space when the status is clean.
+//!
+//! Laying out a template produces [`Token`]s, which hold no shell syntax at all; a
+//! [`Backend`](crate::backend::Backend) turns them into prompt text.
+use crate::style::Style;
+use serde::Serialize;
+
+/// A piece of the prompt that a template can place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// `%n`/`%m` for root users and SSH sessions.
+    Identity,
+    /// The transient prompt timestamp.
+    Timestamp,
+    /// The current time, as `HH:MM:SS`.
+    Clock,
+    /// The toolbox, `DevPod`, AWS, Kubernetes and Python markers.
+    Context,
+    /// The git user name.
+    GitUser,
+    /// The current directory.
+    Path,
+    /// Every git segment below, in the default order.
+    Git,
+    GitBranch,
+    GitStatus,
+    GitRemote,
+    GitAction,
+    GitStaged,
+    /// Lines added and removed since `HEAD`, when `SLICK_PROMPT_GIT_DIFFSTAT` is on.
+    GitDiffstat,
+    /// The authentication-failure or unreachable-remote marker.
+    GitFetch,
+    /// How long the last command ran, past `SLICK_PROMPT_CMD_MAX_EXEC_TIME`.
+    Elapsed,
+    Newline,
+    /// The prompt symbol, which also resets the color.
+    Symbol,
+}
+
+/// Placeholder names, in the order they are documented.
+pub const SEGMENTS: &[(&str, Segment)] = &[
+    ("identity", Segment::Identity),
+    ("timestamp", Segment::Timestamp),
+    ("clock", Segment::Clock),
+    ("context", Segment::Context),
+    ("git_user", Segment::GitUser),
+    ("path", Segment::Path),
+    ("git", Segment::Git),
+    ("git_branch", Segment::GitBranch),
+    ("git_status", Segment::GitStatus),
+    ("git_remote", Segment::GitRemote),
+    ("git_action", Segment::GitAction),
+    ("git_staged", Segment::GitStaged),
+    ("git_diffstat", Segment::GitDiffstat),
+    ("git_fetch", Segment::GitFetch),
+    ("elapsed", Segment::Elapsed),
+    ("newline", Segment::Newline),
+    ("symbol", Segment::Symbol),
+];
+
+impl Segment {
+    /// Returns the segment for a placeholder name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        SEGMENTS
+            .iter()
+            .find_map(|(segment_name, segment)| (*segment_name == name).then_some(*segment))
+    }
+}
+
+/// What a piece of the prompt shows, so backends can tell segments apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Identity,
+    Timestamp,
+    Clock,
+    Context,
+    GitUser,
+    Path,
+    GitBranch,
+    GitStatus,
+    GitRemote,
+    GitAction,
+    GitStaged,
+    GitDiffstat,
+    GitFetch,
+    /// Marks git data read from the cache, shown until `slick precmd` answers.
+    GitCached,
+    Elapsed,
+    Symbol,
+    /// A problem with the setup, shown above the prompt.
+    Warning,
+    /// Literal text from the template.
+    Text,
+}
+
+/// The text of a span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Text {
+    /// Text from the repository, the environment or the template; backends escape it.
+    Literal(String),
+    /// A configured symbol, such as the prompt symbol, printed as-is.
+    Verbatim(String),
+    /// The user name.
+    UserName,
+    /// The short host name.
+    HostName,
+    /// The current directory, with the home directory shown as `~`.
+    Directory,
+    /// The current time, as `HH:MM:SS`.
+    Clock,
+    /// Resets the foreground color, so the command line is typed uncolored.
+    ResetColor,
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Self::Literal(text)
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Self::Literal(text.to_string())
+    }
+}
+
+/// Text printed in one style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub style: Style,
+    pub text: Text,
+}
+
+/// Styled text a segment renders to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece {
+    pub role: Role,
+    /// Spans printed back to back, such as a branch symbol and the branch name.
+    pub spans: Vec<Span>,
+    /// Whether powerline mode draws this piece as a colored block.
+    pub block: bool,
+}
+
+impl Piece {
+    /// A single span drawn as a block in powerline mode.
+    #[must_use]
+    pub fn new(role: Role, style: &Style, text: impl Into<Text>) -> Self {
+        Self {
+            role,
+            spans: vec![Span {
+                style: style.clone(),
+                text: text.into(),
+            }],
+            block: true,
+        }
+    }
+
+    /// Unstyled text that is never drawn as a block.
+    #[must_use]
+    pub fn plain(role: Role, text: impl Into<Text>) -> Self {
+        Self {
+            block: false,
+            ..Self::new(role, &Style::default(), text)
+        }
+    }
+
+    /// Appends another span.
+    #[must_use]
+    pub fn with(mut self, style: &Style, text: impl Into<Text>) -> Self {
+        self.spans.push(Span {
+            style: style.clone(),
+            text: text.into(),
+        });
+        self
+    }
+
+    /// The style of the last span, which colors the whole block in powerline mode.
+    #[must_use]
+    pub fn style(&self) -> Option<&Style> {
+        self.spans.last().map(|span| &span.style)
+    }
+}
+
+/// A laid-out prompt: pieces with the spaces and line breaks that survived between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Piece(Piece),
+    Space(String),
+    Newline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Text(String),
+    Space(String),
+    Segment(Segment),
+}
+
+/// A parsed layout template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    items: Vec<Item>,
+}
+
+impl Layout {
+    /// Parses a template, returning `None` for an unknown placeholder or an unbalanced brace.
+    #[must_use]
+    pub fn parse(template: &str) -> Option<Self> {
+        let mut items = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(character) = chars.next() {
+            match character {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next()? {
+                            '}' => break,
+                            character => name.push(character),
+                        }
+                    }
+                    push_literal(&mut items, &mut literal);
+                    items.push(Item::Segment(Segment::from_name(name.trim())?));
+                }
+                '}' => return None,
+                '\n' => {
+                    push_literal(&mut items, &mut literal);
+                    items.push(Item::Segment(Segment::Newline));
+                }
+                character => literal.push(character),
+            }
+        }
+        push_literal(&mut items, &mut literal);
+
+        Some(Self { items })
+    }
+
+    /// Lays out the segments.
+    ///
+    /// `render` fills a segment (no pieces hide it). The pieces of one segment are
+    /// separated by a space.
+    pub fn tokens(&self, mut render: impl FnMut(Segment) -> Vec<Piece>) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut line_has_content = false;
+        let mut pending_space: Option<&str> = None;
+
+        for item in &self.items {
+            let pieces = match item {
+                Item::Space(space) => {
+                    if line_has_content && pending_space.is_none() {
+                        pending_space = Some(space);
+                    }
+                    continue;
+                }
+                Item::Segment(Segment::Newline) => {
+                    tokens.push(Token::Newline);
+                    line_has_content = false;
+                    pending_space = None;
+                    continue;
+                }
+                Item::Text(text) => vec![Piece::plain(Role::Text, text.as_str())],
+                Item::Segment(segment) => render(*segment),
+            };
+
+            if pieces.is_empty() {
+                continue;
+            }
+            if let Some(space) = pending_space.take() {
+                tokens.push(Token::Space(space.to_string()));
+            }
+            for (index, piece) in pieces.into_iter().enumerate() {
+                if index > 0 {
+                    tokens.push(Token::Space(" ".to_string()));
+                }
+                tokens.push(Token::Piece(piece));
+            }
+            line_has_content = true;
+        }
+
+        tokens
+    }
+}
+
+/// Splits literal text into runs of spaces and other text.
+fn push_literal(items: &mut Vec<Item>, literal: &mut String) {
+    let mut run = String::new();
+    let mut run_is_space = false;
+
+    for character in literal.drain(..) {
+        let is_space = character == ' ' || character == '\t';
+        if is_space != run_is_space && !run.is_empty() {
+            items.push(literal_item(run_is_space, std::mem::take(&mut run)));
+        }
+        run_is_space = is_space;
+        run.push(character);
+    }
+    if !run.is_empty() {
+        items.push(literal_item(run_is_space, run));
+    }
+}
+
+fn literal_item(is_space: bool, text: String) -> Item {
+    if is_space {
+        Item::Space(text)
+    } else {
+        Item::Text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, Piece, Role, Segment, Text};
+    use crate::backend::{
+        Backend,
+        zsh::{PromptLiteralEncoding, Zsh},
+    };
+
+    fn render(template: &str, filled: &[(Segment, &str)]) -> Option<String> {
+        Layout::parse(template).map(|layout| {
+            let tokens = layout.tokens(|segment| {
+                filled
+                    .iter()
+                    .filter(|(filled, _)| *filled == segment)
+                    .map(|(_, text)| Piece::plain(Role::Text, Text::Verbatim((*text).to_string())))
+                    .collect()
+            });
+            Zsh::new(PromptLiteralEncoding::Backslash, false).render(&tokens)
+        })
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_placeholders_and_stray_braces() {
+        assert!(Layout::parse("{path} {symbol}").is_some());
+        assert!(Layout::parse("{ path }").is_some());
+        assert!(Layout::parse("").is_some());
+        assert!(Layout::parse("{nope}").is_none());
+        assert!(Layout::parse("{path").is_none());
+        assert!(Layout::parse("path}").is_none());
+    }
+
+    #[test]
+    fn test_spaces_around_empty_segments_are_dropped() {
+        let filled = [(Segment::Path, "~"), (Segment::Symbol, "$")];
+
+        assert_eq!(
+            render("{identity} {path} {git_status} {symbol}", &filled).as_deref(),
+            Some("~ $")
+        );
+        assert_eq!(
+            render(
+                "{context} {path}",
+                &[(Segment::Context, "(a)"), (Segment::Context, "(b)")]
+            )
+            .as_deref(),
+            Some("(a) (b)")
+        );
+        assert_eq!(
+            render("{path} {elapsed}{newline}{context} {symbol}", &filled).as_deref(),
+            Some("~\n$")
+        );
+    }
+
+    #[test]
+    fn test_literal_text_and_braces() {
+        let filled = [(Segment::Path, "~"), (Segment::GitBranch, "main")];
+
+        assert_eq!(
+            render("{path} on {git_branch} {{100%}}", &filled).as_deref(),
+            Some("~ on main {100%%}")
+        );
+        assert_eq!(render("{path}\n> ", &filled).as_deref(), Some("~\n>"));
+    }
+}
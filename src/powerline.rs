@@ -0,0 +1,174 @@
+//! Powerline rendering: each piece becomes a block with a background color.
+//!
+//! A block takes its background from the piece's style, the background when it has
+//! one and the foreground color otherwise, and prints its text in
+//! `SLICK_PROMPT_POWERLINE_TEXT_COLOR`. Neighbouring blocks are joined by
+//! `SLICK_PROMPT_POWERLINE_SEPARATOR`, drawn in the left block's color over the
+//! right block's background, and the last block of a run ends with the separator on
+//! the terminal background. Plain pieces such as the prompt symbol or literal
+//! template text, and line breaks, end the run.
+use crate::{
+    backend::Backend,
+    layout::{Piece, Token},
+    style::{Color, Style},
+};
+
+/// The block colors for `piece`: its background and the style its text is printed in.
+fn block_style(piece: &Piece, text_color: &Style) -> (Color, Style) {
+    let style = piece.style().cloned().unwrap_or_default();
+    let (background, foreground) = match (style.bg, style.fg) {
+        (Some(background), foreground) => (background, foreground),
+        (None, Some(background)) => (background, text_color.fg.clone()),
+        (None, None) => (Color::default(), text_color.fg.clone()),
+    };
+
+    let text_style = Style {
+        fg: foreground,
+        bg: Some(background.clone()),
+        ..style
+    };
+    (background, text_style)
+}
+
+fn separator_style(left: Color, right: Option<Color>) -> Style {
+    Style {
+        fg: Some(left),
+        bg: right,
+        ..Style::default()
+    }
+}
+
+/// Closes the open block, if any, with a separator on the terminal background.
+fn close<B: Backend + ?Sized>(
+    backend: &B,
+    output: &mut String,
+    open: &mut Option<Color>,
+    separator: &str,
+) {
+    if let Some(left) = open.take() {
+        output.push_str(&backend.paint(&separator_style(left, None), separator));
+        output.push_str(&backend.reset_color());
+    }
+}
+
+/// Prints laid-out tokens as powerline blocks with `backend`.
+#[must_use]
+pub fn render<B: Backend + ?Sized>(
+    backend: &B,
+    tokens: &[Token],
+    separator: &str,
+    text_color: &Style,
+) -> String {
+    let mut output = String::with_capacity(512);
+    let mut open: Option<Color> = None;
+    let mut pending_space: Option<&str> = None;
+
+    for token in tokens {
+        match token {
+            Token::Piece(piece) if piece.block => {
+                let (background, text_style) = block_style(piece, text_color);
+                if let Some(left) = open.take() {
+                    output.push_str(
+                        &backend.paint(&separator_style(left, Some(background.clone())), separator),
+                    );
+                } else if let Some(space) = pending_space {
+                    output.push_str(space);
+                }
+                pending_space = None;
+
+                let text: String = piece
+                    .spans
+                    .iter()
+                    .map(|span| backend.text(&span.text))
+                    .collect();
+                output.push_str(&backend.paint(&text_style, &format!(" {text} ")));
+                open = Some(background);
+            }
+            Token::Piece(piece) => {
+                close(backend, &mut output, &mut open, separator);
+                if let Some(space) = pending_space.take() {
+                    output.push_str(space);
+                }
+                output.push_str(&backend.piece(piece));
+            }
+            Token::Space(space) => pending_space = Some(space),
+            Token::Newline => {
+                close(backend, &mut output, &mut open, separator);
+                pending_space = None;
+                output.push('\n');
+            }
+        }
+    }
+    close(backend, &mut output, &mut open, separator);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::zsh::{PromptLiteralEncoding, Zsh},
+        layout::{Piece, Role, Text, Token},
+        style::Style,
+    };
+
+    fn render(tokens: &[Token], separator: &str, text_color: &Style) -> String {
+        let zsh = Zsh::new(PromptLiteralEncoding::Backslash, false);
+        super::render(&zsh, tokens, separator, text_color)
+    }
+
+    fn style(spec: &str) -> Style {
+        Style::parse(spec).unwrap_or_default()
+    }
+
+    fn space() -> Token {
+        Token::Space(" ".to_string())
+    }
+
+    #[test]
+    fn test_blocks_are_joined_by_separators() {
+        let tokens = [
+            Token::Piece(Piece::new(Role::Path, &style("74"), Text::Directory)),
+            space(),
+            Token::Piece(
+                Piece::new(Role::GitBranch, &style("2"), "\u{e0a0} ").with(&style("3"), "main"),
+            ),
+            Token::Newline,
+            Token::Piece(Piece::plain(Role::Symbol, Text::Verbatim("$".to_string()))),
+        ];
+
+        assert_eq!(
+            render(&tokens, ">", &style("0")),
+            "%F{0}%K{74} %~ %k%F{74}%K{3}>%k%F{0}%K{3} \u{e0a0} main %k%F{3}>%f\n$"
+        );
+    }
+
+    #[test]
+    fn test_styles_with_a_background_keep_their_foreground() {
+        let tokens = [Token::Piece(Piece::new(
+            Role::Context,
+            &style("bold 15 on 52"),
+            "x",
+        ))];
+
+        assert_eq!(
+            render(&tokens, "", &style("0")),
+            "%F{15}%K{52}%B x %b%k%F{52}%f"
+        );
+    }
+
+    #[test]
+    fn test_plain_pieces_end_the_run() {
+        let tokens = [
+            Token::Piece(Piece::plain(Role::Text, "[")),
+            Token::Piece(Piece::new(Role::GitStatus, &style("5"), "M 1")),
+            space(),
+            Token::Piece(Piece::plain(Role::Text, "|")),
+        ];
+
+        assert_eq!(
+            render(&tokens, ">", &style("0")),
+            "[%F{0}%K{5} M 1 %k%F{5}>%f |"
+        );
+    }
+}
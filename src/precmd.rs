@@ -1,27 +1,25 @@
-use crate::get_env;
-use crate::git;
+use crate::{daemon, diffstat, git, settings, status_cache, watch};
 use git2::Repository;
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 use std::{
+    cell::RefCell,
     env,
     ffi::OsStr,
     fs,
     io::{self, Write},
-    path::Path,
-    process::{Command as StdCommand, Output, Stdio},
+    path::{Path, PathBuf},
+    process::{Command as StdCommand, Output, Stdio, exit},
     thread::sleep,
     time::Duration,
 };
 use tokio::{
     process::Command,
     spawn,
-    task::{JoinHandle, spawn_blocking},
+    task::{JoinError, JoinHandle, spawn_blocking},
     time::timeout,
 };
 
-const DEFAULT_GIT_FETCH_TIMEOUT_SECS: u64 = 5;
-
 /// Patterns in `git fetch` stderr that mean "the remote rejected our credentials".
 const AUTH_FAILURE_PATTERNS: [&str; 5] = [
     "permission denied",
@@ -35,17 +33,12 @@ const AUTH_FAILURE_PATTERNS: [&str; 5] = [
 ///
 /// The fetch runs after the prompt is already on screen, so this deadline does not
 /// delay the prompt; it only bounds how long the background process may linger.
-fn git_fetch_timeout() -> Duration {
-    get_env("SLICK_PROMPT_GIT_FETCH_TIMEOUT")
-        .parse::<u64>()
-        .map_or_else(
-            |_| Duration::from_secs(DEFAULT_GIT_FETCH_TIMEOUT_SECS),
-            Duration::from_secs,
-        )
+fn git_fetch_timeout(overrides: &git::RepoOverrides) -> Duration {
+    overrides.fetch_timeout()
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum GitFetchOutcome {
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum GitFetchOutcome {
     Completed,
     SpawnFailed,
     TimedOut,
@@ -182,6 +175,28 @@ async fn run_git_fetch(
     GitFetchOutcome::Completed
 }
 
+/// Runs `git fetch` in `fetch_path`, recording the result in the auth cache. The
+/// daemon passes the fetch variables of the shell it fetches for in `fetch_env`.
+pub(crate) async fn fetch_repository(
+    cache_path: Option<PathBuf>,
+    fetch_path: &Path,
+    fetch_timeout: Duration,
+    fetch_env: Option<&daemon::FetchEnv>,
+) -> GitFetchOutcome {
+    // Create cache directory if cache path exists
+    if let Some(ref cache) = cache_path
+        && let Some(parent) = cache.parent()
+    {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let mut command = git_fetch_command(OsStr::new("git"), fetch_path);
+    if let Some(fetch_env) = fetch_env {
+        daemon::apply_fetch_env(&mut command, fetch_env);
+    }
+    run_git_fetch(command, cache_path.as_deref(), fetch_timeout).await
+}
+
 async fn join_git_fetch(handle: JoinHandle<GitFetchOutcome>) -> Option<GitFetchOutcome> {
     handle.await.ok()
 }
@@ -208,34 +223,130 @@ fn refresh_after_fetch(repo_path: &Path, prompt: &mut git::Prompt) -> bool {
         return false;
     }
 
+    prompt.phase = git::Phase::PostFetch;
     prompt.remote = remote;
     prompt.auth_failed = auth_failed;
     prompt.fetch_failed = fetch_failed;
     true
 }
 
+/// Prints one phase line. Write errors are ignored: zsh closes the pipe early when a
+/// command starts before every phase is in.
+fn emit(prompt: &git::Prompt) {
+    if let Ok(serialized) = serde_json::to_string(prompt) {
+        let _ = writeln!(io::stdout(), "{serialized}");
+        // Flush so each phase reaches the shell before the next one starts
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Waits for a blocking task until `deadline` passes; zero waits as long as it takes.
+/// `None` means the task is still running.
+async fn join_within<T>(handle: JoinHandle<T>, deadline: Duration) -> Option<Result<T, JoinError>> {
+    if deadline.is_zero() {
+        Some(handle.await)
+    } else {
+        timeout(deadline, handle).await.ok()
+    }
+}
+
+/// Counts the lines changed since `HEAD` in the blocking pool, when
+/// `SLICK_PROMPT_GIT_DIFFSTAT` is on. The count reads every changed file, so it runs
+/// beside the status scan and is printed after it.
+fn spawn_diffstat(git_dir: PathBuf) -> Option<JoinHandle<Option<diffstat::DiffStat>>> {
+    settings().git.diffstat.then(|| {
+        spawn_blocking(move || {
+            // TEST: SLICK_TEST_DIFFSTAT_DELAY=N delays the count by N seconds
+            if let Ok(delay_str) = env::var("SLICK_TEST_DIFFSTAT_DELAY")
+                && let Ok(delay_secs) = delay_str.parse::<u64>()
+            {
+                sleep(Duration::from_secs(delay_secs));
+            }
+            let repo = Repository::open(&git_dir).ok()?;
+            diffstat::diffstat(&repo, settings().git.diffstat_max_files)
+                .ok()
+                .flatten()
+        })
+    })
+}
+
+/// Prints the line counts on a line of their own once they arrive, if the status
+/// phase was printed. They get the same deadline as the status (zero waits as long as
+/// it takes); returns true if the count missed it and is still running.
+async fn emit_diffstat(
+    handle: Option<JoinHandle<Option<diffstat::DiffStat>>>,
+    repo: &Repository,
+    prompt: &RefCell<git::Prompt>,
+    deadline: Duration,
+) -> bool {
+    let Some(handle) = handle.filter(|_| {
+        let prompt = prompt.borrow();
+        prompt.phase == git::Phase::Status && !prompt.status_timed_out
+    }) else {
+        return false;
+    };
+    let Some(diffstat) = join_within(handle, deadline).await else {
+        return true;
+    };
+    if let Ok(Some(diffstat)) = diffstat {
+        let mut prompt = prompt.borrow_mut();
+        prompt.diffstat = Some(diffstat);
+        emit(&prompt);
+        git::write_cached_prompt(repo, &prompt);
+    }
+    false
+}
+
+/// Re-emits the prompt once the fetch completes and has moved something.
+async fn emit_post_fetch(
+    handle: Option<JoinHandle<GitFetchOutcome>>,
+    repo: &Repository,
+    prompt: &RefCell<git::Prompt>,
+) {
+    if let Some(handle) = handle
+        && join_git_fetch(handle).await == Some(GitFetchOutcome::Completed)
+    {
+        let mut prompt = prompt.borrow_mut();
+        if refresh_after_fetch(repo.path(), &mut prompt) {
+            emit(&prompt);
+            git::write_cached_prompt(repo, &prompt);
+        }
+    }
+}
+
 pub async fn render() {
     // Check if we're in a git repository
     let repo_result = env::current_dir()
         .ok()
         .and_then(|path| Repository::discover(path).ok());
 
-    if let Some(repo) = repo_result {
+    // `slick.disable` in git config hides git information for this repository.
+    let repo_result = repo_result
+        .map(|repo| {
+            let overrides = git::RepoOverrides::read(&repo);
+            (repo, overrides)
+        })
+        .filter(|(_, overrides)| !overrides.disable);
+
+    if let Some((repo, overrides)) = repo_result {
         // Inside git repo: Output git info in 2 phases
         // Phase 1: Output all fast/local git info immediately (no blocking)
-        let mut prompt = git::build_prompt_fast(&repo);
+        let mut prompt = git::build_prompt_fast_with(&repo, &overrides);
+        emit(&prompt);
 
-        if let Ok(serialized) = serde_json::to_string(&prompt) {
-            // Ignore broken pipe errors (happens when zsh closes the pipe early)
-            let _ = writeln!(io::stdout(), "{serialized}");
-            // Force flush to ensure immediate output before Phase 2 starts
-            let _ = io::stdout().flush();
-        }
+        // Watch mode starts watching before the status scan, so nothing that changes
+        // while the phases run is missed.
+        let watcher = if settings().git.watch {
+            watch::Watcher::new(repo.path())
+        } else {
+            None
+        };
 
         // Phase 2a: Spawn blocking task for slow git status (CPU-bound)
         let repo_path = repo.path().to_path_buf();
         let repo_for_refresh = repo_path.clone();
-        let status_handle = spawn_blocking(move || -> Option<String> {
+        let status_overrides = overrides.clone();
+        let status_handle = spawn_blocking(move || -> Option<git::StatusReport> {
             // TEST: Simulate slow git status (for testing non-blocking behavior)
             // Set SLICK_TEST_DELAY=N to add N seconds delay (e.g., SLICK_TEST_DELAY=1)
             // Note: Using thread::sleep here (not tokio::time::sleep) because spawn_blocking
@@ -247,9 +358,16 @@ pub async fn render() {
                 sleep(Duration::from_secs(delay_secs));
             }
 
+            // A running `slick daemon` may already have the status of this repository
+            let status_settings = git::StatusSettings::current();
+            if let Some(status) = daemon::status(&repo_path, &status_settings) {
+                return Some(status);
+            }
+
             // Re-open repository in the blocking thread pool
             if let Ok(repo) = Repository::open(&repo_path)
-                && let Ok(status) = git::get_status(&repo)
+                && let Ok(status) =
+                    status_cache::get_status(&repo, &status_overrides, &status_settings)
             {
                 return Some(status);
             }
@@ -258,66 +376,95 @@ pub async fn render() {
 
         // Phase 2b: Async git fetch with auth detection and cache update
         // This spawns a tokio task that checks auth status and updates cache
-        let fetch_handle = if matches!(
-            get_env("SLICK_PROMPT_GIT_FETCH"),
-            "0" | "false" | "no" | "off"
-        ) {
-            None
-        } else {
+        let fetch_handle = if overrides.fetch() {
             let cache_path = git::get_auth_cache_path(&repo);
             let fetch_path = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
 
+            let git_dir = repo.path().to_path_buf();
+            let fetch_timeout = git_fetch_timeout(&overrides);
+
             Some(spawn(async move {
-                // Create cache directory if cache path exists
-                if let Some(ref cache) = cache_path
-                    && let Some(parent) = cache.parent()
+                // A running `slick daemon` shares one fetch between every shell in the
+                // repository; ask it off the runtime thread so phase 2 is not held up.
+                if let Ok(Some(outcome)) =
+                    spawn_blocking(move || daemon::fetch(&git_dir, fetch_timeout)).await
                 {
-                    let _ = fs::create_dir_all(parent);
+                    return outcome;
                 }
-
-                let command = git_fetch_command(OsStr::new("git"), &fetch_path);
-                run_git_fetch(command, cache_path.as_deref(), git_fetch_timeout()).await
+                fetch_repository(cache_path, &fetch_path, fetch_timeout, None).await
             }))
+        } else {
+            None
         };
 
-        // Wait for git status (fast ~10-50ms), output immediately
-        if let Some(status) = status_handle.await.ok().flatten() {
-            prompt.status = status;
-            if let Ok(serialized) = serde_json::to_string(&prompt) {
-                let _ = writeln!(io::stdout(), "{serialized}");
-                let _ = io::stdout().flush();
+        // Phase 2c: Line counts for `{git_diffstat}`, off by default
+        let diffstat_handle = spawn_diffstat(repo_for_refresh.clone());
+
+        // Wait for git status (fast ~10-50ms), output immediately. In a huge repository
+        // it can take seconds; past the deadline the prompt shows that it is unknown.
+        let status_timeout = overrides.status_timeout();
+        let status = join_within(status_handle, status_timeout).await;
+        let status_timed_out = status.is_none();
+        if status_timed_out {
+            prompt.phase = git::Phase::Status;
+            prompt.status_timed_out = true;
+            emit(&prompt);
+            if settings().git.status_fallback {
+                git::mark_status_slow(&repo);
             }
+        } else if let Some(report) = status.and_then(Result::ok).flatten() {
+            prompt.phase = git::Phase::Status;
+            prompt.set_status(&report);
+            emit(&prompt);
+            // Shown by `slick prompt --cached` before phase 1 next time
+            git::write_cached_prompt(&repo, &prompt);
         }
 
         // Phase 3: the fetch above may have moved the remote refs, so the ahead/behind
         // counts emitted in phase 1 can be stale. Recompute once the fetch settles and
         // re-emit only when something actually changed, to avoid a pointless redraw.
         // The deadline is enforced inside the task so timeout cleanup can kill and reap the child.
-        if let Some(handle) = fetch_handle
-            && join_git_fetch(handle).await == Some(GitFetchOutcome::Completed)
-            && refresh_after_fetch(&repo_for_refresh, &mut prompt)
-            && let Ok(serialized) = serde_json::to_string(&prompt)
-        {
-            let _ = writeln!(io::stdout(), "{serialized}");
-            let _ = io::stdout().flush();
+        // The line counts are awaited alongside, so whichever is ready first is printed first.
+        let prompt_cell = RefCell::new(prompt);
+        let (diffstat_timed_out, ()) = tokio::join!(
+            emit_diffstat(diffstat_handle, &repo, &prompt_cell, status_timeout),
+            emit_post_fetch(fetch_handle, &repo, &prompt_cell),
+        );
+        let mut prompt = prompt_cell.into_inner();
+
+        // Watch mode: stay attached and re-emit the status whenever the repository
+        // changes, until the shell stops reading or the repository goes quiet. After a
+        // missed deadline, the first change replaces the unknown status.
+        if let Some(watcher) = watcher {
+            prompt.phase = git::Phase::Status;
+            if let Ok(last) = serde_json::to_string(&prompt) {
+                let idle = settings().git.watch_timeout;
+                let _ = spawn_blocking(move || {
+                    watcher.run(&repo_for_refresh, &overrides, last, idle);
+                })
+                .await;
+            }
+        }
+
+        // The status scan and the line counts cannot be interrupted, and the runtime
+        // would wait for them on shutdown; exit instead of lingering after the user has
+        // moved on.
+        if status_timed_out || diffstat_timed_out {
+            exit(0);
         }
     } else {
         // Outside git repo: Output empty prompt data (ensures handler fires for elapsed time)
-        let prompt = git::Prompt::default();
-        if let Ok(serialized) = serde_json::to_string(&prompt) {
-            let _ = writeln!(io::stdout(), "{serialized}");
-            let _ = io::stdout().flush();
-        }
+        emit(&git::Prompt::default());
     }
 }
 
 #[cfg(all(test, unix))]
 mod tests {
     use super::{
-        DEFAULT_GIT_FETCH_TIMEOUT_SECS, GitFetchOutcome, classify_fetch_output, git_fetch_command,
-        git_fetch_timeout, join_git_fetch, run_git_fetch,
+        GitFetchOutcome, classify_fetch_output, git_fetch_command, git_fetch_timeout,
+        join_git_fetch, run_git_fetch,
     };
-    use crate::git::FetchStatus;
+    use crate::git::{FetchStatus, RepoOverrides};
     use std::{
         error::Error,
         fs, io,
@@ -566,8 +713,8 @@ mod tests {
         // SLICK_PROMPT_GIT_FETCH_TIMEOUT is unset in the test environment, so the
         // cached default applies.
         assert_eq!(
-            git_fetch_timeout(),
-            Duration::from_secs(DEFAULT_GIT_FETCH_TIMEOUT_SECS)
+            git_fetch_timeout(&RepoOverrides::default()),
+            Duration::from_secs(5)
         );
     }
 }
@@ -1,44 +1,28 @@
-use crate::{context::collect_context_markers, get_env, get_env_var};
-use clap::ArgMatches;
-use serde::{Deserialize, Serialize};
+use crate::{
+    backend::{self, Backend},
+    config,
+    context::collect_context_markers,
+    git::{self, DataError, Phase, Prompt},
+    init,
+    layout::{Layout, Piece, Role, Segment, Text, Token},
+    settings,
+    status_format::{Category, StatusFormat},
+    style::Style,
+};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use std::{
-    env,
-    fmt::Write as _,
-    fs,
+    env, fs,
     path::{Component, Path, PathBuf},
     process::exit,
     time::{Duration, SystemTime},
 };
 use uzers::get_current_uid;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
-#[serde(default)]
-struct Prompt {
-    action: String,
-    branch: String,
-    remote: Vec<String>,
-    staged: bool,
-    status: String,
-    u_name: String,
-    auth_failed: bool,
-    fetch_failed: bool,
-}
-
-const TRANSIENT_TIMESTAMP_COLOR: &str = "8";
-const INTERNAL_DOLLAR_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_DOLLAR";
-const INTERNAL_BACKTICK_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_BACKTICK";
-const INTERNAL_BACKSLASH_PSVAR_ENV: &str = "_SLICK_PROMPT_PSVAR_BACKSLASH";
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum PromptLiteralEncoding {
-    Backslash,
-    Psvar {
-        dollar: usize,
-        backtick: usize,
-        backslash: usize,
-    },
-}
+const TIMESTAMP_COLOR: &str = "8";
 
+fn timestamp_style() -> Style {
+    Style::parse(TIMESTAMP_COLOR).unwrap_or_default()
+}
 fn is_root() -> bool {
     get_current_uid() == 0
 }
@@ -47,96 +31,38 @@ fn is_remote() -> bool {
     env::var("SSH_CONNECTION").is_ok()
 }
 
-fn append_identity_prefix(prompt: &mut String, is_root_user: bool, is_remote_user: bool) {
+fn append_identity_prefix(pieces: &mut Vec<Piece>, is_root_user: bool, is_remote_user: bool) {
     if is_remote_user {
         if is_root_user {
-            let _ = write!(
-                prompt,
-                "%F{{{}}}%n%F{{{}}}@%m ",
-                get_env("SLICK_PROMPT_ROOT_COLOR"),
-                get_env("SLICK_PROMPT_SSH_COLOR")
+            pieces.push(
+                Piece::new(Role::Identity, &settings().root_color, Text::UserName)
+                    .with(&settings().ssh_color, "@")
+                    .with(&settings().ssh_color, Text::HostName),
             );
         } else {
-            let _ = write!(prompt, "%F{{{}}}%n@%m ", get_env("SLICK_PROMPT_SSH_COLOR"));
+            pieces.push(
+                Piece::new(Role::Identity, &settings().ssh_color, Text::UserName)
+                    .with(&settings().ssh_color, "@")
+                    .with(&settings().ssh_color, Text::HostName),
+            );
         }
     } else if is_root_user {
-        let _ = write!(prompt, "%F{{{}}}%n ", get_env("SLICK_PROMPT_ROOT_COLOR"));
+        pieces.push(Piece::new(
+            Role::Identity,
+            &settings().root_color,
+            Text::UserName,
+        ));
     }
 }
 
-fn append_context_markers(prompt: &mut String, encoding: PromptLiteralEncoding) {
-    let short = get_env("SLICK_PROMPT_SHORT_CONTEXT") == "1";
+fn append_context_markers(pieces: &mut Vec<Piece>) {
+    let short = settings().context.short;
     for marker in collect_context_markers(short) {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            marker.color,
-            escape_prompt_literal(&marker.text, encoding)
-        );
-    }
-}
-
-fn parse_psvar_index(value: &str) -> Option<usize> {
-    value.parse::<usize>().ok().filter(|index| *index > 0)
-}
-
-fn prompt_literal_encoding(dollar: &str, backtick: &str, backslash: &str) -> PromptLiteralEncoding {
-    match (
-        parse_psvar_index(dollar),
-        parse_psvar_index(backtick),
-        parse_psvar_index(backslash),
-    ) {
-        (Some(dollar), Some(backtick), Some(backslash)) => PromptLiteralEncoding::Psvar {
-            dollar,
-            backtick,
-            backslash,
-        },
-        _ => PromptLiteralEncoding::Backslash,
-    }
-}
-
-fn current_prompt_literal_encoding() -> PromptLiteralEncoding {
-    prompt_literal_encoding(
-        &get_env_var(INTERNAL_DOLLAR_PSVAR_ENV),
-        &get_env_var(INTERNAL_BACKTICK_PSVAR_ENV),
-        &get_env_var(INTERNAL_BACKSLASH_PSVAR_ENV),
-    )
-}
-
-fn escape_prompt_literal(segment: &str, encoding: PromptLiteralEncoding) -> String {
-    let mut escaped = String::with_capacity(segment.len());
-    for character in segment.chars() {
-        match character {
-            '%' => escaped.push_str("%%"),
-            '\\' | '$' | '`' if encoding == PromptLiteralEncoding::Backslash => {
-                escaped.push('\\');
-                escaped.push(character);
-            }
-            '$' => {
-                if let PromptLiteralEncoding::Psvar { dollar, .. } = encoding {
-                    let _ = write!(escaped, "%{dollar}v");
-                }
-            }
-            '`' => {
-                if let PromptLiteralEncoding::Psvar { backtick, .. } = encoding {
-                    let _ = write!(escaped, "%{backtick}v");
-                }
-            }
-            '\\' => {
-                if let PromptLiteralEncoding::Psvar { backslash, .. } = encoding {
-                    let _ = write!(escaped, "%{backslash}v");
-                }
-            }
-            _ => escaped.push(character),
-        }
+        pieces.push(Piece::new(Role::Context, &marker.style, marker.text));
     }
-    escaped
 }
 
-fn compact_path_segments<'a>(
-    segments: impl Iterator<Item = &'a str>,
-    encoding: PromptLiteralEncoding,
-) -> String {
+fn compact_path_segments<'a>(segments: impl Iterator<Item = &'a str>) -> String {
     let parts: Vec<&str> = segments.collect();
     if parts.is_empty() {
         return String::new();
@@ -149,16 +75,16 @@ fn compact_path_segments<'a>(
         }
 
         if index + 1 == parts.len() {
-            compacted.push_str(&escape_prompt_literal(part, encoding));
+            compacted.push_str(part);
         } else if let Some(ch) = part.chars().next() {
-            compacted.push_str(&escape_prompt_literal(&ch.to_string(), encoding));
+            compacted.push(ch);
         }
     }
 
     compacted
 }
 
-fn compact_path(path: &Path, home: Option<&Path>, encoding: PromptLiteralEncoding) -> String {
+fn compact_path(path: &Path, home: Option<&Path>) -> String {
     if let Some(home) = home
         && let Ok(relative) = path.strip_prefix(home)
     {
@@ -167,7 +93,6 @@ fn compact_path(path: &Path, home: Option<&Path>, encoding: PromptLiteralEncodin
                 .iter()
                 .filter_map(|segment| segment.to_str())
                 .filter(|segment| !segment.is_empty()),
-            encoding,
         );
 
         return if rendered.is_empty() {
@@ -196,7 +121,7 @@ fn compact_path(path: &Path, home: Option<&Path>, encoding: PromptLiteralEncodin
         }
     }
 
-    let rendered = compact_path_segments(segments.into_iter(), encoding);
+    let rendered = compact_path_segments(segments.into_iter());
     if rendered.is_empty() {
         if prefix.is_empty() {
             ".".to_string()
@@ -212,9 +137,9 @@ fn compact_path(path: &Path, home: Option<&Path>, encoding: PromptLiteralEncodin
     }
 }
 
-fn current_path_symbol(encoding: PromptLiteralEncoding) -> String {
-    if get_env("SLICK_PROMPT_SHORT_PATH") != "1" {
-        return "%~".to_string();
+fn current_path() -> Text {
+    if !settings().path.short {
+        return Text::Directory;
     }
 
     let current_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
@@ -223,56 +148,59 @@ fn current_path_symbol(encoding: PromptLiteralEncoding) -> String {
         .map(PathBuf::from)
         .map(|home| fs::canonicalize(&home).unwrap_or(home));
 
-    compact_path(&current_dir, home_dir.as_deref(), encoding)
+    Text::Literal(compact_path(&current_dir, home_dir.as_deref()))
 }
 
-fn append_branch(prompt: &mut String, branch: &str, encoding: PromptLiteralEncoding) {
+/// Appends the branch symbol and name as one piece.
+///
+/// `repo_color` is the repository's `slick.branchColor`, if any; it wins over the
+/// configured branch styles.
+fn append_branch(pieces: &mut Vec<Piece>, branch: &str, repo_color: &str) {
     if branch.is_empty() {
         return;
     }
 
-    let branch_color = if branch == "master" || branch == "main" {
-        get_env("SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR")
+    let repo_color = Style::parse(repo_color);
+    let branch_color = if let Some(color) = &repo_color {
+        color
+    } else if branch == "master" || branch == "main" {
+        &settings().git.main_branch_color
     } else {
-        get_env("SLICK_PROMPT_GIT_BRANCH_COLOR")
+        &settings().git.branch_color
     };
-    let branch_symbol = get_env("SLICK_PROMPT_GIT_BRANCH_SYMBOL");
-
-    if !branch_symbol.is_empty() {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_BRANCH_SYMBOL_COLOR"),
-            branch_symbol
-        );
-    }
+    let branch_symbol = &settings().git.branch_symbol;
 
-    let _ = write!(
-        prompt,
-        "%F{{{branch_color}}}{}",
-        escape_prompt_literal(branch, encoding)
-    );
+    pieces.push(if branch_symbol.is_empty() {
+        Piece::new(Role::GitBranch, branch_color, branch)
+    } else {
+        Piece::new(
+            Role::GitBranch,
+            &settings().git.branch_symbol_color,
+            Text::Verbatim(format!("{branch_symbol} ")),
+        )
+        .with(branch_color, branch)
+    });
 }
 
-fn prompt_symbol(keymap: &str, last_return_code: &str, is_root_user: bool) -> (String, String) {
-    let vicmd_symbol = get_env("SLICK_PROMPT_VICMD_SYMBOL");
+fn prompt_symbol(keymap: &str, last_return_code: &str, is_root_user: bool) -> (String, Style) {
+    let vicmd_symbol = &settings().vicmd_symbol;
     let symbol = if keymap == "vicmd" {
         vicmd_symbol
     } else if is_root_user {
-        get_env("SLICK_PROMPT_ROOT_SYMBOL")
+        &settings().root_symbol
     } else {
-        get_env("SLICK_PROMPT_SYMBOL")
+        &settings().symbol
     };
 
     let color = if keymap == "vicmd" {
-        get_env("SLICK_PROMPT_VICMD_COLOR")
+        &settings().vicmd_color
     } else if last_return_code == "0" {
-        get_env("SLICK_PROMPT_SYMBOL_COLOR")
+        &settings().symbol_color
     } else {
-        get_env("SLICK_PROMPT_ERROR_COLOR")
+        &settings().error_color
     };
 
-    (symbol.to_string(), color.to_string())
+    (symbol.clone(), color.clone())
 }
 
 fn elapsed_from_timestamp(matches: &ArgMatches) -> u64 {
@@ -308,223 +236,416 @@ fn parse_time_elapsed(matches: &ArgMatches) -> u64 {
     )
 }
 
-/// `1`, `true`, `yes`, and `on` hide the name; every other value safely leaves it visible.
-fn git_user_name_is_hidden(value: &str) -> bool {
-    ["1", "true", "yes", "on"]
-        .iter()
-        .any(|enabled| value.eq_ignore_ascii_case(enabled))
+/// Appends the git user name. `slick precmd` leaves `u_name` empty when the name
+/// is hidden, so a repository's `slick.hideUserName` can override the setting.
+fn append_git_user_name(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    if !deserialized.u_name.is_empty() {
+        pieces.push(Piece::new(
+            Role::GitUser,
+            &settings().git.uname_color,
+            deserialized.u_name.as_str(),
+        ));
+    }
 }
 
-fn append_git_user_name(
-    prompt: &mut String,
-    deserialized: &Prompt,
-    encoding: PromptLiteralEncoding,
-) {
-    if !git_user_name_is_hidden(&get_env_var("SLICK_PROMPT_NO_GIT_UNAME"))
-        && !deserialized.u_name.is_empty()
-    {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_UNAME_COLOR"),
-            escape_prompt_literal(&deserialized.u_name, encoding)
-        );
-    }
+fn append_git_branch(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    append_branch(pieces, &deserialized.branch, &deserialized.branch_color);
 }
 
-fn append_git_metadata(
-    prompt: &mut String,
-    deserialized: &Prompt,
-    encoding: PromptLiteralEncoding,
-) {
-    if !deserialized.branch.is_empty() {
-        append_branch(prompt, &deserialized.branch, encoding);
-        prompt.push(' ');
+fn append_git_status(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    if deserialized.status_timed_out {
+        pieces.push(Piece::new(
+            Role::GitStatus,
+            &settings().git.status_color,
+            Text::Verbatim(format!("[{}]", settings().git.status_timeout_symbol)),
+        ));
+    } else if deserialized.dirty {
+        pieces.push(Piece::new(
+            Role::GitStatus,
+            &settings().git.status_color,
+            Text::Verbatim(format!("[{}]", settings().git.dirty_symbol)),
+        ));
+    } else {
+        // The work-tree side; the staged side is the `git_staged` segment
+        let counts = &deserialized.counts;
+        pieces.extend(status_counts(
+            Role::GitStatus,
+            &settings().git.status_color,
+            &settings().git.status_format,
+            |category| category.count(&counts.unstaged, counts, false),
+        ));
     }
+}
 
-    if !deserialized.status.is_empty() {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}[{}] ",
-            get_env("SLICK_PROMPT_GIT_STATUS_COLOR"),
-            deserialized.status
-        );
+/// Fills a status template as one bracketed piece, each count in its category's
+/// color, or `None` when every count is zero.
+fn status_counts(
+    role: Role,
+    color: &Style,
+    format: &StatusFormat,
+    count: impl Fn(Category) -> u32,
+) -> Option<Piece> {
+    let counts = format.fill(count);
+    if counts.is_empty() {
+        return None;
+    }
+    let mut piece = Piece::new(role, color, "[");
+    for count in counts {
+        if !count.space.is_empty() {
+            piece = piece.with(color, count.space);
+        }
+        let category_color = settings().git.category_color(count.category);
+        piece = piece.with(category_color.unwrap_or(color), count.text);
     }
+    Some(piece.with(color, "]"))
+}
 
+fn append_git_remote(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
     if !deserialized.remote.is_empty() {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_REMOTE_COLOR"),
-            deserialized.remote.join(" ")
-        );
+        pieces.push(Piece::new(
+            Role::GitRemote,
+            &settings().git.remote_color,
+            deserialized.remote.join(" "),
+        ));
     }
+}
 
+fn append_git_action(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
     if !deserialized.action.is_empty() {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_ACTION_COLOR"),
-            deserialized.action
-        );
+        pieces.push(Piece::new(
+            Role::GitAction,
+            &settings().git.action_color,
+            deserialized.action.as_str(),
+        ));
     }
+}
 
-    if deserialized.staged {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}[staged] ",
-            get_env("SLICK_PROMPT_GIT_STAGED_COLOR"),
-        );
+fn append_git_staged(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    let counts = &deserialized.counts;
+    if let Some(piece) = status_counts(
+        Role::GitStaged,
+        &settings().git.staged_color,
+        &settings().git.staged_format,
+        |category| category.count(&counts.staged, counts, true),
+    ) {
+        pieces.push(piece);
+    } else if deserialized.staged && counts.staged.is_empty() {
+        // Only the first phase has run, or the status was not counted
+        pieces.push(Piece::new(
+            Role::GitStaged,
+            &settings().git.staged_color,
+            "[staged]",
+        ));
     }
+}
 
-    if deserialized.auth_failed {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_AUTH_COLOR"),
-            get_env("SLICK_PROMPT_GIT_AUTH_SYMBOL")
-        );
-    } else if deserialized.fetch_failed {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_GIT_OFFLINE_COLOR"),
-            get_env("SLICK_PROMPT_GIT_OFFLINE_SYMBOL")
-        );
+fn append_git_diffstat(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    let Some(diffstat) = deserialized.diffstat else {
+        return;
+    };
+    let mut counts = Vec::new();
+    if diffstat.insertions > 0 {
+        counts.push(format!(
+            "{}{}",
+            settings().git.diffstat_insertions_symbol,
+            diffstat.insertions
+        ));
+    }
+    if diffstat.deletions > 0 {
+        counts.push(format!(
+            "{}{}",
+            settings().git.diffstat_deletions_symbol,
+            diffstat.deletions
+        ));
+    }
+    if !counts.is_empty() {
+        pieces.push(Piece::new(
+            Role::GitDiffstat,
+            &settings().git.diffstat_color,
+            Text::Verbatim(counts.join(" ")),
+        ));
     }
 }
 
-fn append_elapsed(prompt: &mut String, time_elapsed: u64) {
-    let max_time = get_env("SLICK_PROMPT_CMD_MAX_EXEC_TIME")
-        .parse()
-        .unwrap_or(5);
-    if time_elapsed > max_time {
-        let _ = write!(
-            prompt,
-            "%F{{{}}}{} ",
-            get_env("SLICK_PROMPT_TIME_ELAPSED_COLOR"),
-            compound_duration::format_dhms(time_elapsed)
-        );
+fn append_git_fetch(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    if deserialized.auth_failed {
+        pieces.push(Piece::new(
+            Role::GitFetch,
+            &settings().git.auth_color,
+            Text::Verbatim(settings().git.auth_symbol.clone()),
+        ));
+    } else if deserialized.fetch_failed {
+        pieces.push(Piece::new(
+            Role::GitFetch,
+            &settings().git.offline_color,
+            Text::Verbatim(settings().git.offline_symbol.clone()),
+        ));
     }
 }
 
-fn trim_trailing_space(prompt: &mut String) {
-    if prompt.ends_with(' ') {
-        prompt.pop();
-    }
+/// Marks the git segments when the precmd data could not be read, so a truncated line
+/// or output from another `slick` binary does not pass for a clean repository. Data
+/// from another schema is still shown after the marker as far as it could be read.
+fn append_data_error(pieces: &mut Vec<Piece>) {
+    pieces.push(Piece::new(
+        Role::Warning,
+        &settings().error_color,
+        Text::Verbatim(settings().git.data_error_symbol.clone()),
+    ));
 }
 
-fn resolve_cursor_shape(configured_shape: &str, keymap: &str) -> Option<u8> {
-    if configured_shape == "dynamic" {
-        return Some(if matches!(keymap, "vicmd" | "visual") {
-            2
-        } else {
-            6
-        });
+/// Marks the git segments while they show the data saved by an earlier prompt, which
+/// may be out of date until `slick precmd` answers.
+fn append_cached(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    if deserialized.phase != Phase::Cached {
+        return;
     }
+    pieces.push(Piece::new(
+        Role::GitCached,
+        &settings().git.cached_color,
+        Text::Verbatim(settings().git.cached_symbol.clone()),
+    ));
+}
 
-    configured_shape
-        .parse::<u8>()
-        .ok()
-        .filter(|cursor_shape| *cursor_shape <= 6)
+fn append_git_metadata(pieces: &mut Vec<Piece>, deserialized: &Prompt) {
+    append_git_branch(pieces, deserialized);
+    append_git_status(pieces, deserialized);
+    append_git_remote(pieces, deserialized);
+    append_git_action(pieces, deserialized);
+    append_git_staged(pieces, deserialized);
+    append_git_diffstat(pieces, deserialized);
+    append_git_fetch(pieces, deserialized);
 }
 
-fn append_cursor_shape(prompt: &mut String, keymap: &str) {
-    let Some(cursor_shape) = resolve_cursor_shape(get_env("SLICK_PROMPT_CURSOR_SHAPE"), keymap)
-    else {
-        return;
-    };
+fn append_elapsed(pieces: &mut Vec<Piece>, time_elapsed: u64) {
+    if time_elapsed > settings().cmd_max_exec_time.as_secs() {
+        pieces.push(Piece::new(
+            Role::Elapsed,
+            &settings().time_elapsed_color,
+            compound_duration::format_dhms(time_elapsed),
+        ));
+    }
+}
 
-    let _ = write!(prompt, "%{{\x1b[{cursor_shape} q%}}");
+fn append_cursor_shape(prompt: &mut String, backend: &dyn Backend, keymap: &str) {
+    if let Some(cursor_shape) = settings().cursor_shape.for_keymap(keymap) {
+        prompt.push_str(&backend.cursor_shape(cursor_shape));
+    }
 }
 
-fn build_transient_prompt(
-    deserialized: &Prompt,
+/// The data every layout segment is rendered from.
+struct PromptParts<'a> {
+    deserialized: &'a Prompt,
+    data_error: Option<&'a DataError>,
     is_root_user: bool,
     is_remote_user: bool,
-    symbol: &str,
-    prompt_symbol_color: &str,
-    transient_timestamp: &str,
-    keymap: &str,
-) -> String {
-    let mut prompt = String::with_capacity(256);
-    let encoding = current_prompt_literal_encoding();
-
-    append_cursor_shape(&mut prompt, keymap);
-    append_identity_prefix(&mut prompt, is_root_user, is_remote_user);
+    symbol: &'a str,
+    symbol_style: &'a Style,
+    transient_timestamp: &'a str,
+    time_elapsed: u64,
+}
 
-    if !transient_timestamp.is_empty() {
-        let _ = write!(
-            prompt,
-            "%F{{{TRANSIENT_TIMESTAMP_COLOR}}}{transient_timestamp} "
-        );
+impl PromptParts<'_> {
+    /// Renders one segment into the pieces it is drawn from.
+    fn render(&self, segment: Segment) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        match segment {
+            Segment::Identity => {
+                append_identity_prefix(&mut pieces, self.is_root_user, self.is_remote_user);
+            }
+            Segment::Timestamp => {
+                if !self.transient_timestamp.is_empty() {
+                    pieces.push(Piece::new(
+                        Role::Timestamp,
+                        &timestamp_style(),
+                        self.transient_timestamp,
+                    ));
+                }
+            }
+            Segment::Clock => {
+                pieces.push(Piece::new(Role::Clock, &timestamp_style(), Text::Clock));
+            }
+            Segment::Context => append_context_markers(&mut pieces),
+            Segment::GitUser => append_git_user_name(&mut pieces, self.deserialized),
+            Segment::Path => {
+                pieces.push(Piece::new(
+                    Role::Path,
+                    &settings().path.color,
+                    current_path(),
+                ));
+            }
+            Segment::Git | Segment::GitBranch if self.data_error.is_some() => {
+                append_data_error(&mut pieces);
+                if segment == Segment::Git {
+                    append_git_metadata(&mut pieces, self.deserialized);
+                } else {
+                    append_git_branch(&mut pieces, self.deserialized);
+                }
+            }
+            Segment::Git => {
+                append_cached(&mut pieces, self.deserialized);
+                append_git_metadata(&mut pieces, self.deserialized);
+            }
+            Segment::GitBranch => {
+                append_cached(&mut pieces, self.deserialized);
+                append_git_branch(&mut pieces, self.deserialized);
+            }
+            Segment::GitStatus => append_git_status(&mut pieces, self.deserialized),
+            Segment::GitRemote => append_git_remote(&mut pieces, self.deserialized),
+            Segment::GitAction => append_git_action(&mut pieces, self.deserialized),
+            Segment::GitStaged => append_git_staged(&mut pieces, self.deserialized),
+            Segment::GitDiffstat => append_git_diffstat(&mut pieces, self.deserialized),
+            Segment::GitFetch => append_git_fetch(&mut pieces, self.deserialized),
+            Segment::Elapsed => append_elapsed(&mut pieces, self.time_elapsed),
+            Segment::Newline => {}
+            Segment::Symbol => {
+                let mut symbol = Piece::new(
+                    Role::Symbol,
+                    self.symbol_style,
+                    Text::Verbatim(self.symbol.to_string()),
+                )
+                .with(&Style::default(), Text::ResetColor)
+                .with(
+                    &Style::default(),
+                    Text::Verbatim(settings().non_breaking_space.clone()),
+                );
+                symbol.block = false;
+                pieces.push(symbol);
+            }
+        }
+        pieces
     }
+}
 
-    append_context_markers(&mut prompt, encoding);
-    let path_symbol = current_path_symbol(encoding);
-    let _ = write!(
-        prompt,
-        "%F{{{}}}{path_symbol}",
-        get_env("SLICK_PROMPT_PATH_COLOR")
-    );
-
-    if !deserialized.branch.is_empty() {
-        prompt.push(' ');
-        append_branch(&mut prompt, &deserialized.branch, encoding);
+fn render_layout(
+    backend: &dyn Backend,
+    layout: &Layout,
+    parts: &PromptParts,
+    powerline: bool,
+    warning: Option<&str>,
+) -> String {
+    let mut tokens = Vec::new();
+    if let Some(warning) = warning {
+        tokens.push(Token::Piece(Piece::new(
+            Role::Warning,
+            &settings().error_color,
+            warning,
+        )));
+        tokens.push(Token::Newline);
+    }
+    tokens.extend(layout.tokens(|segment| parts.render(segment)));
+
+    if powerline && settings().powerline.enabled {
+        backend.render_powerline(&tokens, &settings().powerline)
+    } else {
+        backend.render(&tokens)
     }
-
-    let _ = write!(
-        prompt,
-        " %F{{{}}}{}%f{}",
-        prompt_symbol_color,
-        symbol,
-        get_env("SLICK_PROMPT_NON_BREAKING_SPACE"),
-    );
-
-    prompt
 }
 
-fn build_full_prompt(
-    deserialized: &Prompt,
-    is_root_user: bool,
-    is_remote_user: bool,
-    symbol: &str,
-    prompt_symbol_color: &str,
-    time_elapsed: u64,
+/// Renders `layout` after the cursor-shape escape for `keymap`, with `warning` on a
+/// line of its own above it.
+fn build_prompt(
+    backend: &dyn Backend,
+    layout: &Layout,
+    parts: &PromptParts,
     keymap: &str,
+    warning: Option<&str>,
 ) -> String {
     let mut prompt = String::with_capacity(256);
-    let encoding = current_prompt_literal_encoding();
-
-    append_cursor_shape(&mut prompt, keymap);
-    append_identity_prefix(&mut prompt, is_root_user, is_remote_user);
+    append_cursor_shape(&mut prompt, backend, keymap);
+    prompt.push_str(&render_layout(backend, layout, parts, true, warning));
+    prompt
+}
 
-    append_context_markers(&mut prompt, encoding);
-    append_git_user_name(&mut prompt, deserialized, encoding);
+/// Renders the right-hand layout. It carries no cursor-shape escape; the left prompt
+/// already set it.
+fn build_right_prompt(backend: &dyn Backend, parts: &PromptParts) -> String {
+    let right = render_layout(backend, &settings().right_format, parts, false, None);
+    if right.is_empty() {
+        return right;
+    }
+    format!("{right}{}", backend.reset_color())
+}
 
-    let path_symbol = current_path_symbol(encoding);
-    let _ = write!(
-        prompt,
-        "%F{{{}}}{path_symbol} ",
-        get_env("SLICK_PROMPT_PATH_COLOR")
-    );
+/// Reduces an RFC 3339 timestamp such as `2026-05-09T21:47:04+02:00` to `21:47:04`.
+///
+/// Timestamps that are already short, or not RFC 3339, are returned unchanged.
+fn shorten_transient_timestamp(timestamp: &str, short: bool) -> &str {
+    if !short {
+        return timestamp;
+    }
 
-    append_git_metadata(&mut prompt, deserialized, encoding);
-    append_elapsed(&mut prompt, time_elapsed);
-    trim_trailing_space(&mut prompt);
+    timestamp
+        .split_once('T')
+        .and_then(|(_, time)| time.get(..8))
+        .unwrap_or(timestamp)
+}
 
-    let _ = write!(
-        prompt,
-        "\n%F{{{}}}{}%f{}",
-        prompt_symbol_color,
-        symbol,
-        get_env("SLICK_PROMPT_NON_BREAKING_SPACE"),
-    );
+/// The warning for a shell integration printed by another version of `slick init`.
+fn loader_warning(matches: &ArgMatches) -> Option<String> {
+    let shell = match matches.get_one::<String>("backend").map(String::as_str) {
+        Some("bash") => "bash",
+        Some("fish") => "fish",
+        _ => "zsh",
+    };
+    init::loader_mismatch(&crate::get_env_var(init::LOADER_VERSION_ENV), shell)
+}
 
-    prompt
+/// The `slick prompt` command line, shared with `slick serve`.
+#[must_use]
+pub fn command() -> Command {
+    Command::new("prompt")
+        .about("prompt")
+        .override_help(config::prompt_help())
+        .arg(
+            Arg::new("transient")
+                .long("transient")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("right")
+                .long("right")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("transient"),
+        )
+        .arg(
+            Arg::new("with_right")
+                .long("with-right")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["transient", "right"]),
+        )
+        .arg(Arg::new("cached").long("cached").action(ArgAction::SetTrue))
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .num_args(1)
+                .value_parser(backend::BACKENDS.to_vec())
+                .default_value("zsh"),
+        )
+        .arg(
+            Arg::new("transient_timestamp")
+                .long("transient-timestamp")
+                .num_args(1),
+        )
+        .arg(Arg::new("last_return_code").short('r').num_args(1))
+        .arg(Arg::new("keymap").short('k').num_args(1))
+        .arg(Arg::new("data").short('d').num_args(1))
+        .arg(Arg::new("time").short('t').num_args(1))
+        .arg(
+            Arg::new("elapsed")
+                .short('e')
+                .num_args(1)
+                .allow_negative_numbers(true),
+        )
 }
 
 pub fn display(matches: &ArgMatches) {
+    print!("{}", render(matches));
+}
+
+/// Renders the prompt `matches` asks for.
+#[must_use]
+pub fn render(matches: &ArgMatches) -> String {
     let keymap = matches
         .get_one("keymap")
         .map_or_else(|| "main".to_string(), String::clone);
@@ -534,162 +655,136 @@ pub fn display(matches: &ArgMatches) {
     let serialized = matches
         .get_one("data")
         .map_or_else(String::new, String::clone);
-    let deserialized: Prompt =
-        serde_json::from_str(&serialized).unwrap_or_else(|_| Prompt::default());
-    let transient = matches.get_flag("transient");
-    let transient_timestamp = matches
-        .get_one::<String>("transient_timestamp")
-        .map_or("", String::as_str);
+    let (mut deserialized, data_error) = Prompt::decode(&serialized);
+    // Until the first `slick precmd` phase arrives, show what it printed last time
+    if serialized.is_empty()
+        && matches.get_flag("cached")
+        && let Some(cached) = env::current_dir()
+            .ok()
+            .and_then(|dir| git::read_cached_prompt(&dir))
+    {
+        deserialized = cached;
+    }
+    // The shell integration only knows about the environment, so the config file's
+    // `[transient]` table is applied here.
+    let transient = matches.get_flag("transient") && settings().transient.enabled;
+    let transient_timestamp = shorten_transient_timestamp(
+        matches
+            .get_one::<String>("transient_timestamp")
+            .map_or("", String::as_str),
+        settings().transient.short_timestamp,
+    );
 
     let is_root_user = is_root();
-    let is_remote_user = is_remote();
-    let (symbol, prompt_symbol_color) = prompt_symbol(&keymap, &last_return_code, is_root_user);
-
-    if transient {
-        print!(
-            "{}",
-            build_transient_prompt(
-                &deserialized,
-                is_root_user,
-                is_remote_user,
-                &symbol,
-                &prompt_symbol_color,
-                transient_timestamp,
-                &keymap,
-            )
-        );
-        return;
+    let (symbol, symbol_style) = prompt_symbol(&keymap, &last_return_code, is_root_user);
+    let parts = PromptParts {
+        deserialized: &deserialized,
+        data_error: data_error.as_ref(),
+        is_root_user,
+        is_remote_user: is_remote(),
+        symbol: &symbol,
+        symbol_style: &symbol_style,
+        transient_timestamp,
+        time_elapsed: if transient {
+            0
+        } else {
+            parse_time_elapsed(matches)
+        },
+    };
+    let backend = matches
+        .get_one::<String>("backend")
+        .and_then(|name| backend::from_name(name))
+        .unwrap_or_else(|| Box::new(backend::zsh::Zsh::from_env()));
+
+    if matches.get_flag("right") {
+        return build_right_prompt(backend.as_ref(), &parts);
     }
 
-    print!(
-        "{}",
-        build_full_prompt(
-            &deserialized,
-            is_root_user,
-            is_remote_user,
-            &symbol,
-            &prompt_symbol_color,
-            parse_time_elapsed(matches),
-            &keymap,
-        )
+    let (layout, warning) = if transient {
+        (&settings().transient.format, None)
+    } else {
+        (&settings().format, loader_warning(matches))
+    };
+    let prompt = build_prompt(
+        backend.as_ref(),
+        layout,
+        &parts,
+        &keymap,
+        warning.as_deref(),
     );
+    if matches.get_flag("with_right") {
+        return format!("{prompt}\0{}", build_right_prompt(backend.as_ref(), &parts));
+    }
+    prompt
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{
-        PromptLiteralEncoding, append_branch, compact_path, compact_path_segments,
-        escape_prompt_literal, git_user_name_is_hidden, prompt_literal_encoding,
+    use super::{append_branch, compact_path, compact_path_segments, shorten_transient_timestamp};
+    use crate::backend::{
+        Backend,
+        zsh::{PromptLiteralEncoding, Zsh, escape_prompt_literal},
     };
     use std::path::Path;
 
     #[test]
-    fn test_escape_prompt_literal() {
+    fn test_compact_path_segments_escape_prompt_syntax() {
         let psvar = PromptLiteralEncoding::Psvar {
             dollar: 11,
             backtick: 12,
             backslash: 13,
         };
-        let cases = [
-            ("plain text", "plain text", "plain text"),
-            ("%n", "%%n", "%%n"),
-            ("$(id)", r"\$(id)", "%11v(id)"),
-            ("`id`", r"\`id\`", "%12vid%12v"),
-            (r"\$(id)", r"\\\$(id)", "%13v%11v(id)"),
-            (
-                r"before %n $(id) `id` \ after",
-                r"before %%n \$(id) \`id\` \\ after",
-                r"before %%n %11v(id) %12vid%12v %13v after",
-            ),
-        ];
-
-        for (input, direct, canonical) in cases {
-            assert_eq!(
-                escape_prompt_literal(input, PromptLiteralEncoding::Backslash),
-                direct,
-                "direct caller, input: {input:?}"
-            );
-            assert_eq!(
-                escape_prompt_literal(input, psvar),
-                canonical,
-                "canonical loader, input: {input:?}"
-            );
-        }
+        let compacted = compact_path_segments(["%parent", r"$(id)%n`id`\"].into_iter());
+        assert_eq!(
+            escape_prompt_literal(&compacted, PromptLiteralEncoding::Backslash),
+            r"%%/\$(id)%%n\`id\`\\"
+        );
+        assert_eq!(
+            escape_prompt_literal(&compacted, psvar),
+            r"%%/%11v(id)%%n%12vid%12v%13v"
+        );
     }
 
     #[test]
-    fn test_prompt_literal_encoding_defaults_safely() {
+    fn test_shorten_transient_timestamp() {
         let cases = [
-            ("", "", "", PromptLiteralEncoding::Backslash),
-            ("1", "2", "", PromptLiteralEncoding::Backslash),
-            ("1", "invalid", "3", PromptLiteralEncoding::Backslash),
+            ("2026-05-09T21:47:04+02:00", true, "21:47:04"),
             (
-                "11",
-                "12",
-                "13",
-                PromptLiteralEncoding::Psvar {
-                    dollar: 11,
-                    backtick: 12,
-                    backslash: 13,
-                },
+                "2026-05-09T21:47:04+02:00",
+                false,
+                "2026-05-09T21:47:04+02:00",
             ),
+            ("21:47:04", true, "21:47:04"),
+            ("", true, ""),
         ];
 
-        for (dollar, backtick, backslash, expected) in cases {
+        for (timestamp, short, expected) in cases {
             assert_eq!(
-                prompt_literal_encoding(dollar, backtick, backslash),
+                shorten_transient_timestamp(timestamp, short),
                 expected,
-                "values: {dollar:?}, {backtick:?}, {backslash:?}"
+                "timestamp: {timestamp:?}, short: {short}"
             );
         }
     }
 
-    #[test]
-    fn test_compact_path_segments_escape_prompt_syntax() {
-        let psvar = PromptLiteralEncoding::Psvar {
-            dollar: 11,
-            backtick: 12,
-            backslash: 13,
-        };
-        assert_eq!(
-            compact_path_segments(
-                ["%parent", r"$(id)%n`id`\"].into_iter(),
-                PromptLiteralEncoding::Backslash,
-            ),
-            r"%%/\$(id)%%n\`id\`\\"
-        );
-        assert_eq!(
-            compact_path_segments(["%parent", r"$(id)%n`id`\"].into_iter(), psvar),
-            r"%%/%11v(id)%%n%12vid%12v%13v"
-        );
+    fn branch(name: &str, repo_color: &str) -> String {
+        let zsh = Zsh::new(PromptLiteralEncoding::Backslash, false);
+        let mut pieces = Vec::new();
+        append_branch(&mut pieces, name, repo_color);
+        pieces.iter().map(|piece| zsh.piece(piece)).collect()
     }
 
     #[test]
-    fn test_git_user_name_hidden_boolean_values() {
-        let cases = [
-            ("1", true),
-            ("true", true),
-            ("TRUE", true),
-            ("YeS", true),
-            ("oN", true),
-            ("0", false),
-            ("false", false),
-            ("NO", false),
-            ("Off", false),
-            ("", false),
-            ("invalid", false),
-        ];
-
-        for (value, expected) in cases {
-            assert_eq!(git_user_name_is_hidden(value), expected, "value: {value:?}");
-        }
+    fn test_append_branch_uses_separate_symbol_color() {
+        assert_eq!(branch("main", ""), "%F{2} %F{160}main");
     }
 
     #[test]
-    fn test_append_branch_uses_separate_symbol_color() {
-        let mut prompt = String::new();
-        append_branch(&mut prompt, "main", PromptLiteralEncoding::Backslash);
-        assert_eq!(prompt, "%F{2} %F{160}main");
+    fn test_append_branch_prefers_repository_color() {
+        assert_eq!(branch("main", "42"), "%F{2} %F{42}main");
+
+        // Invalid colors from the precmd data are ignored.
+        assert_eq!(branch("topic", "}%F{1"), "%F{2} %F{3}topic");
     }
 
     #[test]
@@ -697,10 +792,7 @@ mod tests {
         let path = Path::new("/var/home/nbari/projects/rust/slick");
         let home = Path::new("/var/home/nbari");
 
-        assert_eq!(
-            compact_path(path, Some(home), PromptLiteralEncoding::Backslash),
-            "~/p/r/slick"
-        );
+        assert_eq!(compact_path(path, Some(home)), "~/p/r/slick");
     }
 
     #[test]
@@ -708,10 +800,7 @@ mod tests {
         let path = Path::new("/var/home/nbari/projects/rust/slick");
         let home = Path::new("/tmp/home");
 
-        assert_eq!(
-            compact_path(path, Some(home), PromptLiteralEncoding::Backslash),
-            "/v/h/n/p/r/slick"
-        );
+        assert_eq!(compact_path(path, Some(home)), "/v/h/n/p/r/slick");
     }
 
     #[test]
@@ -719,9 +808,6 @@ mod tests {
         let path = Path::new("/var/home/nbari");
         let home = Path::new("/var/home/nbari");
 
-        assert_eq!(
-            compact_path(path, Some(home), PromptLiteralEncoding::Backslash),
-            "~"
-        );
+        assert_eq!(compact_path(path, Some(home)), "~");
     }
 }
@@ -0,0 +1,119 @@
+//! `slick serve`: renders prompts for one shell from a long-lived process.
+//!
+//! The zsh integration starts it once per shell (with `SLICK_PROMPT_SERVE=1`) and
+//! sends it every render that would otherwise spawn `slick prompt`, so keymap
+//! switches and `precmd` phases cost a pipe round trip instead of a fork and exec,
+//! and the settings are resolved once per shell.
+//!
+//! The shell talks to it over a fifo and a process substitution rather than a zsh
+//! coprocess: a shell has only one coprocess, which the server would take from the
+//! user's own `coproc`, and a coprocess is a job, so it shows up in `jobs` and keeps
+//! the shell from exiting with "you have running jobs".
+use crate::prompt;
+use std::{
+    env,
+    io::{self, BufRead, Write},
+};
+
+pub const HELP: &str = "\
+Render prompts for one shell over stdin and stdout.
+
+On startup, prints the names of the environment variables a request should carry,
+separated by spaces and followed by a NUL byte. Each request is a block of lines
+ended by an empty line:
+
+  arg VALUE        one argument of `slick prompt`, in order
+  env NAME=VALUE   set an environment variable for this and later requests
+  unset NAME       remove an environment variable
+
+Each request is answered with the rendered prompt followed by a NUL byte (an empty
+answer when the arguments are invalid). With `--with-right`, the left and the right
+prompt are each followed by a NUL byte. Setting PWD also changes the working
+directory. The process exits when stdin is closed.";
+
+/// Variables the prompt reads on every render, which a shell can change between
+/// prompts. The shell sends their current values with each request.
+pub const REQUEST_ENV: &[&str] = &[
+    "PWD",
+    "HOME",
+    "USER",
+    "SSH_CONNECTION",
+    "COLORTERM",
+    "VIRTUAL_ENV",
+    "VIRTUAL_ENV_PROMPT",
+    "PIPENV_ACTIVE",
+    "PIPENV_ACTIVE_COLOR",
+    "PYENV_VERSION",
+    "AWS_PROFILE",
+    "AWS_REGION",
+    "AWS_DEFAULT_REGION",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "KUBECONFIG",
+    "DEVPOD",
+    "DEVPOD_WORKSPACE_ID",
+    "SLICK_TEST_TOOLBOXENV_PATH",
+    "SLICK_TEST_CONTAINERENV_PATH",
+];
+
+/// Applies one request line, collecting arguments into `args`.
+fn apply(line: &str, args: &mut Vec<String>) {
+    if let Some(arg) = line.strip_prefix("arg ") {
+        args.push(arg.to_string());
+    } else if let Some((name, value)) = line
+        .strip_prefix("env ")
+        .and_then(|assignment| assignment.split_once('='))
+    {
+        if name.is_empty() || name.contains('\0') || value.contains('\0') {
+            return;
+        }
+        // SAFETY: `slick serve` handles one request at a time on the main thread and
+        // starts no other threads that could read the environment concurrently.
+        unsafe { env::set_var(name, value) };
+        if name == "PWD" {
+            let _ = env::set_current_dir(value);
+        }
+    } else if let Some(name) = line.strip_prefix("unset ") {
+        if name.is_empty() || name.contains(['=', '\0']) {
+            return;
+        }
+        // SAFETY: as above.
+        unsafe { env::remove_var(name) };
+    }
+}
+
+/// Renders the prompt for `args`, the arguments after `slick prompt`.
+fn respond(args: &[String]) -> String {
+    prompt::command()
+        .try_get_matches_from(std::iter::once("prompt").chain(args.iter().map(String::as_str)))
+        .map_or_else(|_| String::new(), |matches| prompt::render(&matches))
+}
+
+/// Answers the requests read from `input` until it is closed.
+///
+/// # Errors
+///
+/// Returns an error when `output` cannot be written, for example once the shell
+/// that started the server is gone.
+pub fn serve(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    write!(output, "{}\0", REQUEST_ENV.join(" "))?;
+    output.flush()?;
+
+    let mut args = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.is_empty() {
+            write!(output, "{}\0", respond(&args))?;
+            output.flush()?;
+            args.clear();
+        } else {
+            apply(&line, &mut args);
+        }
+    }
+    Ok(())
+}
+
+pub fn run() {
+    let _ = serve(io::stdin().lock(), io::stdout().lock());
+}
@@ -0,0 +1,599 @@
+//! Typed, validated prompt settings.
+//!
+//! [`crate::get_env`] hands out the raw strings resolved from the environment,
+//! the config file and the defaults. [`Settings`] parses those strings once into
+//! styles, flags, durations and enums, and records a [`Warning`] for every value
+//! it had to reject instead of silently falling back.
+use crate::{
+    config::{Source, Warning, find_setting},
+    layout::{Layout, SEGMENTS},
+    status_format::{CATEGORIES, Category, StatusFormat},
+    style::Style,
+    theme::{self, THEMES, Theme, find_theme},
+};
+use std::time::Duration;
+
+/// The `DECSCUSR` cursor shape requested by `SLICK_PROMPT_CURSOR_SHAPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorShape {
+    /// Emit no cursor-shape escape at all.
+    Off,
+    /// A block in `vicmd`/`visual`, a bar everywhere else.
+    Dynamic,
+    /// The same shape (0-6) in every keymap.
+    Fixed(u8),
+}
+
+impl CursorShape {
+    /// Parses `dynamic`, a shape number from 0 to 6, or an empty string for [`Self::Off`].
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "" => Some(Self::Off),
+            "dynamic" => Some(Self::Dynamic),
+            shape => shape
+                .parse::<u8>()
+                .ok()
+                .filter(|shape| *shape <= 6)
+                .map(Self::Fixed),
+        }
+    }
+
+    /// The shape to emit for `keymap`, if any.
+    #[must_use]
+    pub fn for_keymap(self, keymap: &str) -> Option<u8> {
+        match self {
+            Self::Off => None,
+            Self::Dynamic => Some(if matches!(keymap, "vicmd" | "visual") {
+                2
+            } else {
+                6
+            }),
+            Self::Fixed(shape) => Some(shape),
+        }
+    }
+}
+
+/// Parses `1`/`0`, `true`/`false`, `yes`/`no` and `on`/`off`, ignoring case.
+#[must_use]
+pub fn parse_flag(value: &str) -> Option<bool> {
+    let value = value.trim();
+    if ["1", "true", "yes", "on"]
+        .iter()
+        .any(|enabled| value.eq_ignore_ascii_case(enabled))
+    {
+        Some(true)
+    } else if ["0", "false", "no", "off"]
+        .iter()
+        .any(|disabled| value.eq_ignore_ascii_case(disabled))
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Parses a whole number of seconds.
+#[must_use]
+pub fn parse_seconds(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Settings for the current-directory segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSettings {
+    pub color: Style,
+    pub short: bool,
+}
+
+/// Settings for the git segments and the background fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // one field per on/off setting
+pub struct GitSettings {
+    pub action_color: Style,
+    pub auth_color: Style,
+    pub auth_symbol: String,
+    pub branch_color: Style,
+    pub branch_symbol: String,
+    pub branch_symbol_color: Style,
+    pub cached_color: Style,
+    pub cached_symbol: String,
+    pub data_error_symbol: String,
+    pub diffstat: bool,
+    pub diffstat_color: Style,
+    pub diffstat_deletions_symbol: String,
+    pub diffstat_insertions_symbol: String,
+    pub diffstat_max_files: usize,
+    pub dirty_symbol: String,
+    pub dirty_threshold: usize,
+    pub fetch: bool,
+    pub fetch_timeout: Duration,
+    pub hide_uname: bool,
+    pub main_branch_color: Style,
+    pub offline_color: Style,
+    pub offline_symbol: String,
+    pub remote_ahead: String,
+    pub remote_behind: String,
+    pub remote_color: Style,
+    pub staged_color: Style,
+    pub staged_format: StatusFormat,
+    pub status_added_color: Option<Style>,
+    pub status_cache: bool,
+    pub status_color: Style,
+    pub status_conflicted_color: Option<Style>,
+    pub status_deleted_color: Option<Style>,
+    pub status_fallback: bool,
+    pub status_format: StatusFormat,
+    pub status_modified_color: Option<Style>,
+    pub status_renamed_color: Option<Style>,
+    pub status_timeout: Duration,
+    pub status_timeout_symbol: String,
+    pub status_typechange_color: Option<Style>,
+    pub status_untracked_color: Option<Style>,
+    pub uname_color: Style,
+    pub watch: bool,
+    pub watch_timeout: Duration,
+}
+
+impl GitSettings {
+    fn read(reader: &mut Reader) -> Self {
+        Self {
+            action_color: reader.color("SLICK_PROMPT_GIT_ACTION_COLOR"),
+            auth_color: reader.color("SLICK_PROMPT_GIT_AUTH_COLOR"),
+            auth_symbol: reader.text("SLICK_PROMPT_GIT_AUTH_SYMBOL"),
+            branch_color: reader.color("SLICK_PROMPT_GIT_BRANCH_COLOR"),
+            branch_symbol: reader.text("SLICK_PROMPT_GIT_BRANCH_SYMBOL"),
+            branch_symbol_color: reader.color("SLICK_PROMPT_GIT_BRANCH_SYMBOL_COLOR"),
+            cached_color: reader.color("SLICK_PROMPT_GIT_CACHED_COLOR"),
+            cached_symbol: reader.text("SLICK_PROMPT_GIT_CACHED_SYMBOL"),
+            data_error_symbol: reader.text("SLICK_PROMPT_GIT_DATA_ERROR_SYMBOL"),
+            diffstat: reader.flag("SLICK_PROMPT_GIT_DIFFSTAT"),
+            diffstat_color: reader.color("SLICK_PROMPT_GIT_DIFFSTAT_COLOR"),
+            diffstat_deletions_symbol: reader.text("SLICK_PROMPT_GIT_DIFFSTAT_DELETIONS_SYMBOL"),
+            diffstat_insertions_symbol: reader.text("SLICK_PROMPT_GIT_DIFFSTAT_INSERTIONS_SYMBOL"),
+            diffstat_max_files: reader.count("SLICK_PROMPT_GIT_DIFFSTAT_MAX_FILES"),
+            dirty_symbol: reader.text("SLICK_PROMPT_GIT_DIRTY_SYMBOL"),
+            dirty_threshold: reader.count("SLICK_PROMPT_GIT_DIRTY_THRESHOLD"),
+            fetch: reader.flag("SLICK_PROMPT_GIT_FETCH"),
+            fetch_timeout: reader.seconds("SLICK_PROMPT_GIT_FETCH_TIMEOUT"),
+            hide_uname: reader.flag("SLICK_PROMPT_NO_GIT_UNAME"),
+            main_branch_color: reader.color("SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR"),
+            offline_color: reader.color("SLICK_PROMPT_GIT_OFFLINE_COLOR"),
+            offline_symbol: reader.text("SLICK_PROMPT_GIT_OFFLINE_SYMBOL"),
+            remote_ahead: reader.text("SLICK_PROMPT_GIT_REMOTE_AHEAD"),
+            remote_behind: reader.text("SLICK_PROMPT_GIT_REMOTE_BEHIND"),
+            remote_color: reader.color("SLICK_PROMPT_GIT_REMOTE_COLOR"),
+            staged_color: reader.color("SLICK_PROMPT_GIT_STAGED_COLOR"),
+            staged_format: reader.status_format("SLICK_PROMPT_GIT_STAGED_FORMAT"),
+            status_added_color: reader.optional_color("SLICK_PROMPT_GIT_STATUS_ADDED_COLOR"),
+            status_cache: reader.flag("SLICK_PROMPT_GIT_STATUS_CACHE"),
+            status_color: reader.color("SLICK_PROMPT_GIT_STATUS_COLOR"),
+            status_conflicted_color: reader
+                .optional_color("SLICK_PROMPT_GIT_STATUS_CONFLICTED_COLOR"),
+            status_deleted_color: reader.optional_color("SLICK_PROMPT_GIT_STATUS_DELETED_COLOR"),
+            status_fallback: reader.flag("SLICK_PROMPT_GIT_STATUS_FALLBACK"),
+            status_format: reader.status_format("SLICK_PROMPT_GIT_STATUS_FORMAT"),
+            status_modified_color: reader.optional_color("SLICK_PROMPT_GIT_STATUS_MODIFIED_COLOR"),
+            status_renamed_color: reader.optional_color("SLICK_PROMPT_GIT_STATUS_RENAMED_COLOR"),
+            status_timeout: reader.seconds("SLICK_PROMPT_GIT_STATUS_TIMEOUT"),
+            status_timeout_symbol: reader.text("SLICK_PROMPT_GIT_STATUS_TIMEOUT_SYMBOL"),
+            status_typechange_color: reader
+                .optional_color("SLICK_PROMPT_GIT_STATUS_TYPECHANGE_COLOR"),
+            status_untracked_color: reader
+                .optional_color("SLICK_PROMPT_GIT_STATUS_UNTRACKED_COLOR"),
+            uname_color: reader.color("SLICK_PROMPT_GIT_UNAME_COLOR"),
+            watch: reader.flag("SLICK_PROMPT_GIT_WATCH"),
+            watch_timeout: reader.seconds("SLICK_PROMPT_GIT_WATCH_TIMEOUT"),
+        }
+    }
+
+    /// The color of a status category, if one is set; otherwise the count takes the
+    /// color of its segment.
+    #[must_use]
+    pub const fn category_color(&self, category: Category) -> Option<&Style> {
+        match category {
+            Category::Conflicted => self.status_conflicted_color.as_ref(),
+            Category::Modified => self.status_modified_color.as_ref(),
+            Category::Deleted => self.status_deleted_color.as_ref(),
+            Category::Renamed => self.status_renamed_color.as_ref(),
+            Category::Typechange => self.status_typechange_color.as_ref(),
+            Category::Added => self.status_added_color.as_ref(),
+            Category::Untracked => self.status_untracked_color.as_ref(),
+        }
+    }
+}
+
+/// Settings for the toolbox, `DevPod`, AWS, Kubernetes and Python markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSettings {
+    pub aws_color: Style,
+    pub devpod_color: Style,
+    pub devpod_symbol: String,
+    pub k8s_color: Style,
+    pub python_env_color: Style,
+    pub short: bool,
+    pub toolbox_color: Style,
+    pub toolbox_symbol: String,
+}
+
+/// Settings for powerline rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerlineSettings {
+    pub enabled: bool,
+    pub separator: String,
+    pub text_color: Style,
+}
+
+/// Settings for the transient prompt left behind in scrollback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransientSettings {
+    pub enabled: bool,
+    pub format: Layout,
+    pub short_timestamp: bool,
+}
+
+/// Every prompt setting, parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub cmd_max_exec_time: Duration,
+    pub cursor_shape: CursorShape,
+    pub error_color: Style,
+    pub format: Layout,
+    pub non_breaking_space: String,
+    pub right_format: Layout,
+    pub root_color: Style,
+    pub root_symbol: String,
+    /// Read by the zsh loader itself; parsed here so `slick config` checks it.
+    pub serve: bool,
+    pub ssh_color: Style,
+    pub symbol: String,
+    pub symbol_color: Style,
+    pub theme: &'static Theme,
+    pub time_elapsed_color: Style,
+    pub vicmd_color: Style,
+    pub vicmd_symbol: String,
+    pub path: PathSettings,
+    pub git: GitSettings,
+    pub context: ContextSettings,
+    pub powerline: PowerlineSettings,
+    pub transient: TransientSettings,
+    /// Values that were rejected, in registry order.
+    pub warnings: Vec<Warning>,
+}
+
+impl Settings {
+    /// Parses the settings from their resolved raw values.
+    ///
+    /// `lookup` returns the raw value and source of a setting by environment variable
+    /// name. Invalid values are replaced by the setting's default and reported in
+    /// [`Settings::warnings`].
+    pub fn from_values<'a>(lookup: impl Fn(&str) -> (&'a str, Source)) -> Self {
+        let mut reader = Reader {
+            lookup: &lookup,
+            warnings: Vec::new(),
+        };
+
+        let path = PathSettings {
+            color: reader.color("SLICK_PROMPT_PATH_COLOR"),
+            short: reader.flag("SLICK_PROMPT_SHORT_PATH"),
+        };
+        let git = GitSettings::read(&mut reader);
+        let context = ContextSettings {
+            aws_color: reader.color("SLICK_PROMPT_AWS_COLOR"),
+            devpod_color: reader.color("SLICK_PROMPT_DEVPOD_COLOR"),
+            devpod_symbol: reader.text("SLICK_PROMPT_DEVPOD_SYMBOL"),
+            k8s_color: reader.color("SLICK_PROMPT_K8S_COLOR"),
+            python_env_color: reader.color("SLICK_PROMPT_PYTHON_ENV_COLOR"),
+            short: reader.flag("SLICK_PROMPT_SHORT_CONTEXT"),
+            toolbox_color: reader.color("SLICK_PROMPT_TOOLBOX_COLOR"),
+            toolbox_symbol: reader.text("SLICK_PROMPT_TOOLBOX_SYMBOL"),
+        };
+        let powerline = PowerlineSettings {
+            enabled: reader.flag("SLICK_PROMPT_POWERLINE"),
+            separator: reader.text("SLICK_PROMPT_POWERLINE_SEPARATOR"),
+            text_color: reader.color("SLICK_PROMPT_POWERLINE_TEXT_COLOR"),
+        };
+        let transient = TransientSettings {
+            enabled: reader.flag("SLICK_PROMPT_TRANSIENT"),
+            format: reader.layout("SLICK_PROMPT_TRANSIENT_FORMAT"),
+            short_timestamp: reader.flag("SLICK_PROMPT_SHORT_TIMESTAMP"),
+        };
+
+        let cmd_max_exec_time = reader.seconds("SLICK_PROMPT_CMD_MAX_EXEC_TIME");
+        let cursor_shape = reader.cursor_shape("SLICK_PROMPT_CURSOR_SHAPE");
+        let error_color = reader.color("SLICK_PROMPT_ERROR_COLOR");
+        let format = reader.layout("SLICK_PROMPT_FORMAT");
+        let non_breaking_space = reader.text("SLICK_PROMPT_NON_BREAKING_SPACE");
+        let right_format = reader.layout("SLICK_PROMPT_RIGHT_FORMAT");
+        let root_color = reader.color("SLICK_PROMPT_ROOT_COLOR");
+        let root_symbol = reader.text("SLICK_PROMPT_ROOT_SYMBOL");
+        let serve = reader.flag("SLICK_PROMPT_SERVE");
+        let ssh_color = reader.color("SLICK_PROMPT_SSH_COLOR");
+        let symbol = reader.text("SLICK_PROMPT_SYMBOL");
+        let symbol_color = reader.color("SLICK_PROMPT_SYMBOL_COLOR");
+        let theme = reader.theme("SLICK_PROMPT_THEME");
+        let time_elapsed_color = reader.color("SLICK_PROMPT_TIME_ELAPSED_COLOR");
+        let vicmd_color = reader.color("SLICK_PROMPT_VICMD_COLOR");
+        let vicmd_symbol = reader.text("SLICK_PROMPT_VICMD_SYMBOL");
+
+        let mut warnings = reader.warnings;
+        warnings.sort_by_key(|warning| find_setting(&warning.subject).map(|(index, _)| index));
+
+        Self {
+            cmd_max_exec_time,
+            cursor_shape,
+            error_color,
+            format,
+            non_breaking_space,
+            right_format,
+            root_color,
+            root_symbol,
+            serve,
+            ssh_color,
+            symbol,
+            symbol_color,
+            theme,
+            time_elapsed_color,
+            vicmd_color,
+            vicmd_symbol,
+            path,
+            git,
+            context,
+            powerline,
+            transient,
+            warnings,
+        }
+    }
+}
+
+/// Parses raw values and collects a warning for each one it rejects.
+struct Reader<'l, 'a> {
+    lookup: &'l dyn Fn(&str) -> (&'a str, Source),
+    warnings: Vec<Warning>,
+}
+
+impl Reader<'_, '_> {
+    fn text(&self, name: &str) -> String {
+        (self.lookup)(name).0.to_string()
+    }
+
+    fn color(&mut self, name: &str) -> Style {
+        self.parse(
+            name,
+            Style::parse,
+            "a color (0-255, a name or #rrggbb), optionally with bold, underline, italic and on <color>",
+        )
+        .unwrap_or_default()
+    }
+
+    fn layout(&mut self, name: &str) -> Layout {
+        let placeholders: Vec<String> = SEGMENTS
+            .iter()
+            .map(|(placeholder, _)| format!("{{{placeholder}}}"))
+            .collect();
+        self.parse(
+            name,
+            Layout::parse,
+            &format!("a template using {}", placeholders.join(", ")),
+        )
+        .unwrap_or_default()
+    }
+
+    fn status_format(&mut self, name: &str) -> StatusFormat {
+        let placeholders: Vec<String> = CATEGORIES
+            .iter()
+            .map(|(placeholder, _)| format!("{{{placeholder}}}"))
+            .collect();
+        self.parse(
+            name,
+            StatusFormat::parse,
+            &format!("a template using {}", placeholders.join(", ")),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Like [`Self::color`], but an empty value is no color rather than an error.
+    fn optional_color(&mut self, name: &str) -> Option<Style> {
+        if (self.lookup)(name).0.trim().is_empty() {
+            return None;
+        }
+        self.parse(
+            name,
+            Style::parse,
+            "empty or a color (0-255, a name or #rrggbb), optionally with bold, underline, italic and on <color>",
+        )
+    }
+
+    fn theme(&mut self, name: &str) -> &'static Theme {
+        let names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+        self.parse(name, find_theme, &format!("one of {}", names.join(", ")))
+            .unwrap_or(&theme::DEFAULT)
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        self.parse(name, parse_flag, "1/0, true/false, yes/no or on/off")
+            .unwrap_or_default()
+    }
+
+    fn seconds(&mut self, name: &str) -> Duration {
+        self.parse(name, parse_seconds, "a whole number of seconds")
+            .unwrap_or_default()
+    }
+
+    fn count(&mut self, name: &str) -> usize {
+        self.parse(name, |value| value.trim().parse().ok(), "a whole number")
+            .unwrap_or_default()
+    }
+
+    /// Unlike the other settings, an invalid cursor shape disables the escape
+    /// instead of falling back to `dynamic`, as it always has.
+    fn cursor_shape(&mut self, name: &str) -> CursorShape {
+        let (value, source) = (self.lookup)(name);
+        CursorShape::parse(value).unwrap_or_else(|| {
+            self.warnings.push(Warning::invalid_value(
+                name,
+                source,
+                value,
+                "dynamic, a shape from 0 to 6, or empty",
+                "no cursor shape",
+            ));
+            CursorShape::Off
+        })
+    }
+
+    /// Parses `name`, falling back to its registered default when the value is invalid.
+    fn parse<T>(
+        &mut self,
+        name: &str,
+        parse: impl Fn(&str) -> Option<T>,
+        expected: &str,
+    ) -> Option<T> {
+        let (value, source) = (self.lookup)(name);
+        if let Some(parsed) = parse(value) {
+            return Some(parsed);
+        }
+
+        let default = find_setting(name).map_or("", |(_, setting)| setting.default);
+        self.warnings.push(Warning::invalid_value(
+            name,
+            source,
+            value,
+            expected,
+            &format!("default {default:?}"),
+        ));
+        parse(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CursorShape, Settings, parse_flag, parse_seconds};
+    use crate::config::{SETTINGS, Source, find_setting};
+    use std::{cell::RefCell, time::Duration};
+
+    fn defaults(name: &str) -> (&'static str, Source) {
+        find_setting(name).map_or(("", Source::Default), |(_, setting)| {
+            (setting.default, Source::Default)
+        })
+    }
+
+    #[test]
+    fn test_defaults_parse_without_warnings() {
+        let settings = Settings::from_values(defaults);
+
+        assert!(settings.warnings.is_empty(), "{:?}", settings.warnings);
+        assert_eq!(settings.cmd_max_exec_time, Duration::from_secs(5));
+        assert_eq!(settings.cursor_shape, CursorShape::Dynamic);
+        assert_eq!(settings.git.fetch_timeout, Duration::from_secs(5));
+        assert!(settings.git.fetch);
+        assert!(!settings.git.hide_uname);
+        assert!(settings.git.status_cache);
+        assert_eq!(settings.git.dirty_threshold, 0);
+        assert_eq!(settings.git.status_modified_color, None);
+        assert!(!settings.git.diffstat);
+        assert_eq!(settings.git.diffstat_max_files, 1000);
+        assert!(!settings.git.watch);
+        assert_eq!(settings.git.watch_timeout, Duration::from_mins(10));
+        assert!(settings.transient.enabled);
+        assert_eq!(settings.path.color.to_string(), "74");
+    }
+
+    #[test]
+    fn test_invalid_values_fall_back_with_warnings() {
+        let settings = Settings::from_values(|name| match name {
+            "SLICK_PROMPT_GIT_FETCH_TIMEOUT" => ("5s", Source::Env),
+            "SLICK_PROMPT_PATH_COLOR" => ("bleu", Source::ConfigFile),
+            "SLICK_PROMPT_SHORT_PATH" => ("maybe", Source::Env),
+            "SLICK_PROMPT_CURSOR_SHAPE" => ("9", Source::Env),
+            _ => defaults(name),
+        });
+
+        assert_eq!(settings.git.fetch_timeout, Duration::from_secs(5));
+        assert_eq!(settings.path.color.to_string(), "74");
+        assert!(!settings.path.short);
+        assert_eq!(settings.cursor_shape, CursorShape::Off);
+
+        let warned: Vec<&str> = settings
+            .warnings
+            .iter()
+            .map(|warning| warning.subject.as_str())
+            .collect();
+        assert_eq!(
+            warned,
+            [
+                "SLICK_PROMPT_CURSOR_SHAPE",
+                "SLICK_PROMPT_GIT_FETCH_TIMEOUT",
+                "SLICK_PROMPT_PATH_COLOR",
+                "SLICK_PROMPT_SHORT_PATH",
+            ]
+        );
+
+        let message = settings
+            .warnings
+            .iter()
+            .find(|warning| warning.subject == "SLICK_PROMPT_GIT_FETCH_TIMEOUT")
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        assert!(message.contains("\"5s\""), "{message}");
+        assert!(message.contains("whole number of seconds"), "{message}");
+    }
+
+    #[test]
+    fn test_every_setting_is_read() {
+        let read = RefCell::new(Vec::new());
+        let _ = Settings::from_values(|name| {
+            read.borrow_mut().push(name.to_string());
+            defaults(name)
+        });
+
+        let mut read = read.into_inner();
+        read.sort();
+        let registered: Vec<String> = SETTINGS
+            .iter()
+            .map(|setting| setting.name.to_string())
+            .collect();
+        assert_eq!(read, registered);
+    }
+
+    #[test]
+    fn test_flag_values() {
+        let cases = [
+            ("1", Some(true)),
+            ("true", Some(true)),
+            ("TRUE", Some(true)),
+            ("YeS", Some(true)),
+            ("oN", Some(true)),
+            ("0", Some(false)),
+            ("false", Some(false)),
+            ("NO", Some(false)),
+            ("Off", Some(false)),
+            ("", None),
+            ("invalid", None),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(parse_flag(value), expected, "value: {value:?}");
+        }
+    }
+
+    #[test]
+    fn test_seconds_reject_units() {
+        assert_eq!(parse_seconds("10"), Some(Duration::from_secs(10)));
+        assert_eq!(parse_seconds("5s"), None);
+        assert_eq!(parse_seconds("-1"), None);
+    }
+
+    #[test]
+    fn test_cursor_shape() {
+        assert_eq!(CursorShape::parse(""), Some(CursorShape::Off));
+        assert_eq!(CursorShape::parse("dynamic"), Some(CursorShape::Dynamic));
+        assert_eq!(CursorShape::parse("4"), Some(CursorShape::Fixed(4)));
+        assert_eq!(CursorShape::parse("9"), None);
+
+        assert_eq!(CursorShape::Dynamic.for_keymap("vicmd"), Some(2));
+        assert_eq!(CursorShape::Dynamic.for_keymap("visual"), Some(2));
+        assert_eq!(CursorShape::Dynamic.for_keymap("main"), Some(6));
+        assert_eq!(CursorShape::Fixed(4).for_keymap("vicmd"), Some(4));
+        assert_eq!(CursorShape::Off.for_keymap("main"), None);
+    }
+}
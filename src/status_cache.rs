@@ -0,0 +1,271 @@
+//! The last `git status` summary of each repository, reused while nothing changed.
+//!
+//! Pressing Enter on an empty line would otherwise scan the whole work tree again. The
+//! summary is saved under `$XDG_CACHE_HOME/slick` with a fingerprint of what the scan
+//! looked at: the `HEAD` commit, the `index` file, the exclude files, the stat data of
+//! every tracked file and of every directory a new file would show up in (creating or
+//! removing a file changes its directory), and any `.gitignore` that is not tracked.
+//! The scan that saves the summary also lists ignored entries, which name those
+//! directories, so no second walk of the work tree is needed.
+//!
+//! Checking the fingerprint takes one `lstat` per tracked file and directory, with no
+//! directory reads, content hashing or ignore rules. The `lstat` of every tracked file
+//! is also most of what a scan of a clean tree costs: on 100,000 tracked files the
+//! check took about a third less time than the scan, and half as long once the scan
+//! also had 20,000 untracked files to list.
+//!
+//! Repositories with submodules are always scanned, since a change inside a submodule
+//! leaves every stamp of the parent untouched, and so are bare repositories.
+use crate::git::{self, RepoOverrides, StatusConfig, StatusReport, StatusSettings};
+use git2::{Config, Error, Repository, Status};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    env,
+    ffi::OsStr,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// `git_index_entry` mode of a submodule.
+const GITLINK_MODE: u32 = 0o160_000;
+
+/// A summary saved for one repository.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    fingerprint: u64,
+    report: StatusReport,
+    watched: Watched,
+}
+
+/// The status of `repo`, as [`git::get_status_report`] returns it, taken from the cache
+/// when the repository has not changed since it was saved and `status.cache` is on.
+///
+/// # Errors
+///
+/// Returns an error if the scan fails.
+pub fn get_status(
+    repo: &Repository,
+    overrides: &RepoOverrides,
+    status: &StatusSettings,
+) -> Result<StatusReport, Error> {
+    let Some(path) = status
+        .cache
+        .then(|| git::cache_file(repo, "status"))
+        .flatten()
+    else {
+        return git::get_status_report(repo, overrides);
+    };
+
+    let saved = fs::read_to_string(&path)
+        .ok()
+        .and_then(|saved| serde_json::from_str::<Saved>(&saved).ok());
+    if let Some(saved) = &saved
+        && fingerprint(repo, overrides, &saved.watched)
+            .is_some_and(|(fingerprint, _)| fingerprint == saved.fingerprint)
+    {
+        return Ok(saved.report.clone());
+    }
+
+    let started = now();
+    let mut watched = Watched::default();
+    let report = git::scan_status(repo, overrides, true, |status, path| {
+        watched.add(repo, status, path);
+    })?;
+
+    // Anything stamped within a second of the scan may have changed while it ran (the
+    // scan itself rewrites a stale index), and mtimes can be that coarse, so such a
+    // result is only kept once the repository settles.
+    if let Some((fingerprint, newest)) = fingerprint(repo, overrides, &watched)
+        && newest < started - 1
+        && saved.is_none_or(|saved| saved.fingerprint != fingerprint || saved.report != report)
+        && let Ok(serialized) = serde_json::to_string(&Saved {
+            fingerprint,
+            report: report.clone(),
+            watched,
+        })
+    {
+        git::write_cache_file(&path, &serialized);
+    }
+    Ok(report)
+}
+
+/// What the status depends on besides the tracked files and their directories, relative
+/// to the work tree, gathered from the entries of a status scan.
+#[derive(Serialize, Deserialize, Default)]
+struct Watched {
+    /// Directories a new file could appear in: those holding untracked or ignored
+    /// entries, and every directory below one that holds nothing else.
+    dirs: BTreeSet<PathBuf>,
+    /// `.gitignore` files that are not tracked, which can change without touching
+    /// their directory.
+    files: BTreeSet<PathBuf>,
+}
+
+impl Watched {
+    fn add(&mut self, repo: &Repository, status: Status, path: &str) {
+        if !status.intersects(Status::WT_NEW | Status::IGNORED) {
+            return;
+        }
+        let Some(dir) = path.strip_suffix('/') else {
+            let path = Path::new(path);
+            if path.file_name() == Some(OsStr::new(".gitignore")) {
+                self.files.insert(path.to_path_buf());
+            }
+            self.dirs
+                .insert(path.parent().unwrap_or(Path::new("")).to_path_buf());
+            return;
+        };
+
+        // An untracked directory counts once, whatever else is created in it
+        let dir = Path::new(dir);
+        if !status.contains(Status::IGNORED) {
+            self.dirs.insert(dir.to_path_buf());
+        } else if repo.is_path_ignored(path).unwrap_or(true) {
+            self.dirs
+                .insert(dir.parent().unwrap_or(Path::new("")).to_path_buf());
+        } else if let Some(workdir) = repo.workdir() {
+            // Listed as ignored although no rule ignores it: it is empty or only holds
+            // ignored files, so a new file anywhere below it would be untracked
+            self.add_tree(workdir, dir);
+        }
+    }
+
+    /// Adds `dir` and every directory below it.
+    fn add_tree(&mut self, workdir: &Path, dir: &Path) {
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            if let Ok(entries) = fs::read_dir(workdir.join(&dir)) {
+                pending.extend(
+                    entries
+                        .flatten()
+                        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                        .map(|entry| dir.join(entry.file_name())),
+                );
+            }
+            self.dirs.insert(dir);
+        }
+    }
+}
+
+/// The global exclude file: `core.excludesFile`, or git's default under
+/// `$XDG_CONFIG_HOME`.
+fn excludes_file(config: Option<&Config>) -> Option<PathBuf> {
+    if let Some(path) = config.and_then(|config| config.get_path("core.excludesFile").ok()) {
+        return Some(path);
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("git").join("ignore"))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|d| i64::try_from(d.as_secs()).ok())
+        .unwrap_or(i64::MAX)
+}
+
+/// Hashes the stat data of every file and directory the status depends on. Also returns
+/// the newest modification or change time seen, in seconds. `None` means the status of
+/// `repo` can't be fingerprinted.
+fn fingerprint(
+    repo: &Repository,
+    overrides: &RepoOverrides,
+    watched: &Watched,
+) -> Option<(u64, i64)> {
+    let workdir = repo.workdir()?;
+    let mut index = repo.index().ok()?;
+    // The repository keeps the index it loaded first; pick up the one on disk
+    index.read(false).ok()?;
+
+    let mut stamps = Stamps::default();
+    repo.head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.as_bytes().to_vec())
+        .hash(&mut stamps.hasher);
+    overrides
+        .untracked_files(StatusConfig::read(repo).untracked_files)
+        .hash(&mut stamps.hasher);
+    overrides.dirty_only(index.len()).hash(&mut stamps.hasher);
+    stamps.add(&repo.path().join("index"));
+    stamps.add(&repo.path().join("info").join("exclude"));
+    let excludes = excludes_file(repo.config().ok().as_ref());
+    excludes.hash(&mut stamps.hasher);
+    if let Some(excludes) = &excludes {
+        stamps.add(excludes);
+    }
+
+    let mut dirs = BTreeSet::from([PathBuf::new()]);
+    let mut add_parents = |path: &Path| {
+        for dir in path.ancestors().skip(1) {
+            if !dirs.insert(dir.to_path_buf()) {
+                break;
+            }
+        }
+    };
+    // Entries are sorted by path, so the files of a directory follow each other
+    let mut parent = Vec::new();
+    for entry in index.iter() {
+        if entry.mode == GITLINK_MODE {
+            return None;
+        }
+        entry.path.hash(&mut stamps.hasher);
+        let path = Path::new(OsStr::from_bytes(&entry.path));
+        stamps.add(&workdir.join(path));
+        let entry_parent = path
+            .parent()
+            .map_or(&[][..], |dir| dir.as_os_str().as_bytes());
+        if entry_parent != parent.as_slice() {
+            add_parents(path);
+            parent = entry_parent.to_vec();
+        }
+    }
+    for dir in &watched.dirs {
+        add_parents(&dir.join("_"));
+    }
+    for dir in &dirs {
+        dir.hash(&mut stamps.hasher);
+        stamps.add(&workdir.join(dir));
+    }
+    for file in &watched.files {
+        file.hash(&mut stamps.hasher);
+        stamps.add(&workdir.join(file));
+    }
+
+    Some((stamps.hasher.finish(), stamps.newest))
+}
+
+#[derive(Default)]
+struct Stamps {
+    hasher: DefaultHasher,
+    newest: i64,
+}
+
+impl Stamps {
+    fn add(&mut self, path: &Path) {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            false.hash(&mut self.hasher);
+            return;
+        };
+        (
+            true,
+            metadata.ino(),
+            metadata.mode(),
+            metadata.size(),
+            metadata.mtime(),
+            metadata.mtime_nsec(),
+            metadata.ctime(),
+            metadata.ctime_nsec(),
+        )
+            .hash(&mut self.hasher);
+        self.newest = self.newest.max(metadata.mtime()).max(metadata.ctime());
+    }
+}
@@ -0,0 +1,207 @@
+//! Status count templates.
+//!
+//! `SLICK_PROMPT_GIT_STATUS_FORMAT` and `SLICK_PROMPT_GIT_STAGED_FORMAT` lay out the
+//! counts of the `{git_status}` and `{git_staged}` segments with placeholders such as
+//! `{modified}` and `{untracked}`. The text before a placeholder, back to the previous
+//! one, is its symbol: `+{added} !{modified}` shows `+1 !2` and `M {modified}` shows
+//! `M 2`. A count of zero is hidden with its symbol, and the first count shown drops
+//! the spaces before it. Text after the last placeholder follows the last count shown.
+//! `{{` and `}}` print a brace.
+use crate::git::{ChangeCounts, StatusCounts};
+
+/// A kind of change a status template can count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Conflicted,
+    Modified,
+    Deleted,
+    Renamed,
+    Typechange,
+    Added,
+    Untracked,
+}
+
+/// Every category, by placeholder name.
+pub const CATEGORIES: &[(&str, Category)] = &[
+    ("conflicted", Category::Conflicted),
+    ("modified", Category::Modified),
+    ("deleted", Category::Deleted),
+    ("renamed", Category::Renamed),
+    ("typechange", Category::Typechange),
+    ("added", Category::Added),
+    ("untracked", Category::Untracked),
+];
+
+impl Category {
+    /// Looks up a placeholder name, such as `modified`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        CATEGORIES
+            .iter()
+            .find(|(placeholder, _)| *placeholder == name)
+            .map(|(_, category)| *category)
+    }
+
+    /// The count of this category on one side of `counts`. Untracked and conflicted
+    /// files belong to neither side and are counted with the work tree.
+    #[must_use]
+    pub const fn count(self, side: &ChangeCounts, counts: &StatusCounts, staged: bool) -> u32 {
+        match self {
+            Self::Conflicted | Self::Untracked if staged => 0,
+            Self::Conflicted => counts.conflicted,
+            Self::Untracked => counts.untracked,
+            Self::Modified => side.modified,
+            Self::Deleted => side.deleted,
+            Self::Renamed => side.renamed,
+            Self::Typechange => side.typechange,
+            Self::Added => side.added,
+        }
+    }
+}
+
+/// A placeholder with the text before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+    /// The spaces separating it from the previous count.
+    space: String,
+    symbol: String,
+    category: Category,
+}
+
+/// A count shown by [`StatusFormat::fill`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Count {
+    /// Spaces to print before the symbol, empty for the first count.
+    pub space: String,
+    /// The symbol and the count, such as `M 2`.
+    pub text: String,
+    pub category: Category,
+}
+
+/// A parsed status template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusFormat {
+    fields: Vec<Field>,
+    suffix: String,
+}
+
+impl StatusFormat {
+    /// Parses a template, returning `None` for an unknown placeholder or an unbalanced brace.
+    #[must_use]
+    pub fn parse(template: &str) -> Option<Self> {
+        let mut fields = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(character) = chars.next() {
+            match character {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next()? {
+                            '}' => break,
+                            character => name.push(character),
+                        }
+                    }
+                    fields.push(Field {
+                        space: literal.chars().take_while(|c| c.is_whitespace()).collect(),
+                        symbol: literal.trim_start().to_string(),
+                        category: Category::from_name(name.trim())?,
+                    });
+                    literal.clear();
+                }
+                '}' => return None,
+                character => literal.push(character),
+            }
+        }
+
+        Some(Self {
+            fields,
+            suffix: literal,
+        })
+    }
+
+    /// The counts to show, in template order; empty when every count is zero.
+    pub fn fill(&self, count: impl Fn(Category) -> u32) -> Vec<Count> {
+        let mut shown: Vec<Count> = Vec::new();
+        for field in &self.fields {
+            let value = count(field.category);
+            if value == 0 {
+                continue;
+            }
+            shown.push(Count {
+                space: if shown.is_empty() {
+                    String::new()
+                } else {
+                    field.space.clone()
+                },
+                text: format!("{}{value}", field.symbol),
+                category: field.category,
+            });
+        }
+        if let Some(last) = shown.last_mut() {
+            last.text.push_str(&self.suffix);
+        }
+        shown
+    }
+
+    /// The counts to show as plain text, such as `M 2 ?? 3`.
+    pub fn render(&self, count: impl Fn(Category) -> u32) -> String {
+        self.fill(count)
+            .into_iter()
+            .map(|count| count.space + &count.text)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, StatusFormat};
+
+    fn render(template: &str, counts: &[(Category, u32)]) -> Option<String> {
+        StatusFormat::parse(template).map(|format| {
+            format.render(|category| {
+                counts
+                    .iter()
+                    .find(|(counted, _)| *counted == category)
+                    .map_or(0, |(_, count)| *count)
+            })
+        })
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_placeholders_and_stray_braces() {
+        assert!(StatusFormat::parse("+{added} !{ modified }").is_some());
+        assert!(StatusFormat::parse("").is_some());
+        assert!(StatusFormat::parse("{staged}").is_none());
+        assert!(StatusFormat::parse("{added").is_none());
+        assert!(StatusFormat::parse("added}").is_none());
+    }
+
+    #[test]
+    fn test_zero_counts_are_dropped_with_their_symbol() {
+        let counts = [(Category::Modified, 2), (Category::Untracked, 3)];
+
+        assert_eq!(
+            render("UU {conflicted} M {modified} ?? {untracked}", &counts).as_deref(),
+            Some("M 2 ?? 3")
+        );
+        assert_eq!(
+            render("+{added} !{modified} ?{untracked}", &counts).as_deref(),
+            Some("!2 ?3")
+        );
+        assert_eq!(render("{{{modified}}}", &counts).as_deref(), Some("{2}"));
+        assert_eq!(
+            render("+{added} ={conflicted}", &counts).as_deref(),
+            Some("")
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Style specs for the `*_COLOR` settings.
+//!
+//! A spec is a whitespace-separated list of an optional foreground color, the
+//! attributes `bold`, `underline` and `italic`, and an optional background color
+//! after `on`:
+//!
+//! ```text
+//! 74
+//! bold #ff8800 on 236
+//! underline italic cyan
+//! on red
+//! ```
+//!
+//! Colors are palette indexes (`0`-`255`), names, or `#rgb`/`#rrggbb` hex. Hex colors
+//! are sent as-is when `COLORTERM` advertises truecolor and downgraded to the nearest
+//! 256-color index otherwise.
+use crate::get_env_var;
+use std::{borrow::Cow, fmt};
+
+const COLOR_NAMES: [&str; 9] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "default",
+];
+
+/// The levels of each channel in the 6x6x6 color cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A palette index, a color name or `#rgb`/`#rrggbb`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Color(String);
+
+impl Color {
+    /// Parses a color, returning `None` for anything else.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let valid = value.parse::<u8>().is_ok()
+            || COLOR_NAMES
+                .iter()
+                .any(|name| value.eq_ignore_ascii_case(name))
+            || value.strip_prefix('#').is_some_and(|hex| {
+                matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+            });
+
+        valid.then(|| Self(value.to_ascii_lowercase()))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The palette index of a numbered or named color; `None` for hex colors and
+    /// `default`.
+    #[must_use]
+    pub fn index(&self) -> Option<u8> {
+        self.0.parse::<u8>().ok().or_else(|| {
+            COLOR_NAMES
+                .iter()
+                .take(8)
+                .zip(0u8..)
+                .find_map(|(name, index)| (*name == self.0).then_some(index))
+        })
+    }
+
+    /// The red, green and blue channels of a hex color.
+    #[must_use]
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.0.strip_prefix('#')?;
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        match hex.len() {
+            3 => {
+                let mut channels = hex.chars().map(|c| channel(&format!("{c}{c}")));
+                Some((channels.next()??, channels.next()??, channels.next()??))
+            }
+            6 => Some((
+                channel(hex.get(0..2)?)?,
+                channel(hex.get(2..4)?)?,
+                channel(hex.get(4..6)?)?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The value to put inside zsh's `%F{...}`/`%K{...}`.
+    ///
+    /// Without truecolor support, hex colors become the nearest 256-color index.
+    #[must_use]
+    pub fn render(&self, truecolor: bool) -> Cow<'_, str> {
+        match self.rgb() {
+            Some((r, g, b)) if !truecolor => Cow::Owned(nearest_ansi256(r, g, b).to_string()),
+            _ => Cow::Borrowed(&self.0),
+        }
+    }
+}
+
+impl Default for Color {
+    /// The terminal's own color.
+    fn default() -> Self {
+        Self(String::from("default"))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Returns the index and value of the cube level closest to `channel`.
+fn nearest_cube_level(channel: u8) -> (u8, u8) {
+    CUBE_LEVELS
+        .iter()
+        .zip(0u8..)
+        .min_by_key(|(level, _)| level.abs_diff(channel))
+        .map_or((0, 0), |(level, index)| (index, *level))
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .iter()
+        .map(|(a, b)| u32::from(a.abs_diff(*b)).pow(2))
+        .sum()
+}
+
+/// Maps an RGB color to the closest entry of the xterm 256-color palette,
+/// considering both the color cube (16-231) and the grayscale ramp (232-255).
+#[must_use]
+pub fn nearest_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, rl) = nearest_cube_level(r);
+    let (gi, gl) = nearest_cube_level(g);
+    let (bi, bl) = nearest_cube_level(b);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+    let cube_distance = distance((r, g, b), (rl, gl, bl));
+
+    // The ramp runs from 8 to 238 in steps of 10.
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let step = u8::try_from((average.saturating_sub(3) / 10).min(23)).unwrap_or(23);
+    let gray = 8 + 10 * step;
+    let gray_distance = distance((r, g, b), (gray, gray, gray));
+
+    if gray_distance < cube_distance {
+        232 + step
+    } else {
+        cube_index
+    }
+}
+
+/// Whether `COLORTERM` advertises 24-bit color support. It is read on every render,
+/// since `slick serve` gets it with each request.
+pub(crate) fn truecolor() -> bool {
+    let colorterm = get_env_var("COLORTERM");
+    colorterm.eq_ignore_ascii_case("truecolor") || colorterm.eq_ignore_ascii_case("24bit")
+}
+
+/// A parsed style spec.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub underline: bool,
+    pub italic: bool,
+}
+
+impl Style {
+    /// Parses a style spec, returning `None` for an empty or malformed one.
+    #[must_use]
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut style = Self::default();
+        let mut tokens = spec.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            match token.to_ascii_lowercase().as_str() {
+                "bold" => style.bold = true,
+                "underline" => style.underline = true,
+                "italic" => style.italic = true,
+                "on" if style.bg.is_none() => style.bg = Some(Color::parse(tokens.next()?)?),
+                color if style.fg.is_none() => {
+                    style.fg = Some(Color::parse(color)?);
+                }
+                _ => return None,
+            }
+        }
+
+        (style != Self::default()).then_some(style)
+    }
+}
+
+impl fmt::Display for Style {
+    /// Writes the normalized spec, e.g. `bold #ff8800 on 236`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<Cow<'_, str>> = Vec::with_capacity(5);
+        for (enabled, attribute) in [
+            (self.bold, "bold"),
+            (self.underline, "underline"),
+            (self.italic, "italic"),
+        ] {
+            if enabled {
+                parts.push(Cow::Borrowed(attribute));
+            }
+        }
+        if let Some(fg) = &self.fg {
+            parts.push(Cow::Borrowed(fg.as_str()));
+        }
+        if let Some(bg) = &self.bg {
+            parts.push(Cow::Owned(format!("on {bg}")));
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, Style, nearest_ansi256};
+
+    fn style(spec: &str) -> Style {
+        Style::parse(spec).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_color_parse() {
+        for valid in ["0", "255", "red", "Magenta", "default", "#fff", "#00aaFF"] {
+            assert!(Color::parse(valid).is_some(), "{valid:?}");
+        }
+        for invalid in ["", "256", "-1", "bleu", "#ff", "#gggggg", "1}%F{2"] {
+            assert!(Color::parse(invalid).is_none(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_style_parse() {
+        assert_eq!(
+            Style::parse("bold #FF8800 on 236"),
+            Some(Style {
+                fg: Color::parse("#ff8800"),
+                bg: Color::parse("236"),
+                bold: true,
+                ..Style::default()
+            })
+        );
+        assert_eq!(
+            Style::parse("underline italic cyan"),
+            Some(Style {
+                fg: Color::parse("cyan"),
+                underline: true,
+                italic: true,
+                ..Style::default()
+            })
+        );
+        assert_eq!(
+            Style::parse("on red"),
+            Some(Style {
+                bg: Color::parse("red"),
+                ..Style::default()
+            })
+        );
+
+        for invalid in [
+            "",
+            "   ",
+            "red blue",
+            "on",
+            "on bleu",
+            "red on",
+            "on red on blue",
+        ] {
+            assert_eq!(Style::parse(invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_style_display_is_normalized_spec() {
+        assert_eq!(
+            style("on 236 #FF8800 bold").to_string(),
+            "bold #ff8800 on 236"
+        );
+        assert_eq!(style("74").to_string(), "74");
+    }
+
+    #[test]
+    fn test_color_index() {
+        assert_eq!(style("74").fg.and_then(|color| color.index()), Some(74));
+        assert_eq!(style("cyan").fg.and_then(|color| color.index()), Some(6));
+        assert_eq!(style("default").fg.and_then(|color| color.index()), None);
+        assert_eq!(style("#fff").fg.and_then(|color| color.index()), None);
+    }
+
+    #[test]
+    fn test_nearest_ansi256() {
+        assert_eq!(nearest_ansi256(0xff, 0x88, 0x00), 208);
+        assert_eq!(nearest_ansi256(0, 0, 0), 16);
+        assert_eq!(nearest_ansi256(0xff, 0xff, 0xff), 231);
+        assert_eq!(nearest_ansi256(0x80, 0x80, 0x80), 244);
+        assert_eq!(nearest_ansi256(0x5f, 0x87, 0xaf), 67);
+        assert_eq!(
+            Color::parse("#fff").map(|color| color.render(false).into_owned()),
+            Some("231".to_string())
+        );
+    }
+}
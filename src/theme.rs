@@ -0,0 +1,173 @@
+//! Built-in theme presets.
+//!
+//! A theme is a bundle of defaults selected with `SLICK_PROMPT_THEME` (or `theme`
+//! in the config file). It sits between the config file and the built-in defaults,
+//! so any individual variable or config key still overrides it.
+
+/// A named bundle of setting defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// One line shown by `slick config` and `slick prompt --help`.
+    pub description: &'static str,
+    /// `(environment variable, value)` pairs that replace the built-in defaults.
+    pub values: &'static [(&'static str, &'static str)],
+}
+
+impl Theme {
+    /// The value this theme sets for the setting `name`, if any.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find_map(|(setting, value)| (*setting == name).then_some(*value))
+    }
+}
+
+/// The built-in defaults, unchanged.
+pub const DEFAULT: Theme = Theme {
+    name: "default",
+    description: "the built-in defaults",
+    values: &[],
+};
+
+/// Every built-in theme, `default` first.
+pub const THEMES: &[Theme] = &[
+    DEFAULT,
+    Theme {
+        name: "ascii",
+        description: "plain ASCII symbols for fonts without Nerd Font or other glyphs",
+        values: &[
+            ("SLICK_PROMPT_DEVPOD_SYMBOL", "devpod"),
+            ("SLICK_PROMPT_GIT_AUTH_SYMBOL", "[auth]"),
+            ("SLICK_PROMPT_GIT_BRANCH_SYMBOL", ""),
+            ("SLICK_PROMPT_GIT_CACHED_SYMBOL", "..."),
+            ("SLICK_PROMPT_GIT_DATA_ERROR_SYMBOL", "[data?]"),
+            ("SLICK_PROMPT_GIT_DIFFSTAT_DELETIONS_SYMBOL", "-"),
+            ("SLICK_PROMPT_GIT_DIRTY_SYMBOL", "*"),
+            ("SLICK_PROMPT_GIT_OFFLINE_SYMBOL", "[offline]"),
+            ("SLICK_PROMPT_GIT_REMOTE_AHEAD", "^"),
+            ("SLICK_PROMPT_GIT_REMOTE_BEHIND", "v"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT_SYMBOL", "..."),
+            ("SLICK_PROMPT_POWERLINE_SEPARATOR", ""),
+            ("SLICK_PROMPT_TOOLBOX_SYMBOL", "toolbox"),
+        ],
+    },
+    Theme {
+        name: "nerd",
+        description: "Nerd Font glyphs for the prompt, auth and ahead/behind symbols",
+        values: &[
+            ("SLICK_PROMPT_GIT_AUTH_SYMBOL", "\u{f023}"),
+            ("SLICK_PROMPT_GIT_REMOTE_AHEAD", "\u{f005d}"),
+            ("SLICK_PROMPT_GIT_REMOTE_BEHIND", "\u{f0045}"),
+            ("SLICK_PROMPT_ROOT_SYMBOL", "\u{f0e7}"),
+            ("SLICK_PROMPT_SYMBOL", "\u{f460}"),
+            ("SLICK_PROMPT_VICMD_SYMBOL", "\u{f47d}"),
+        ],
+    },
+    Theme {
+        name: "minimal",
+        description: "no git fetch, user name or branch symbol",
+        values: &[
+            ("SLICK_PROMPT_GIT_BRANCH_SYMBOL", ""),
+            ("SLICK_PROMPT_GIT_FETCH", "0"),
+            ("SLICK_PROMPT_NO_GIT_UNAME", "1"),
+            ("SLICK_PROMPT_SYMBOL", ">"),
+        ],
+    },
+    Theme {
+        name: "high-contrast",
+        description: "bright colors that stay readable on dark and light backgrounds",
+        values: &[
+            ("SLICK_PROMPT_AWS_COLOR", "15"),
+            ("SLICK_PROMPT_DEVPOD_COLOR", "15"),
+            ("SLICK_PROMPT_ERROR_COLOR", "196"),
+            ("SLICK_PROMPT_GIT_ACTION_COLOR", "214"),
+            ("SLICK_PROMPT_GIT_AUTH_COLOR", "196"),
+            ("SLICK_PROMPT_GIT_BRANCH_COLOR", "226"),
+            ("SLICK_PROMPT_GIT_BRANCH_SYMBOL_COLOR", "46"),
+            ("SLICK_PROMPT_GIT_CACHED_COLOR", "250"),
+            ("SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR", "196"),
+            ("SLICK_PROMPT_GIT_OFFLINE_COLOR", "214"),
+            ("SLICK_PROMPT_GIT_REMOTE_COLOR", "51"),
+            ("SLICK_PROMPT_GIT_STAGED_COLOR", "15"),
+            ("SLICK_PROMPT_GIT_STATUS_COLOR", "201"),
+            ("SLICK_PROMPT_GIT_UNAME_COLOR", "250"),
+            ("SLICK_PROMPT_K8S_COLOR", "15"),
+            ("SLICK_PROMPT_PATH_COLOR", "51"),
+            ("SLICK_PROMPT_PYTHON_ENV_COLOR", "15"),
+            ("SLICK_PROMPT_ROOT_COLOR", "196"),
+            ("SLICK_PROMPT_SSH_COLOR", "250"),
+            ("SLICK_PROMPT_SYMBOL_COLOR", "201"),
+            ("SLICK_PROMPT_TIME_ELAPSED_COLOR", "226"),
+            ("SLICK_PROMPT_TOOLBOX_COLOR", "226"),
+            ("SLICK_PROMPT_VICMD_COLOR", "226"),
+        ],
+    },
+];
+
+/// Returns the built-in theme called `name`.
+#[must_use]
+pub fn find_theme(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name == name.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use super::{DEFAULT, THEMES, find_theme};
+    use crate::{
+        config::{SETTINGS, Source, find_setting},
+        settings::Settings,
+    };
+
+    #[test]
+    fn test_themes_only_set_known_settings() {
+        for theme in THEMES {
+            for (name, _) in theme.values {
+                assert!(
+                    find_setting(name).is_some_and(|(_, setting)| setting.name == *name),
+                    "theme {} sets unknown setting {name}",
+                    theme.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_theme_values_are_valid() {
+        for theme in THEMES {
+            let settings = Settings::from_values(|name| {
+                let value = theme.get(name).unwrap_or_else(|| {
+                    SETTINGS
+                        .iter()
+                        .find(|setting| setting.name == name)
+                        .map_or("", |setting| setting.default)
+                });
+                (value, Source::Default)
+            });
+            assert!(
+                settings.warnings.is_empty(),
+                "theme {}: {:?}",
+                theme.name,
+                settings.warnings
+            );
+        }
+    }
+
+    #[test]
+    fn test_ascii_theme_is_ascii() {
+        let ascii = find_theme("ascii").expect("ascii theme exists");
+        for (name, value) in ascii.values {
+            assert!(value.is_ascii(), "{name}={value:?}");
+        }
+    }
+
+    #[test]
+    fn test_find_theme() {
+        assert_eq!(find_theme("default"), Some(&DEFAULT));
+        assert_eq!(find_theme(" nerd ").map(|theme| theme.name), Some("nerd"));
+        assert_eq!(find_theme("solarized"), None);
+    }
+}
@@ -0,0 +1,371 @@
+//! Watch mode for `slick precmd` (`SLICK_PROMPT_GIT_WATCH=1`).
+//!
+//! After its last phase, `slick precmd` stays attached to the shell and watches
+//! `HEAD`, `index`, the refs and the top of the work tree with inotify. A burst of
+//! changes is followed by one fresh `status` line, so a prompt left waiting while a
+//! commit, checkout or edit happens elsewhere redraws with the new state.
+//!
+//! The watch ends when the shell stops reading (it closes the pipe before running a
+//! command), after `SLICK_PROMPT_GIT_WATCH_TIMEOUT` seconds without a change, or
+//! straight away where inotify is not available.
+use crate::{
+    diffstat,
+    git::{self, Phase, RepoOverrides},
+    settings, status_cache,
+};
+use git2::Repository;
+use std::{
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// How long to wait after the first change of a burst before rescanning, so a
+/// `git commit` or a checkout is picked up once instead of once per file it writes.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The refresh waiting for a burst of changes to end.
+#[derive(Debug, Default)]
+struct Debounce {
+    pending: Option<Instant>,
+}
+
+impl Debounce {
+    /// Schedules a refresh [`DEBOUNCE`] after the first change of a burst.
+    fn changed(&mut self, now: Instant) {
+        self.pending.get_or_insert(now + DEBOUNCE);
+    }
+
+    /// Whether the refresh is due, clearing it if so. Checked after every wake, since
+    /// files written faster than the debounce would otherwise keep it from running.
+    fn due(&mut self, now: Instant) -> bool {
+        let due = self.pending.is_some_and(|at| now >= at);
+        if due {
+            self.pending = None;
+        }
+        due
+    }
+}
+
+/// Recomputes the local and status phases. Returns the line to print, or `None` when
+/// the repository cannot be read.
+fn refresh(git_dir: &Path, overrides: &RepoOverrides) -> Option<String> {
+    let repo = Repository::open(git_dir).ok()?;
+    let mut prompt = git::build_prompt_fast_with(&repo, overrides);
+    prompt.phase = Phase::Status;
+    prompt.set_status(
+        &status_cache::get_status(&repo, overrides, &git::StatusSettings::current()).ok()?,
+    );
+    if settings().git.diffstat {
+        prompt.diffstat = diffstat::diffstat(&repo, settings().git.diffstat_max_files)
+            .ok()
+            .flatten();
+    }
+    git::write_cached_prompt(&repo, &prompt);
+    serde_json::to_string(&prompt).ok()
+}
+
+/// Watches on a repository, set up before its status is scanned so changes made
+/// while the phases run are not missed.
+pub struct Watcher {
+    #[cfg(target_os = "linux")]
+    inotify: inotify::Watches,
+}
+
+impl Watcher {
+    /// Starts watching the repository at `git_dir`, or returns `None` where inotify
+    /// is not available.
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn new(git_dir: &Path) -> Option<Self> {
+        let repo = Repository::open(git_dir).ok()?;
+        let mut inotify = inotify::Watches::new()?;
+        inotify.add(repo.path(), inotify::Kind::GitDir);
+        if repo.commondir() != repo.path() {
+            inotify.add(repo.commondir(), inotify::Kind::GitDir);
+        }
+        inotify.add_tree(&repo.commondir().join("refs"));
+        if let Some(workdir) = repo.workdir() {
+            inotify.add(workdir, inotify::Kind::WorkTree);
+        }
+        Some(Self { inotify })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[must_use]
+    pub fn new(_git_dir: &Path) -> Option<Self> {
+        None
+    }
+
+    /// Prints a line whenever the prompt data for `git_dir` differs from `last`, the
+    /// line printed before, until stdout is closed or nothing changes for `idle`.
+    #[cfg(target_os = "linux")]
+    pub fn run(mut self, git_dir: &Path, overrides: &RepoOverrides, last: String, idle: Duration) {
+        let mut last = last;
+        let mut deadline = Instant::now() + idle;
+        let mut debounce = Debounce::default();
+        loop {
+            // A pending refresh outlasts the idle timeout
+            let wake = debounce.pending.unwrap_or(deadline);
+            match self
+                .inotify
+                .wait(wake.saturating_duration_since(Instant::now()))
+            {
+                inotify::Wake::Ignored => {}
+                inotify::Wake::Changed => debounce.changed(Instant::now()),
+                inotify::Wake::Timeout if debounce.pending.is_some() => {}
+                inotify::Wake::Timeout | inotify::Wake::Closed => return,
+            }
+
+            if debounce.due(Instant::now()) {
+                if let Some(line) = refresh(git_dir, overrides)
+                    && line != last
+                {
+                    if writeln!(io::stdout(), "{line}").is_err() || io::stdout().flush().is_err() {
+                        return;
+                    }
+                    last = line;
+                }
+                deadline = Instant::now() + idle;
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn run(self, _git_dir: &Path, _overrides: &RepoOverrides, _last: String, _idle: Duration) {}
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{
+        collections::HashMap,
+        ffi::{CString, OsStr},
+        fs::{self, File},
+        io::{self, Read},
+        os::{
+            fd::{AsRawFd, FromRawFd},
+            unix::ffi::OsStrExt,
+        },
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    /// Files in the git directory that change what the prompt shows.
+    const GIT_DIR_FILES: &[&str] = &[
+        "HEAD",
+        "index",
+        "packed-refs",
+        "MERGE_HEAD",
+        "CHERRY_PICK_HEAD",
+        "REVERT_HEAD",
+        "BISECT_LOG",
+        "rebase-merge",
+        "rebase-apply",
+    ];
+
+    const MASK: u32 = libc::IN_CLOSE_WRITE
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    /// Size of `struct inotify_event` before its name.
+    const EVENT_HEADER: usize = 16;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Kind {
+        GitDir,
+        Refs,
+        WorkTree,
+    }
+
+    impl Kind {
+        fn is_relevant(self, name: &OsStr) -> bool {
+            match self {
+                Self::GitDir => GIT_DIR_FILES.iter().any(|file| name == *file),
+                Self::Refs => !name.as_bytes().ends_with(b".lock"),
+                Self::WorkTree => name != ".git",
+            }
+        }
+    }
+
+    pub enum Wake {
+        /// A relevant file changed.
+        Changed,
+        /// Only files the prompt does not depend on changed.
+        Ignored,
+        Timeout,
+        /// Nobody reads stdout anymore.
+        Closed,
+    }
+
+    pub struct Watches {
+        file: File,
+        dirs: HashMap<i32, (PathBuf, Kind)>,
+    }
+
+    impl Watches {
+        pub fn new() -> Option<Self> {
+            // SAFETY: `inotify_init1` takes no pointers; a valid descriptor is owned below.
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return None;
+            }
+            Some(Self {
+                // SAFETY: `fd` is a fresh descriptor nothing else owns.
+                file: unsafe { File::from_raw_fd(fd) },
+                dirs: HashMap::new(),
+            })
+        }
+
+        pub fn add(&mut self, dir: &Path, kind: Kind) {
+            let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+                return;
+            };
+            // SAFETY: `path` is a NUL-terminated string that outlives the call.
+            let wd = unsafe {
+                libc::inotify_add_watch(
+                    self.file.as_raw_fd(),
+                    path.as_ptr(),
+                    MASK | libc::IN_ONLYDIR,
+                )
+            };
+            if wd >= 0 {
+                self.dirs.insert(wd, (dir.to_path_buf(), kind));
+            }
+        }
+
+        /// Watches `dir` and every directory below it as refs.
+        pub fn add_tree(&mut self, dir: &Path) {
+            let mut dirs = vec![dir.to_path_buf()];
+            while let Some(dir) = dirs.pop() {
+                self.add(&dir, Kind::Refs);
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                dirs.extend(
+                    entries
+                        .flatten()
+                        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                        .map(|entry| entry.path()),
+                );
+            }
+        }
+
+        /// Waits up to `timeout` for a relevant change or for stdout to be closed.
+        pub fn wait(&mut self, timeout: Duration) -> Wake {
+            let mut fds = [
+                libc::pollfd {
+                    fd: self.file.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                // A pipe's write end reports POLLERR once the reader has closed it
+                libc::pollfd {
+                    fd: libc::STDOUT_FILENO,
+                    events: 0,
+                    revents: 0,
+                },
+            ];
+            let millis = i32::try_from(timeout.as_millis().saturating_add(1)).unwrap_or(i32::MAX);
+            // SAFETY: `fds` is a valid array of the length passed.
+            let ready = unsafe { libc::poll(fds.as_mut_ptr(), 2, millis) };
+            let [inotify, stdout] = fds;
+            if ready < 0 {
+                return if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                    Wake::Timeout
+                } else {
+                    Wake::Closed
+                };
+            }
+            if stdout.revents & (libc::POLLERR | libc::POLLHUP) != 0 || self.dirs.is_empty() {
+                return Wake::Closed;
+            }
+            if inotify.revents & libc::POLLIN != 0 && self.read_events() {
+                return Wake::Changed;
+            }
+            if ready == 0 {
+                Wake::Timeout
+            } else {
+                Wake::Ignored
+            }
+        }
+
+        /// Reads every queued event and reports whether any of them matters.
+        fn read_events(&mut self) -> bool {
+            let mut relevant = false;
+            let mut buffer = vec![0_u8; 4096];
+            loop {
+                let Ok(length) = self.file.read(&mut buffer) else {
+                    return relevant;
+                };
+                let Some(mut events) = buffer.get(..length).filter(|events| !events.is_empty())
+                else {
+                    return relevant;
+                };
+                while let (Some(header), Some(rest)) =
+                    (events.get(..EVENT_HEADER), events.get(EVENT_HEADER..))
+                {
+                    let field = |at: usize| {
+                        header
+                            .get(at..at + 4)
+                            .and_then(|bytes| bytes.try_into().ok())
+                            .map_or(0, u32::from_ne_bytes)
+                    };
+                    let wd = i32::from_ne_bytes(field(0).to_ne_bytes());
+                    let mask = field(4);
+                    let name_length = field(12) as usize;
+                    let name = rest.get(..name_length).unwrap_or_default();
+                    events = rest.get(name_length..).unwrap_or_default();
+                    let name = name.split(|byte| *byte == 0).next().unwrap_or_default();
+                    relevant |= self.handle(wd, mask, OsStr::from_bytes(name));
+                }
+            }
+        }
+
+        fn handle(&mut self, wd: i32, mask: u32, name: &OsStr) -> bool {
+            if mask & libc::IN_IGNORED != 0 {
+                self.dirs.remove(&wd);
+                return false;
+            }
+            let Some((dir, kind)) = self.dirs.get(&wd).cloned() else {
+                return false;
+            };
+            // New directories under refs/, such as refs/heads/feature/
+            if kind == Kind::Refs
+                && mask & libc::IN_ISDIR != 0
+                && mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0
+            {
+                self.add_tree(&dir.join(name));
+            }
+            kind.is_relevant(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEBOUNCE, Debounce};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_refresh_is_due_while_changes_keep_coming() {
+        let start = Instant::now();
+        let mut debounce = Debounce::default();
+        assert!(!debounce.due(start));
+
+        let step = Duration::from_millis(10);
+        let mut now = start;
+        while now < start + DEBOUNCE {
+            debounce.changed(now);
+            assert!(!debounce.due(now));
+            now += step;
+        }
+        debounce.changed(now);
+        assert!(
+            debounce.due(now),
+            "the first change of the burst sets the time"
+        );
+        assert!(!debounce.due(now + DEBOUNCE));
+    }
+}
@@ -320,6 +320,14 @@ else
     fail "wrapper loader regression test failed"
 fi
 
+# TEST 21: slick.bash Regression Guard
+test_case "slick.bash Regression Guard"
+if bash --norc --noprofile -i -c "cd '$SCRIPT_DIR'; source tests/slick_bash_regression_test.bash" >/dev/null 2>&1; then
+    pass "slick.bash PROMPT_COMMAND/PS0 hooks preserved"
+else
+    fail "slick.bash regression test failed"
+fi
+
 # SUMMARY
 echo -e "\n${BLUE}━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━${NC}"
 echo -e "${BLUE}TEST SUMMARY${NC}"
@@ -0,0 +1,163 @@
+//! Tests for the `slick prompt --backend` output formats.
+
+#![allow(clippy::expect_used)]
+
+use serde_json::Value;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+/// Renders a prompt with a clean environment and an empty config directory.
+fn prompt(extra_args: &[&str], envs: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .arg("prompt")
+        .args(extra_args)
+        .args([
+            "-e",
+            "0",
+            "-r",
+            "0",
+            "-d",
+            r#"{"schema":2,"branch":"$(id)","counts":{"unstaged":{"modified":1}}}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("SLICK_PROMPT_SYMBOL", ">")
+        .env("SLICK_PROMPT_ROOT_SYMBOL", ">")
+        .env("SLICK_PROMPT_ROOT_COLOR", "7")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_zsh_is_the_default_backend() {
+    let default = prompt(&[], &[]);
+    assert_eq!(prompt(&["--backend", "zsh"], &[]), default);
+    assert!(default.contains("%F{3}\\$(id) %F{5}[M 1]\n"), "{default:?}");
+}
+
+#[test]
+fn test_ansi_backend_prints_escape_sequences() {
+    let stdout = prompt(
+        &["--backend", "ansi"],
+        &[("SLICK_PROMPT_FORMAT", "{git_branch} {git_status} {symbol}")],
+    );
+    assert_eq!(
+        stdout,
+        "\x1b[32m\u{e0a0} \x1b[33m$(id) \x1b[35m[M 1] \x1b[35m>\x1b[39m\u{a0}"
+    );
+}
+
+#[test]
+fn test_fish_backend_prints_ansi_with_the_vi_symbol() {
+    let envs = [("SLICK_PROMPT_FORMAT", "{git_status} {symbol}")];
+    assert_eq!(
+        prompt(&["--backend", "fish"], &envs),
+        prompt(&["--backend", "ansi"], &envs)
+    );
+
+    let vicmd = prompt(
+        &["--backend", "fish", "-k", "vicmd"],
+        &[
+            ("SLICK_PROMPT_FORMAT", "{symbol}"),
+            ("SLICK_PROMPT_CURSOR_SHAPE", "dynamic"),
+        ],
+    );
+    assert_eq!(vicmd, "\x1b[2 q\x1b[33m>\x1b[39m\u{a0}");
+}
+
+#[test]
+fn test_ansi_backend_fills_in_the_directory() {
+    let stdout = prompt(
+        &["--backend", "ansi"],
+        &[("SLICK_PROMPT_FORMAT", "{path}"), ("PWD", "/tmp")],
+    );
+    assert_eq!(stdout, "\x1b[38;5;74m/tmp");
+}
+
+#[test]
+fn test_json_backend_lists_segments_with_roles() {
+    let stdout = prompt(
+        &["--backend", "json"],
+        &[(
+            "SLICK_PROMPT_FORMAT",
+            "{git_branch} {git_status}{newline}{symbol}",
+        )],
+    );
+    let tokens: Value = serde_json::from_str(&stdout).expect("output should be JSON");
+    let tokens = tokens.as_array().expect("output should be an array");
+    let roles: Vec<&str> = tokens
+        .iter()
+        .filter_map(|token| token.get("role").and_then(Value::as_str))
+        .collect();
+    let field = |index: usize, key: &str| {
+        tokens
+            .get(index)
+            .and_then(|token| token.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    assert_eq!(
+        roles,
+        ["git_branch", "space", "git_status", "newline", "symbol"]
+    );
+    assert_eq!(field(0, "text").as_deref(), Some("\u{e0a0} $(id)"));
+    assert_eq!(field(0, "style").as_deref(), Some("3"));
+    assert_eq!(field(2, "text").as_deref(), Some("[M 1]"));
+    assert_eq!(field(4, "style").as_deref(), Some("5"));
+}
+
+/// Expands `ps1` the way bash draws it, with `promptvars` on or off.
+fn expand_with_bash(ps1: &str, promptvars: bool) -> String {
+    let output = Command::new("bash")
+        .args([
+            "--norc",
+            "--noprofile",
+            "-c",
+            r#"shopt "$SLICK_TEST_SHOPT" promptvars; PS1=$SLICK_TEST_PS1; printf '%s' "${PS1@P}""#,
+        ])
+        .env("SLICK_TEST_PS1", ps1)
+        .env("SLICK_TEST_SHOPT", if promptvars { "-s" } else { "-u" })
+        .output()
+        .expect("bash should run");
+    assert!(output.status.success(), "bash failed: {output:?}");
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_bash_backend_keeps_literal_text_literal() {
+    for promptvars in [true, false] {
+        let ps1 = prompt(
+            &["--backend", "bash"],
+            &[
+                ("SLICK_PROMPT_FORMAT", "{git_branch} `x` \\w {symbol}"),
+                (
+                    "_SLICK_PROMPT_BASH_PROMPTVARS",
+                    if promptvars { "1" } else { "0" },
+                ),
+            ],
+        );
+        assert!(ps1.starts_with("\\[\x1b[32m\\]\u{e0a0} "), "{ps1:?}");
+
+        let shown = expand_with_bash(&ps1, promptvars);
+        assert!(
+            shown.contains("\x1b[33m$(id) `x` \\w \x1b[35m>\x1b[39m"),
+            "promptvars {promptvars}: {shown:?}"
+        );
+    }
+}
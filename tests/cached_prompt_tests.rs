@@ -0,0 +1,114 @@
+//! Tests for the prompt data `slick precmd` saves and `slick prompt --cached` shows.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use slick::git::{Phase, Prompt};
+use std::{fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+fn precmd(dir: &Path, cache: &TempDir) {
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+}
+
+fn prompt(dir: &Path, cache: &TempDir, args: &[&str]) -> String {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("prompt")
+        .args(["-r", "0"])
+        .args(args)
+        .current_dir(dir)
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("SLICK_PROMPT_GIT_BRANCH_SYMBOL", "")
+        .env("SLICK_PROMPT_GIT_CACHED_SYMBOL", "CACHED")
+        .output()
+        .expect("slick prompt should run");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_cached_prompt_is_shown_until_the_first_phase_arrives() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+    let cache = TempDir::new().unwrap();
+
+    // Nothing has been saved for this repository yet
+    assert!(!prompt(dir.path(), &cache, &["--cached", "-d", ""]).contains("CACHED"));
+
+    precmd(dir.path(), &cache);
+    let cached = prompt(dir.path(), &cache, &["--cached", "-d", ""]);
+    // One space separates the marker from the branch, as between any two segments
+    assert!(cached.contains("%F{8}CACHED %F{160}"), "{cached}");
+    assert!(
+        cached.contains("master") || cached.contains("main"),
+        "{cached}"
+    );
+    assert!(cached.contains("?? 1"), "{cached}");
+
+    // Also from a subdirectory of the repository
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    assert!(prompt(&dir.path().join("sub"), &cache, &["--cached", "-d", ""]).contains("CACHED"));
+
+    // Real data replaces it, and without --cached nothing is read
+    let live = prompt(
+        dir.path(),
+        &cache,
+        &["--cached", "-d", r#"{"schema":2,"branch":"live"}"#],
+    );
+    assert!(live.contains("live"), "{live}");
+    assert!(!live.contains("CACHED"), "{live}");
+    assert!(!prompt(dir.path(), &cache, &["-d", ""]).contains("CACHED"));
+}
+
+#[test]
+fn test_cached_prompt_follows_the_latest_phase() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    let cache = TempDir::new().unwrap();
+
+    precmd(dir.path(), &cache);
+    fs::write(dir.path().join("a.txt"), "a").unwrap();
+    fs::write(dir.path().join("b.txt"), "b").unwrap();
+    precmd(dir.path(), &cache);
+
+    let path = fs::read_dir(cache.path().join("slick"))
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().contains("prompt_"))
+        .expect("the prompt data should be saved under the cache dir");
+    let (saved, error) = Prompt::decode(&fs::read_to_string(path).unwrap());
+    assert_eq!(error, None);
+    assert_eq!(saved.phase, Phase::Status);
+    assert_eq!(saved.counts.untracked, 2);
+}
+
+#[test]
+fn test_cached_prompt_is_ignored_in_disabled_repositories() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    let cache = TempDir::new().unwrap();
+    precmd(dir.path(), &cache);
+
+    repo.config()
+        .unwrap()
+        .set_bool("slick.disable", true)
+        .unwrap();
+    assert!(!prompt(dir.path(), &cache, &["--cached", "-d", ""]).contains("CACHED"));
+}
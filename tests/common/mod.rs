@@ -31,3 +31,46 @@ pub fn create_commit(repo: &Repository, message: &str) {
             .unwrap();
     }
 }
+
+/// The non-zero status counts of a `slick precmd` line, such as
+/// `staged.added=1 untracked=2`, or an empty string for a clean repository.
+pub fn counts_summary(line: &serde_json::Value) -> String {
+    fn flatten(prefix: &str, value: &serde_json::Value, out: &mut Vec<String>) {
+        if let Some(object) = value.as_object() {
+            for (key, value) in object {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&name, value, out);
+            }
+        } else if value.as_u64().is_some_and(|count| count > 0) {
+            out.push(format!("{prefix}={value}"));
+        }
+    }
+
+    let mut out = Vec::new();
+    flatten("", line.get("counts").unwrap(), &mut out);
+    out.join(" ")
+}
+
+/// The status `slick precmd` reports for the repository in `dir` with `env` set, read
+/// from its last line. It goes through the status cache, kept in a fresh directory,
+/// and never through a running daemon.
+pub fn precmd_report(dir: &std::path::Path, env: &[(&str, &str)]) -> slick::git::StatusReport {
+    let cache = TempDir::new().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    serde_json::from_str(stdout.lines().last().unwrap()).unwrap()
+}
@@ -0,0 +1,172 @@
+//! Tests for `slick config`, which prints and validates the effective configuration.
+
+#![allow(clippy::expect_used)]
+
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+fn config_home(contents: &str) -> TempDir {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let slick_dir = config_home.path().join("slick");
+    fs::create_dir(&slick_dir).expect("config dir should be created");
+    fs::write(slick_dir.join("config.toml"), contents).expect("config should be written");
+    config_home
+}
+
+fn config_command(config_home: &TempDir) -> Command {
+    let mut command = Command::new(get_slick_binary());
+    command
+        .arg("config")
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path());
+    command
+}
+
+struct Output {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn run(command: &mut Command) -> Output {
+    let output = command.output().expect("Failed to execute slick");
+    Output {
+        success: output.status.success(),
+        stdout: String::from_utf8(output.stdout).expect("stdout should be UTF-8"),
+        stderr: String::from_utf8(output.stderr).expect("stderr should be UTF-8"),
+    }
+}
+
+#[test]
+fn test_config_lists_every_setting_with_its_source() {
+    let home = config_home("[path]\ncolor = \"blue\"\n");
+
+    let output = run(config_command(&home)
+        .env("SLICK_PROMPT_SYMBOL", ">")
+        .env("SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR", "99")
+        .env("PIPENV_ACTIVE_COLOR", "88"));
+
+    assert!(output.success, "stderr: {}", output.stderr);
+    assert!(output.stderr.is_empty(), "stderr: {}", output.stderr);
+
+    let stdout = &output.stdout;
+    assert!(stdout.starts_with("# config file: "), "{stdout}");
+    for expected in [
+        "SLICK_PROMPT_AWS_COLOR=7  # default\n",
+        "SLICK_PROMPT_SYMBOL=>  # env\n",
+        "SLICK_PROMPT_PATH_COLOR=blue  # config file\n",
+        "SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR=99  # env SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR, deprecated\n",
+        "SLICK_PROMPT_PYTHON_ENV_COLOR=88  # env PIPENV_ACTIVE_COLOR, deprecated; pipenv environments only\n",
+        "SLICK_PROMPT_NON_BREAKING_SPACE=\"\\u{a0}\"  # default\n",
+        "SLICK_PROMPT_VICMD_SYMBOL=>  # default\n",
+        "SLICK_PROMPT_SERVE=0  # default\n",
+    ] {
+        assert!(
+            stdout.contains(expected),
+            "missing {expected:?} in {stdout}"
+        );
+    }
+    assert_eq!(
+        stdout.lines().count(),
+        1 + slick::config::SETTINGS.len(),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_config_exits_non_zero_on_invalid_values() {
+    let home = config_home("[git]\nfetch_timeout = \"5s\"\n");
+
+    let output = run(config_command(&home)
+        .env("SLICK_PROMPT_PATH_COLOR", "bleu")
+        .env("SLICK_PROMPT_SERVE", "maybe"));
+
+    assert!(!output.success);
+    assert!(
+        output
+            .stdout
+            .contains("SLICK_PROMPT_GIT_FETCH_TIMEOUT=5s  # config file\n"),
+        "{}",
+        output.stdout
+    );
+    assert!(
+        output.stderr.contains(
+            "warning: SLICK_PROMPT_GIT_FETCH_TIMEOUT: invalid value \"5s\" (config file)"
+        ),
+        "{}",
+        output.stderr
+    );
+    assert!(
+        output
+            .stderr
+            .contains("warning: SLICK_PROMPT_PATH_COLOR: invalid value \"bleu\" (env)"),
+        "{}",
+        output.stderr
+    );
+    assert!(
+        output
+            .stderr
+            .contains("warning: SLICK_PROMPT_SERVE: invalid value \"maybe\" (env)"),
+        "{}",
+        output.stderr
+    );
+}
+
+#[test]
+fn test_config_reports_unknown_and_malformed_config_files() {
+    let unknown = run(&mut config_command(&config_home("[git]\nfeth = false\n")));
+    assert!(!unknown.success);
+    assert!(
+        unknown.stderr.contains("unknown key git.feth"),
+        "{}",
+        unknown.stderr
+    );
+
+    let malformed = run(&mut config_command(&config_home("[git\n")));
+    assert!(!malformed.success);
+    assert!(
+        malformed.stderr.contains("config.toml: ignored: "),
+        "{}",
+        malformed.stderr
+    );
+}
+
+#[test]
+fn test_config_prints_a_single_setting() {
+    let home = config_home("");
+
+    let output = run(config_command(&home)
+        .arg("SLICK_PROMPT_GIT_FETCH_TIMEOUT")
+        .env("SLICK_PROMPT_GIT_FETCH_TIMEOUT", "10")
+        .env("SLICK_PROMPT_PATH_COLOR", "bleu"));
+
+    // Warnings about other settings do not affect the exit status.
+    assert!(output.success, "stderr: {}", output.stderr);
+    assert_eq!(output.stdout, "SLICK_PROMPT_GIT_FETCH_TIMEOUT=10  # env\n");
+
+    let unknown = run(config_command(&home).arg("SLICK_PROMPT_NOPE"));
+    assert!(!unknown.success);
+    assert!(unknown.stderr.contains("unknown setting SLICK_PROMPT_NOPE"));
+}
+
+#[test]
+fn test_prompt_help_is_generated_from_the_settings() {
+    let output = run(Command::new(get_slick_binary()).args(["prompt", "--help"]));
+
+    assert!(output.success);
+    for expected in [
+        "    SLICK_PROMPT_NO_GIT_UNAME=0 (if set to 1 hides the git user name)\n",
+        "    SLICK_PROMPT_GIT_FETCH=1 (if set to 0 disables git fetch)\n",
+        "    SLICK_PROMPT_GIT_MASTER_BRANCH_COLOR (deprecated fallback for SLICK_PROMPT_GIT_MAIN_BRANCH_COLOR)\n",
+    ] {
+        assert!(
+            output.stdout.contains(expected),
+            "missing {expected:?} in {}",
+            output.stdout
+        );
+    }
+}
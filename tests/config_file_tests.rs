@@ -0,0 +1,117 @@
+//! Tests for settings loaded from `$XDG_CONFIG_HOME/slick/config.toml`.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::panic)]
+
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+fn write_config(contents: &str) -> TempDir {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let slick_dir = config_home.path().join("slick");
+    fs::create_dir(&slick_dir).expect("config dir should be created");
+    fs::write(slick_dir.join("config.toml"), contents).expect("config should be written");
+    config_home
+}
+
+fn prompt_command(config_home: &TempDir, data: &str) -> Command {
+    let mut command = Command::new(get_slick_binary());
+    command
+        .args(["prompt", "-e", "0", "-r", "0", "-k", "main", "-d", data])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "");
+    command
+}
+
+fn stdout(command: &mut Command) -> String {
+    let output = command.output().expect("Failed to execute slick");
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_config_file_supplies_settings() {
+    let config_home = write_config(
+        r#"
+symbol = "❯"
+root_symbol = "❯"
+symbol_color = 42
+
+[path]
+color = "blue"
+
+[git]
+branch_symbol = ""
+branch_color = 99
+"#,
+    );
+
+    let prompt = stdout(&mut prompt_command(
+        &config_home,
+        r#"{"schema":2,"branch":"feature"}"#,
+    ));
+
+    assert!(prompt.contains("%F{blue}%~"), "prompt: {prompt:?}");
+    assert!(prompt.contains("%F{99}feature"), "prompt: {prompt:?}");
+    assert!(prompt.ends_with("%F{42}❯%f\u{a0}"), "prompt: {prompt:?}");
+}
+
+#[test]
+fn test_environment_overrides_config_file() {
+    let config_home =
+        write_config("symbol = \"❯\"\nroot_symbol = \"❯\"\n[path]\ncolor = \"blue\"\n");
+
+    let prompt = stdout(
+        prompt_command(&config_home, "")
+            .env("SLICK_PROMPT_SYMBOL", ">")
+            .env("SLICK_PROMPT_ROOT_SYMBOL", ">")
+            .env("SLICK_PROMPT_PATH_COLOR", "red"),
+    );
+
+    assert!(prompt.contains("%F{red}%~"), "prompt: {prompt:?}");
+    assert!(prompt.ends_with("%F{5}>%f\u{a0}"), "prompt: {prompt:?}");
+}
+
+#[test]
+fn test_malformed_config_file_falls_back_to_defaults() {
+    let config_home = write_config("symbol = \n[git\n");
+
+    let prompt = stdout(&mut prompt_command(&config_home, ""));
+
+    assert!(prompt.contains("%F{74}%~"), "prompt: {prompt:?}");
+    assert!(prompt.contains("\n%F{5}"), "prompt: {prompt:?}");
+}
+
+#[test]
+fn test_config_file_transient_table_is_honored() {
+    let config_home = write_config("[transient]\nenabled = false\nshort_timestamp = true\n");
+
+    let disabled = stdout(prompt_command(&config_home, "").args([
+        "--transient",
+        "--transient-timestamp",
+        "2026-05-09T21:47:04+02:00",
+    ]));
+    assert!(
+        disabled.contains('\n'),
+        "full prompt expected: {disabled:?}"
+    );
+    assert!(!disabled.contains("21:47:04"), "prompt: {disabled:?}");
+
+    let config_home = write_config("[transient]\nshort_timestamp = true\n");
+    let short = stdout(prompt_command(&config_home, "").args([
+        "--transient",
+        "--transient-timestamp",
+        "2026-05-09T21:47:04+02:00",
+    ]));
+    assert!(short.contains("%F{8}21:47:04 "), "prompt: {short:?}");
+    assert!(!short.contains("2026-05-09"), "prompt: {short:?}");
+}
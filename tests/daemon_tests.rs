@@ -0,0 +1,245 @@
+//! Tests for `slick daemon` and `slick precmd` asking it for repository state.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use serde_json::Value;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+/// A running daemon, killed when dropped.
+struct Daemon {
+    child: Child,
+    runtime_dir: TempDir,
+    cache_dir: TempDir,
+}
+
+impl Daemon {
+    fn start() -> Self {
+        Self::start_with(&[])
+    }
+
+    fn start_with(env: &[(&str, &str)]) -> Self {
+        let runtime_dir = TempDir::new().unwrap();
+        let cache_dir = TempDir::new().unwrap();
+        let child = Command::new(env!("CARGO_BIN_EXE_slick"))
+            .arg("daemon")
+            .env("XDG_RUNTIME_DIR", runtime_dir.path())
+            .env("SLICK_TEST_AUTH_CACHE_DIR", cache_dir.path())
+            .envs(env.iter().copied())
+            .spawn()
+            .expect("slick daemon should start");
+        let daemon = Self {
+            child,
+            runtime_dir,
+            cache_dir,
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while UnixStream::connect(daemon.socket()).is_err() {
+            assert!(Instant::now() < deadline, "slick daemon did not listen");
+            sleep(Duration::from_millis(10));
+        }
+        daemon
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.runtime_dir.path().join("slick").join("daemon.sock")
+    }
+
+    fn stats(&self) -> Value {
+        let mut stream = UnixStream::connect(self.socket()).unwrap();
+        writeln!(stream, "stats").unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn stat(&self, name: &str) -> u64 {
+        self.stats().get(name).and_then(Value::as_u64).unwrap()
+    }
+
+    /// Runs `slick precmd` in `dir` against this daemon and returns the last phase.
+    fn precmd(&self, dir: &Path, fetch: bool) -> Value {
+        self.precmd_with(dir, fetch, &[])
+    }
+
+    fn precmd_with(&self, dir: &Path, fetch: bool, env: &[(&str, &str)]) -> Value {
+        let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+            .arg("precmd")
+            .current_dir(dir)
+            .env("XDG_RUNTIME_DIR", self.runtime_dir.path())
+            .env("SLICK_TEST_AUTH_CACHE_DIR", self.cache_dir.path())
+            .env("SLICK_PROMPT_GIT_FETCH", if fetch { "1" } else { "0" })
+            .envs(env.iter().copied())
+            .output()
+            .expect("slick precmd should run");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        serde_json::from_str(stdout.lines().last().unwrap()).unwrap()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// One of the status counts of a phase, such as `untracked` or `staged/added`.
+fn count(phase: &Value, name: &str) -> u64 {
+    phase
+        .pointer(&format!("/counts/{name}"))
+        .and_then(Value::as_u64)
+        .unwrap()
+}
+
+#[test]
+fn test_daemon_shares_status_until_the_index_changes() {
+    let daemon = Daemon::start();
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+
+    assert_eq!(count(&daemon.precmd(dir.path(), false), "untracked"), 1);
+    assert_eq!(count(&daemon.precmd(dir.path(), false), "untracked"), 1);
+    assert_eq!(daemon.stat("status_computed"), 1);
+    assert_eq!(daemon.stat("status_cached"), 1);
+    assert_eq!(daemon.stat("repos"), 1);
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("new.txt")).unwrap();
+    index.write().unwrap();
+
+    let phase = daemon.precmd(dir.path(), false);
+    assert_eq!(count(&phase, "staged/added"), 1);
+    assert_eq!(count(&phase, "untracked"), 0);
+    assert_eq!(daemon.stat("status_computed"), 2);
+}
+
+#[test]
+fn test_daemon_scans_with_the_settings_of_the_asking_shell() {
+    // The daemon's own environment would put every repository in dirty-only mode
+    let daemon = Daemon::start_with(&[("SLICK_PROMPT_GIT_DIRTY_THRESHOLD", "1")]);
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("tracked.txt"), "one").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("tracked.txt")).unwrap();
+    index.write().unwrap();
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("tracked.txt"), "two").unwrap();
+
+    let counted = daemon.precmd(dir.path(), false);
+    assert_eq!(counted.get("dirty"), Some(&Value::Bool(false)));
+    assert_eq!(count(&counted, "unstaged/modified"), 1);
+
+    let dirty = daemon.precmd_with(
+        dir.path(),
+        false,
+        &[("SLICK_PROMPT_GIT_DIRTY_THRESHOLD", "1")],
+    );
+    assert_eq!(dirty.get("dirty"), Some(&Value::Bool(true)));
+    assert_eq!(count(&dirty, "unstaged/modified"), 0);
+
+    // Each set of settings keeps its own result
+    assert_eq!(
+        count(&daemon.precmd(dir.path(), false), "unstaged/modified"),
+        1
+    );
+    assert_eq!(daemon.stat("status_computed"), 2);
+    assert_eq!(daemon.stat("status_cached"), 1);
+}
+
+#[test]
+fn test_daemon_dedupes_fetches() {
+    let daemon = Daemon::start();
+    let remote_dir = TempDir::new().unwrap();
+    git2::Repository::init_bare(remote_dir.path()).unwrap();
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+
+    daemon.precmd(dir.path(), true);
+    daemon.precmd(dir.path(), true);
+
+    assert_eq!(daemon.stat("fetches"), 1);
+    assert_eq!(daemon.stat("fetches_shared"), 1);
+}
+
+#[test]
+fn test_daemon_fetches_with_the_shell_environment() {
+    let daemon = Daemon::start();
+    let remote_dir = TempDir::new().unwrap();
+    git2::Repository::init_bare(remote_dir.path()).unwrap();
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+
+    // Config passed through the environment of one shell forbids local remotes
+    let forbidden = [
+        ("GIT_CONFIG_COUNT", "1"),
+        ("GIT_CONFIG_KEY_0", "protocol.file.allow"),
+        ("GIT_CONFIG_VALUE_0", "never"),
+    ];
+    let last = daemon.precmd_with(dir.path(), true, &forbidden);
+    assert_eq!(last.get("fetch_failed"), Some(&Value::Bool(true)));
+
+    // Shells with other fetch variables do not share that result
+    let last = daemon.precmd(dir.path(), true);
+    assert_eq!(last.get("fetch_failed"), Some(&Value::Bool(false)));
+    daemon.precmd_with(dir.path(), true, &forbidden);
+    assert_eq!(daemon.stat("fetches"), 2);
+    assert_eq!(daemon.stat("fetches_shared"), 1);
+}
+
+#[test]
+fn test_second_daemon_refuses_to_replace_a_running_one() {
+    let daemon = Daemon::start();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("daemon")
+        .env("XDG_RUNTIME_DIR", daemon.runtime_dir.path())
+        .output()
+        .expect("slick daemon should run");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("already running"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(UnixStream::connect(daemon.socket()).is_ok());
+}
+
+#[test]
+fn test_precmd_works_without_a_daemon() {
+    let runtime_dir = TempDir::new().unwrap();
+    let (dir, _repo) = common::create_test_repo();
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir.path())
+        .env("XDG_RUNTIME_DIR", runtime_dir.path())
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .output()
+        .expect("slick precmd should run");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let last: Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(count(&last, "untracked"), 1);
+}
@@ -0,0 +1,204 @@
+//! Tests for the opt-in line counts of the `{git_diffstat}` segment.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use git2::Repository;
+use serde_json::Value;
+use slick::diffstat::{DiffStat, diffstat};
+use std::{fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+fn stage(repo: &Repository, path: &str) {
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+}
+
+/// A repository with a committed three-line file.
+fn committed_repo() -> (TempDir, Repository) {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+    stage(&repo, "a.txt");
+    common::create_commit(&repo, "initial");
+    (dir, repo)
+}
+
+#[test]
+fn test_diffstat_counts_staged_and_unstaged_lines_since_head() {
+    let (dir, repo) = committed_repo();
+    assert_eq!(diffstat(&repo, 0).unwrap(), Some(DiffStat::default()));
+
+    // A staged line, then an unstaged edit on top of it
+    fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+    stage(&repo, "a.txt");
+    fs::write(dir.path().join("a.txt"), "one\nTWO\nthree\nfour\n").unwrap();
+    // A new staged file; untracked files are not counted
+    fs::write(dir.path().join("b.txt"), "b\nb\n").unwrap();
+    stage(&repo, "b.txt");
+    fs::write(dir.path().join("c.txt"), "c\n").unwrap();
+
+    assert_eq!(
+        diffstat(&repo, 0).unwrap(),
+        Some(DiffStat {
+            insertions: 4,
+            deletions: 1,
+        })
+    );
+
+    // Past the file limit the lines are not counted
+    assert_eq!(diffstat(&repo, 2).unwrap().map(|d| d.insertions), Some(4));
+    assert_eq!(diffstat(&repo, 1).unwrap(), None);
+}
+
+/// Runs `slick precmd` in `dir` and returns its lines.
+fn precmd_lines(dir: &Path, env: &[(&str, &str)]) -> Vec<Value> {
+    let cache = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .envs(env.iter().copied())
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+/// Runs `slick precmd` in `dir` and returns its last line.
+fn precmd(dir: &Path, env: &[(&str, &str)]) -> Value {
+    precmd_lines(dir, env).pop().unwrap()
+}
+
+#[test]
+fn test_precmd_prints_the_diffstat_only_when_enabled() {
+    let (dir, _repo) = committed_repo();
+    fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+
+    let line = precmd(dir.path(), &[]);
+    assert_eq!(line.get("diffstat"), Some(&Value::Null));
+
+    let line = precmd(dir.path(), &[("SLICK_PROMPT_GIT_DIFFSTAT", "1")]);
+    assert_eq!(line.get("phase").and_then(Value::as_str), Some("status"));
+    assert_eq!(line.pointer("/diffstat/deletions"), Some(&Value::from(2)));
+    assert_eq!(common::counts_summary(&line), "unstaged.modified=1");
+
+    let line = precmd(
+        dir.path(),
+        &[
+            ("SLICK_PROMPT_GIT_DIFFSTAT", "1"),
+            ("SLICK_PROMPT_GIT_DIFFSTAT_MAX_FILES", "0"),
+        ],
+    );
+    assert_eq!(line.pointer("/diffstat/deletions"), Some(&Value::from(2)));
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("git should run");
+    assert!(output.status.success(), "git {args:?}: {output:?}");
+}
+
+#[test]
+fn test_post_fetch_line_does_not_wait_for_the_diffstat() {
+    let (dir, _repo) = committed_repo();
+    let remote = TempDir::new().unwrap();
+    Repository::init_bare(remote.path()).unwrap();
+    git(
+        dir.path(),
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+    git(dir.path(), &["push", "--quiet", "-u", "origin", "HEAD"]);
+
+    // Another clone pushes a commit, which the fetch then brings in
+    let other = TempDir::new().unwrap();
+    git(
+        other.path(),
+        &["clone", "--quiet", remote.path().to_str().unwrap(), "."],
+    );
+    git(
+        other.path(),
+        &[
+            "-c",
+            "user.name=Other",
+            "-c",
+            "user.email=other@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "remote",
+        ],
+    );
+    git(other.path(), &["push", "--quiet"]);
+    fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+
+    let lines = precmd_lines(
+        dir.path(),
+        &[
+            ("SLICK_PROMPT_GIT_FETCH", "1"),
+            ("SLICK_PROMPT_GIT_DIFFSTAT", "1"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT", "0"),
+            ("SLICK_TEST_DIFFSTAT_DELAY", "2"),
+        ],
+    );
+    let phases: Vec<_> = lines
+        .iter()
+        .map(|line| {
+            (
+                line.get("phase").and_then(Value::as_str).unwrap(),
+                line.get("diffstat") != Some(&Value::Null),
+            )
+        })
+        .collect();
+    assert_eq!(
+        phases,
+        [
+            ("local", false),
+            ("status", false),
+            ("post-fetch", false),
+            ("post-fetch", true)
+        ]
+    );
+}
+
+fn render(theme: &str) -> String {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args([
+            "prompt",
+            "-r",
+            "0",
+            "-d",
+            r#"{"schema":2,"branch":"main","diffstat":{"insertions":120,"deletions":34}}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("SLICK_PROMPT_THEME", theme)
+        .output()
+        .expect("slick prompt should run");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_diffstat_renders_insertions_and_deletions() {
+    let stdout = render("default");
+    assert!(stdout.contains("%F{8}+120 \u{2212}34"), "{stdout}");
+
+    let stdout = render("ascii");
+    assert!(stdout.contains("%F{8}+120 -34"), "{stdout}");
+}
@@ -0,0 +1,119 @@
+//! Tests for the dirty-only status mode (`slick.dirtyOnly`, `SLICK_PROMPT_GIT_DIRTY_THRESHOLD`).
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use git2::Repository;
+use slick::git::{self, RepoOverrides};
+use std::{fs, os::unix::fs::PermissionsExt, path::Path, process::Command};
+use tempfile::TempDir;
+
+/// Whether `repo` is dirty, checked against what `slick precmd` reports for it.
+fn dirty(repo: &Repository) -> bool {
+    let report = git::get_status_report(repo, &RepoOverrides::read(repo)).unwrap();
+    // Dirty-only mode leaves the counts at zero
+    assert!(report.counts.is_empty());
+    assert_eq!(common::precmd_report(repo.workdir().unwrap(), &[]), report);
+    report.dirty
+}
+
+fn stage(repo: &Repository, path: &str) {
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+}
+
+/// A repository with two committed files.
+fn committed_repo() -> (TempDir, Repository) {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("a.txt"), "one").unwrap();
+    fs::write(dir.path().join("b.txt"), "two").unwrap();
+    stage(&repo, "a.txt");
+    stage(&repo, "b.txt");
+    common::create_commit(&repo, "initial");
+    (dir, repo)
+}
+
+#[test]
+fn test_dirty_only_reports_tracked_changes_without_counting_them() {
+    let (dir, repo) = committed_repo();
+    repo.config()
+        .unwrap()
+        .set_bool("slick.dirtyOnly", true)
+        .unwrap();
+    assert!(!dirty(&repo));
+
+    // Untracked files are not looked for
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+    assert!(!dirty(&repo));
+
+    // A staged file
+    stage(&repo, "new.txt");
+    assert!(dirty(&repo));
+    common::create_commit(&repo, "add new.txt");
+    assert!(!dirty(&repo));
+
+    // An edit that keeps the size, and one that only touches the file
+    fs::write(dir.path().join("a.txt"), "ONE").unwrap();
+    assert!(dirty(&repo));
+    fs::write(dir.path().join("a.txt"), "one").unwrap();
+    assert!(!dirty(&repo));
+
+    // A new mode, and a deleted file
+    fs::set_permissions(dir.path().join("b.txt"), fs::Permissions::from_mode(0o755)).unwrap();
+    assert!(dirty(&repo));
+    fs::set_permissions(dir.path().join("b.txt"), fs::Permissions::from_mode(0o644)).unwrap();
+    assert!(!dirty(&repo));
+    fs::remove_file(dir.path().join("b.txt")).unwrap();
+    assert!(dirty(&repo));
+}
+
+/// Runs `slick precmd` in `dir` and returns whether it reported the repository as
+/// dirty, and how many modified files it counted.
+fn precmd_status(dir: &Path, threshold: &str) -> (bool, u32) {
+    let report = common::precmd_report(dir, &[("SLICK_PROMPT_GIT_DIRTY_THRESHOLD", threshold)]);
+    (report.dirty, report.counts.unstaged.modified)
+}
+
+#[test]
+fn test_dirty_only_is_chosen_from_the_tracked_file_count() {
+    let (dir, repo) = committed_repo();
+    fs::write(dir.path().join("a.txt"), "changed").unwrap();
+    fs::write(dir.path().join("b.txt"), "changed").unwrap();
+
+    assert_eq!(precmd_status(dir.path(), "0"), (false, 2));
+    assert_eq!(precmd_status(dir.path(), "3"), (false, 2));
+    assert_eq!(precmd_status(dir.path(), "2"), (true, 0));
+
+    // The repository can keep the counts
+    repo.config()
+        .unwrap()
+        .set_bool("slick.dirtyOnly", false)
+        .unwrap();
+    assert_eq!(precmd_status(dir.path(), "2"), (false, 2));
+}
+
+#[test]
+fn test_dirty_status_renders_the_dirty_symbol() {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args([
+            "prompt",
+            "-r",
+            "0",
+            "-d",
+            r#"{"schema":2,"branch":"main","dirty":true}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .output()
+        .expect("slick prompt should run");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("%F{5}[\u{25cf}]"), "{stdout}");
+    assert!(!stdout.contains("dirty"), "{stdout}");
+}
@@ -3,7 +3,7 @@
 
 mod common;
 
-use slick::git;
+use slick::git::{self, ChangeCounts, RepoOverrides, StatusCounts};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -16,6 +16,21 @@ fn stage_paths(repo: &git2::Repository, paths: &[&str]) {
     index.write().unwrap();
 }
 
+fn counts(repo: &git2::Repository) -> StatusCounts {
+    git::get_status_report(repo, &RepoOverrides::default())
+        .unwrap()
+        .counts
+}
+
+/// Counts with changes on the staged and the unstaged side only.
+fn sides(staged: ChangeCounts, unstaged: ChangeCounts) -> StatusCounts {
+    StatusCounts {
+        staged,
+        unstaged,
+        ..StatusCounts::default()
+    }
+}
+
 #[test]
 fn test_repo_discovery() {
     let (_dir, repo) = common::create_test_repo();
@@ -172,6 +187,11 @@ fn test_staged_rename_reports_rename() {
 #[test]
 fn test_worktree_rename_into_new_nested_directory_reports_rename() {
     let (dir, repo) = common::create_test_repo();
+    // With git's default `normal`, `new/` is one untracked entry and no rename is found
+    repo.config()
+        .unwrap()
+        .set_str("status.showUntrackedFiles", "all")
+        .unwrap();
     fs::write(
         dir.path().join("before.txt"),
         "content moved into a nested directory\n",
@@ -211,7 +231,19 @@ fn test_staged_rename_with_worktree_modification_reports_rename() {
     )
     .unwrap();
 
-    assert_eq!(git::get_status(&repo).unwrap(), "R 1");
+    assert_eq!(
+        counts(&repo),
+        sides(
+            ChangeCounts {
+                renamed: 1,
+                ..ChangeCounts::default()
+            },
+            ChangeCounts {
+                modified: 1,
+                ..ChangeCounts::default()
+            },
+        )
+    );
 }
 
 #[test]
@@ -229,7 +261,19 @@ fn test_worktree_rename_with_staged_modification_reports_rename() {
     stage_paths(&repo, &["before.txt"]);
     fs::rename(dir.path().join("before.txt"), dir.path().join("after.txt")).unwrap();
 
-    assert_eq!(git::get_status(&repo).unwrap(), "R 1");
+    assert_eq!(
+        counts(&repo),
+        sides(
+            ChangeCounts {
+                modified: 1,
+                ..ChangeCounts::default()
+            },
+            ChangeCounts {
+                renamed: 1,
+                ..ChangeCounts::default()
+            },
+        )
+    );
 }
 
 #[test]
@@ -258,6 +302,27 @@ fn test_unrelated_staged_add_and_delete_remain_distinct() {
 }
 
 #[cfg(unix)]
+/// The counts of the repository `test_get_status_is_deterministic_for_mixed_states`
+/// builds. Added and modified-modified files count on both sides.
+fn mixed_state_counts() -> StatusCounts {
+    StatusCounts {
+        staged: ChangeCounts {
+            added: 2,
+            modified: 1,
+            renamed: 1,
+            ..ChangeCounts::default()
+        },
+        unstaged: ChangeCounts {
+            modified: 3,
+            deleted: 1,
+            typechange: 1,
+            ..ChangeCounts::default()
+        },
+        untracked: 1,
+        conflicted: 1,
+    }
+}
+
 #[test]
 fn test_get_status_is_deterministic_for_mixed_states() {
     use git2::build::CheckoutBuilder;
@@ -360,8 +425,8 @@ fn test_get_status_is_deterministic_for_mixed_states() {
     )
     .unwrap();
 
-    let expected = "UU 1 AM 1 MM 1 M 1 D 1 R 1 T 1 A 1 ?? 1";
+    let expected = mixed_state_counts();
     for _ in 0..32 {
-        assert_eq!(git::get_status(&repo).unwrap(), expected);
+        assert_eq!(counts(&repo), expected);
     }
 }
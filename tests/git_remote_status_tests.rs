@@ -31,7 +31,7 @@ fn render_prompt(data: &str, envs: &[(&str, &str)]) -> String {
 
 fn prompt_data(auth_failed: bool, fetch_failed: bool) -> String {
     format!(
-        r#"{{"action":"","branch":"main","remote":[],"staged":false,"status":"","u_name":"","auth_failed":{auth_failed},"fetch_failed":{fetch_failed}}}"#
+        r#"{{"schema":2,"action":"","branch":"main","remote":[],"staged":false,"u_name":"","auth_failed":{auth_failed},"fetch_failed":{fetch_failed}}}"#
     )
 }
 
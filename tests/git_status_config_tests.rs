@@ -0,0 +1,151 @@
+//! Tests that the status counts follow git's own status settings.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use git2::Repository;
+use slick::git::{self, ChangeCounts, RepoOverrides, StatusConfig, StatusCounts, UntrackedFiles};
+use std::{fs, path::Path};
+use tempfile::TempDir;
+
+fn set(repo: &Repository, key: &str, value: &str) {
+    repo.config().unwrap().set_str(key, value).unwrap();
+}
+
+/// The counts of `repo`, checked against what `slick precmd` reports for it.
+fn status(repo: &Repository) -> StatusCounts {
+    let report = git::get_status_report(repo, &RepoOverrides::read(repo)).unwrap();
+    assert!(!report.dirty);
+    assert_eq!(common::precmd_report(repo.workdir().unwrap(), &[]), report);
+    report.counts
+}
+
+fn untracked(count: u32) -> StatusCounts {
+    StatusCounts {
+        untracked: count,
+        ..StatusCounts::default()
+    }
+}
+
+/// The counts of a submodule with changes in its work tree.
+fn modified_submodule() -> StatusCounts {
+    StatusCounts {
+        unstaged: ChangeCounts {
+            modified: 1,
+            ..ChangeCounts::default()
+        },
+        ..StatusCounts::default()
+    }
+}
+
+#[test]
+fn test_show_untracked_files_follows_git_config() {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("top.txt"), "top").unwrap();
+    fs::create_dir(dir.path().join("notes")).unwrap();
+    fs::write(dir.path().join("notes").join("a.md"), "a").unwrap();
+    fs::write(dir.path().join("notes").join("b.md"), "b").unwrap();
+
+    // git's default counts the untracked directory once
+    assert_eq!(status(&repo), untracked(2));
+
+    set(&repo, "status.showUntrackedFiles", "all");
+    assert_eq!(status(&repo), untracked(3));
+
+    set(&repo, "status.showUntrackedFiles", "no");
+    assert!(status(&repo).is_empty());
+    set(&repo, "status.showUntrackedFiles", "false");
+    assert!(status(&repo).is_empty());
+
+    // slick.showUntracked = true still lists them, in git's default mode
+    set(&repo, "slick.showUntracked", "true");
+    assert_eq!(status(&repo), untracked(2));
+}
+
+#[test]
+fn test_status_config_is_read_from_git_config() {
+    let (_dir, repo) = common::create_test_repo();
+    assert_eq!(StatusConfig::read(&repo), StatusConfig::default());
+    assert_eq!(
+        StatusConfig::read(&repo).untracked_files,
+        UntrackedFiles::Normal
+    );
+
+    set(&repo, "status.showUntrackedFiles", "ALL");
+    set(&repo, "diff.ignoreSubmodules", "dirty");
+    let config = StatusConfig::read(&repo);
+    assert_eq!(config.untracked_files, UntrackedFiles::All);
+    assert_eq!(config.ignore_submodules, Some(git2::SubmoduleIgnore::Dirty));
+
+    set(&repo, "status.showUntrackedFiles", "sometimes");
+    set(&repo, "diff.ignoreSubmodules", "some");
+    assert_eq!(StatusConfig::read(&repo), StatusConfig::default());
+}
+
+/// A repository whose `sub` submodule has a modified file and an untracked one.
+fn repo_with_dirty_submodule() -> (TempDir, TempDir, Repository) {
+    let (upstream_dir, upstream) = common::create_test_repo();
+    fs::write(upstream_dir.path().join("lib.rs"), "one").unwrap();
+    let mut index = upstream.index().unwrap();
+    index.add_path(Path::new("lib.rs")).unwrap();
+    index.write().unwrap();
+    common::create_commit(&upstream, "initial");
+
+    let (dir, repo) = common::create_test_repo();
+    let mut submodule = repo
+        .submodule(
+            upstream_dir.path().to_str().unwrap(),
+            Path::new("sub"),
+            true,
+        )
+        .unwrap();
+    submodule.clone(None).unwrap();
+    submodule.add_finalize().unwrap();
+    drop(submodule);
+    common::create_commit(&repo, "add submodule");
+    assert!(status(&repo).is_empty());
+
+    fs::write(dir.path().join("sub").join("lib.rs"), "two").unwrap();
+    fs::write(dir.path().join("sub").join("new.rs"), "new").unwrap();
+    (upstream_dir, dir, repo)
+}
+
+#[test]
+fn test_submodule_ignore_settings_follow_git_config() {
+    let (_upstream, dir, repo) = repo_with_dirty_submodule();
+    assert_eq!(status(&repo), modified_submodule());
+
+    // The submodule's own setting
+    set(&repo, "submodule.sub.ignore", "dirty");
+    assert!(status(&repo).is_empty());
+
+    // diff.ignoreSubmodules replaces it
+    set(&repo, "diff.ignoreSubmodules", "none");
+    assert_eq!(status(&repo), modified_submodule());
+    set(&repo, "diff.ignoreSubmodules", "all");
+    assert!(status(&repo).is_empty());
+
+    // Only the untracked file left
+    fs::write(dir.path().join("sub").join("lib.rs"), "one").unwrap();
+    set(&repo, "diff.ignoreSubmodules", "none");
+    assert_eq!(status(&repo), modified_submodule());
+    set(&repo, "diff.ignoreSubmodules", "untracked");
+    assert!(status(&repo).is_empty());
+
+    // .gitmodules, when git config says nothing
+    let mut config = repo.config().unwrap();
+    config.remove("diff.ignoreSubmodules").unwrap();
+    config.remove("submodule.sub.ignore").unwrap();
+    assert_eq!(status(&repo), modified_submodule());
+    let gitmodules = dir.path().join(".gitmodules");
+    let mut content = fs::read_to_string(&gitmodules).unwrap();
+    content.push_str("\tignore = untracked\n");
+    fs::write(gitmodules, content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(".gitmodules")).unwrap();
+    index.write().unwrap();
+    common::create_commit(&repo, "ignore untracked files in sub");
+    assert!(status(&repo).is_empty());
+}
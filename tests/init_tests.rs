@@ -0,0 +1,133 @@
+//! Tests for `slick init <shell>` and the loader version check.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+fn init(args: &[&str]) -> String {
+    let output = Command::new(get_slick_binary())
+        .arg("init")
+        .args(args)
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick init failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("init output should be UTF-8")
+}
+
+/// Renders a prompt the way a loader stamped with `loader_version` would.
+fn prompt(backend: &str, loader_version: &str, extra_args: &[&str]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .args(["prompt", "--backend", backend, "-r", "0", "-d", ""])
+        .args(extra_args)
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("_SLICK_PROMPT_LOADER_VERSION", loader_version)
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("prompt output should be UTF-8")
+}
+
+#[test]
+fn test_init_prints_a_stamped_loader_with_the_options_baked_in() {
+    let version = env!("CARGO_PKG_VERSION");
+
+    let zsh = init(&["zsh"]);
+    assert!(zsh.starts_with("#!/usr/bin/env zsh\n"), "{zsh}");
+    assert!(
+        zsh.contains(&format!("\nslick_prompt_loader_version={version}\n")),
+        "{zsh}"
+    );
+    assert!(zsh.contains(&format!("SLICK_PATH='{}'", get_slick_binary())));
+    assert!(!zsh.contains("export SLICK_PROMPT_TRANSIENT"), "{zsh}");
+
+    let bash = init(&["bash", "--no-transient", "--no-cursor"]);
+    assert!(
+        bash.contains("\nexport SLICK_PROMPT_TRANSIENT='0'\n"),
+        "{bash}"
+    );
+    assert!(
+        bash.contains("\nexport SLICK_PROMPT_CURSOR_SHAPE=''\n"),
+        "{bash}"
+    );
+    assert!(bash.contains("slick_prompt_precmd"), "{bash}");
+
+    let fish = init(&["fish"]);
+    assert!(
+        fish.contains(&format!("\nset -g slick_prompt_loader_version {version}\n")),
+        "{fish}"
+    );
+}
+
+#[test]
+fn test_init_rejects_unknown_shells() {
+    let output = Command::new(get_slick_binary())
+        .args(["init", "tcsh"])
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_bash_can_eval_the_init_script() {
+    let output = Command::new("bash")
+        .args([
+            "--norc",
+            "--noprofile",
+            "-i",
+            "-c",
+            r#"eval "$("$0" init bash --no-cursor)" && printf '%s|%s|%s' "$SLICK_PATH" "$slick_prompt_loader_version" "$PROMPT_COMMAND""#,
+        ])
+        .arg(get_slick_binary())
+        .env_remove("SLICK_PATH")
+        .output()
+        .expect("Failed to execute bash");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        format!(
+            "{}|{}|slick_prompt_precmd",
+            get_slick_binary(),
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+}
+
+#[test]
+fn test_prompt_reports_a_loader_from_another_version() {
+    let current = prompt("zsh", env!("CARGO_PKG_VERSION"), &[]);
+    assert!(!current.contains("integration"), "{current}");
+    assert_eq!(prompt("zsh", "", &[]), current);
+
+    let zsh = prompt("zsh", "0.0.1", &[]);
+    assert!(
+        zsh.starts_with(&format!("%F{{196}}slick {}", env!("CARGO_PKG_VERSION"))),
+        "the warning should come first: {zsh}"
+    );
+    assert!(zsh.contains("0.0.1 integration"), "{zsh}");
+    assert!(zsh.contains("slick init zsh)"), "{zsh}");
+    assert!(zsh.ends_with(&current), "the prompt should follow: {zsh}");
+
+    let fish = prompt("fish", "0.0.1", &[]);
+    assert!(fish.contains("slick init fish | source"), "{fish}");
+
+    // The right-hand and transient prompts stay quiet
+    assert!(!prompt("zsh", "0.0.1", &["--right"]).contains("integration"));
+    assert!(!prompt("zsh", "0.0.1", &["--transient"]).contains("integration"));
+}
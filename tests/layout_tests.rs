@@ -0,0 +1,131 @@
+//! Tests for the `SLICK_PROMPT_FORMAT` and `SLICK_PROMPT_TRANSIENT_FORMAT` layout templates.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+const DATA: &str =
+    r#"{"schema":2,"branch":"feature","counts":{"unstaged":{"modified":1}},"u_name":"Dev"}"#;
+
+/// Renders a prompt with a clean environment and an empty config directory.
+fn prompt(extra_args: &[&str], envs: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .arg("prompt")
+        .args(extra_args)
+        .args(["-e", "0", "-r", "0", "-k", "main", "-d", DATA])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("AWS_PROFILE", "dev")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_default_format_keeps_the_classic_layout() {
+    let stdout = prompt(&[], &[]);
+    assert!(
+        stdout.contains("%F{7}(aws dev) %F{8}Dev %F{74}%~ %F{2}\u{e0a0} %F{3}feature %F{5}[M 1]\n"),
+        "{stdout:?}"
+    );
+}
+
+#[test]
+fn test_format_moves_and_drops_segments() {
+    let stdout = prompt(
+        &[],
+        &[(
+            "SLICK_PROMPT_FORMAT",
+            "{path} {git_branch} {git_status}{newline}{context} {symbol}",
+        )],
+    );
+
+    assert!(
+        stdout.starts_with("%F{74}%~ %F{2}\u{e0a0} %F{3}feature %F{5}[M 1]\n%F{7}(aws dev) %F{5}"),
+        "{stdout:?}"
+    );
+    assert!(!stdout.contains("Dev"), "{stdout:?}");
+}
+
+#[test]
+fn test_format_literal_text_is_escaped() {
+    let stdout = prompt(
+        &[],
+        &[("SLICK_PROMPT_FORMAT", "{path} on $(id) 100% {symbol}")],
+    );
+    assert!(
+        stdout.starts_with("%F{74}%~ on \\$(id) 100%% %F{5}"),
+        "{stdout:?}"
+    );
+}
+
+#[test]
+fn test_transient_format() {
+    let stdout = prompt(
+        &["--transient", "--transient-timestamp", "12:00:00"],
+        &[(
+            "SLICK_PROMPT_TRANSIENT_FORMAT",
+            "{timestamp} {git_branch} {git_status} {symbol}",
+        )],
+    );
+
+    assert!(
+        stdout.starts_with("%F{8}12:00:00 %F{2}\u{e0a0} %F{3}feature %F{5}[M 1] %F{5}"),
+        "{stdout:?}"
+    );
+    assert!(!stdout.contains('\n'), "{stdout:?}");
+}
+
+#[test]
+fn test_invalid_format_falls_back_to_the_default() {
+    let stdout = prompt(&[], &[("SLICK_PROMPT_FORMAT", "{path} {nope}")]);
+    assert!(
+        stdout.contains("%F{8}Dev %F{74}%~ %F{2}\u{e0a0} %F{3}feature"),
+        "{stdout:?}"
+    );
+}
+
+#[test]
+fn test_right_prompt_is_empty_by_default() {
+    assert_eq!(prompt(&["--right"], &[]), "");
+}
+
+#[test]
+fn test_right_prompt_renders_its_own_layout() {
+    let stdout = prompt(
+        &["--right"],
+        &[("SLICK_PROMPT_RIGHT_FORMAT", "{context} {git_user} {clock}")],
+    );
+    assert_eq!(stdout, "%F{7}(aws dev) %F{8}Dev %F{8}%*%f");
+}
+
+#[test]
+fn test_with_right_prints_both_sides_in_one_render() {
+    let right_format = [("SLICK_PROMPT_RIGHT_FORMAT", "{git_user}")];
+    let stdout = prompt(&["--with-right"], &right_format);
+    let (left, right) = stdout
+        .split_once('\0')
+        .expect("the right prompt should follow a NUL byte");
+    assert_eq!(left, prompt(&[], &right_format));
+    assert_eq!(right, prompt(&["--right"], &right_format));
+
+    // Without a right-hand layout the right side is empty
+    assert_eq!(
+        prompt(&["--with-right"], &[]),
+        format!("{}\0", prompt(&[], &[]))
+    );
+}
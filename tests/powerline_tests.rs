@@ -0,0 +1,103 @@
+//! Tests for powerline rendering (`SLICK_PROMPT_POWERLINE`).
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+/// Renders a prompt with a clean environment and an empty config directory.
+fn prompt(extra_args: &[&str], envs: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .arg("prompt")
+        .args(extra_args)
+        .args([
+            "-e",
+            "0",
+            "-r",
+            "0",
+            "-k",
+            "main",
+            "-d",
+            r#"{"schema":2,"branch":"feature","counts":{"unstaged":{"modified":1}}}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("SLICK_PROMPT_POWERLINE", "1")
+        .env("AWS_PROFILE", "dev")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_segments_become_blocks_joined_by_separators() {
+    let stdout = prompt(&[], &[]);
+
+    assert!(
+        stdout.contains(concat!(
+            "%F{0}%K{7} (aws dev) %k",
+            "%F{7}%K{74}\u{e0b0}%k%F{0}%K{74} %~ %k",
+            "%F{74}%K{3}\u{e0b0}%k%F{0}%K{3} \u{e0a0} feature %k",
+            "%F{3}%K{5}\u{e0b0}%k%F{0}%K{5} [M 1] %k",
+            "%F{5}\u{e0b0}%f\n%F{5}",
+        )),
+        "{stdout:?}"
+    );
+}
+
+#[test]
+fn test_separator_and_text_color_are_configurable() {
+    let stdout = prompt(
+        &[],
+        &[
+            ("SLICK_PROMPT_POWERLINE_SEPARATOR", ">"),
+            ("SLICK_PROMPT_POWERLINE_TEXT_COLOR", "15"),
+            ("SLICK_PROMPT_GIT_STATUS_COLOR", "bold 0 on 226"),
+        ],
+    );
+
+    assert!(
+        stdout.contains("%F{3}%K{226}>%k%F{0}%K{226}%B [M 1] %b%k%F{226}>%f\n"),
+        "{stdout:?}"
+    );
+    assert!(stdout.contains("%F{15}%K{74} %~ %k"), "{stdout:?}");
+}
+
+#[test]
+fn test_ascii_theme_draws_flat_blocks() {
+    let stdout = prompt(&[], &[("SLICK_PROMPT_THEME", "ascii")]);
+
+    assert!(
+        stdout.contains("%F{0}%K{74} %~ %k%F{74}%K{3}%k%F{0}%K{3} feature %k"),
+        "{stdout:?}"
+    );
+    assert!(!stdout.contains('\u{e0b0}'), "{stdout:?}");
+}
+
+#[test]
+fn test_transient_prompt_uses_blocks_and_right_prompt_does_not() {
+    let transient = prompt(&["--transient"], &[]);
+    assert!(
+        transient.contains("%F{0}%K{74} %~ %k%F{74}%K{3}\u{e0b0}"),
+        "{transient:?}"
+    );
+
+    let right = prompt(
+        &["--right"],
+        &[("SLICK_PROMPT_RIGHT_FORMAT", "{git_status}")],
+    );
+    assert_eq!(right, "%F{5}[M 1]%f");
+}
@@ -0,0 +1,112 @@
+//! Tests for the versioned JSON lines `slick precmd` hands to `slick prompt`.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+
+mod common;
+
+use serde_json::Value;
+use slick::git::{DataError, PROMPT_SCHEMA, Phase, Prompt};
+use std::{fs, process::Command};
+use tempfile::TempDir;
+
+fn render_prompt(data: &str) -> String {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args(["prompt", "-r", "0", "-d", data])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .env("SLICK_PROMPT_GIT_BRANCH_SYMBOL", "")
+        .env("SLICK_PROMPT_GIT_DATA_ERROR_SYMBOL", "DATA?")
+        .output()
+        .expect("slick prompt should run");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_precmd_lines_carry_the_schema_and_phase() {
+    let (dir, _repo) = common::create_test_repo();
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+    let cache = TempDir::new().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir.path())
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+
+    let phases: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let fields: Vec<_> = phases
+        .iter()
+        .map(|phase| (phase.get("schema").cloned(), phase.get("phase").cloned()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            (Some(PROMPT_SCHEMA.into()), Some("local".into())),
+            (Some(PROMPT_SCHEMA.into()), Some("status".into())),
+        ]
+    );
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(Prompt::decode("").1, None);
+
+    let (prompt, error) = Prompt::decode(r#"{"schema":2,"phase":"post-fetch","branch":"main"}"#);
+    assert_eq!(error, None);
+    assert_eq!(prompt.phase, Phase::PostFetch);
+    assert_eq!(prompt.branch, "main");
+
+    // Lines from before the schema field match no schema.
+    let (prompt, error) = Prompt::decode(r#"{"branch":"main"}"#);
+    assert_eq!(error, Some(DataError::Schema(0)));
+    assert_eq!(prompt.branch, "main");
+    assert_eq!(Prompt::default().schema, PROMPT_SCHEMA);
+
+    let (prompt, error) = Prompt::decode(r#"{"schema":99,"branch":"main"}"#);
+    assert_eq!(error, Some(DataError::Schema(99)));
+    assert_eq!(prompt.branch, "main");
+
+    let (prompt, error) = Prompt::decode(r#"{"schema":2,"branch":"ma"#);
+    assert_eq!(error, Some(DataError::Malformed));
+    assert_eq!(prompt.branch, "");
+
+    let line = serde_json::to_string(&Prompt::default()).unwrap();
+    assert_eq!(Prompt::decode(&line).1, None);
+}
+
+#[test]
+fn test_unreadable_data_is_marked_in_the_git_segment() {
+    let good = render_prompt(r#"{"schema":2,"branch":"main"}"#);
+    assert!(good.contains("main"), "{good}");
+    assert!(!good.contains("DATA?"), "{good}");
+
+    let truncated = render_prompt(r#"{"schema":2,"branch":"ma"#);
+    assert!(truncated.contains("%F{196}DATA?"), "{truncated}");
+
+    // One space separates the marker from the branch, as between any two segments
+    let other_schema = render_prompt(r#"{"schema":1,"branch":"main"}"#);
+    assert!(
+        other_schema.contains("%F{196}DATA? %F{160}main"),
+        "{other_schema}"
+    );
+
+    let unversioned = render_prompt(r#"{"branch":"main"}"#);
+    assert!(
+        unversioned.contains("%F{196}DATA? %F{160}main"),
+        "{unversioned}"
+    );
+
+    assert!(!render_prompt("").contains("DATA?"));
+}
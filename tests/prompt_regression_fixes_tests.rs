@@ -3,6 +3,8 @@
 #![allow(clippy::expect_used)]
 #![allow(clippy::panic)]
 
+mod common;
+
 use std::{
     fs,
     path::Path,
@@ -61,9 +63,28 @@ fn render_with_zsh(prompt: &str, mode: &str) -> String {
     String::from_utf8(output.stdout).expect("zsh output should be UTF-8")
 }
 
+/// Runs `slick precmd` in `dir` and returns its first line, the data a prompt renders.
+fn precmd_data(dir: &Path, no_git_uname: Option<&str>) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_slick"));
+    command
+        .arg("precmd")
+        .current_dir(dir)
+        .env_clear()
+        .env("HOME", dir);
+    if let Some(value) = no_git_uname {
+        command.env("SLICK_PROMPT_NO_GIT_UNAME", value);
+    }
+    let stdout = successful_stdout(&mut command);
+    stdout
+        .lines()
+        .next()
+        .expect("slick precmd should print its first phase")
+        .to_string()
+}
+
 #[test]
 fn test_no_git_uname_case_insensitive_boolean_values() {
-    let data = serde_json::json!({ "u_name": "visible-git-user" }).to_string();
+    let (dir, _repo) = common::create_test_repo();
     let cases = [
         (None, true),
         (Some("0"), true),
@@ -80,6 +101,7 @@ fn test_no_git_uname_case_insensitive_boolean_values() {
     ];
 
     for (value, should_show) in cases {
+        let data = precmd_data(dir.path(), value);
         let mut command = prompt_command(&data, "main", "0");
         if let Some(value) = value {
             command.env("SLICK_PROMPT_NO_GIT_UNAME", value);
@@ -87,7 +109,7 @@ fn test_no_git_uname_case_insensitive_boolean_values() {
 
         let stdout = successful_stdout(&mut command);
         assert_eq!(
-            stdout.contains("visible-git-user"),
+            stdout.contains("Test User"),
             should_show,
             "SLICK_PROMPT_NO_GIT_UNAME={value:?}"
         );
@@ -141,6 +163,7 @@ fn test_untrusted_prompt_text_renders_literally_in_both_prompt_modes() {
     fs::create_dir_all(&workdir).expect("work directory should be created");
 
     let data = serde_json::json!({
+        "schema": 2,
         "branch": branch,
         "u_name": git_user,
     })
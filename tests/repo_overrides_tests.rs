@@ -0,0 +1,195 @@
+//! Tests for per-repository `slick.*` overrides in git config.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+#![allow(clippy::indexing_slicing)]
+
+mod common;
+
+use serde_json::Value;
+use slick::git::{self, RepoOverrides};
+use std::{fs, path::Path, process::Command, time::Duration};
+use tempfile::TempDir;
+
+fn set(repo: &git2::Repository, key: &str, value: &str) {
+    repo.config().unwrap().set_str(key, value).unwrap();
+}
+
+/// Runs `slick precmd` in `dir` and returns every emitted phase.
+/// Whether a background fetch recorded its outcome in `cache_dir`.
+fn fetch_recorded(cache_dir: &Path) -> bool {
+    fs::read_dir(cache_dir.join("slick")).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("auth_"))
+    })
+}
+
+fn precmd_phases(dir: &Path, cache_dir: &Path) -> Vec<Value> {
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "1")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache_dir)
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).unwrap_or_else(|error| panic!("bad JSON {line}: {error}"))
+        })
+        .collect()
+}
+
+#[test]
+fn test_overrides_are_read_from_git_config() {
+    let (_dir, repo) = common::create_test_repo();
+    assert_eq!(RepoOverrides::read(&repo), RepoOverrides::default());
+
+    set(&repo, "slick.disable", "yes");
+    set(&repo, "slick.fetch", "false");
+    set(&repo, "slick.fetchTimeout", "30");
+    set(&repo, "slick.showUntracked", "off");
+    set(&repo, "slick.hideUserName", "true");
+    set(&repo, "slick.branchColor", "#ff8800");
+
+    let overrides = RepoOverrides::read(&repo);
+    assert!(overrides.disable);
+    assert!(!overrides.fetch());
+    assert_eq!(overrides.fetch_timeout(), Duration::from_secs(30));
+    assert!(!overrides.show_untracked());
+    assert!(overrides.hide_user_name());
+    assert_eq!(
+        overrides.branch_color.as_ref().map(ToString::to_string),
+        Some("#ff8800".to_string())
+    );
+}
+
+#[test]
+fn test_invalid_overrides_are_ignored() {
+    let (_dir, repo) = common::create_test_repo();
+    set(&repo, "slick.fetch", "sometimes");
+    set(&repo, "slick.fetchTimeout", "5s");
+    set(&repo, "slick.branchColor", "bleu");
+
+    assert_eq!(RepoOverrides::read(&repo), RepoOverrides::default());
+}
+
+#[test]
+fn test_show_untracked_override_skips_untracked_files() {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+
+    assert_eq!(git::get_status(&repo).unwrap(), "?? 1");
+
+    set(&repo, "slick.showUntracked", "false");
+    let overrides = RepoOverrides::read(&repo);
+    let report = git::get_status_report(&repo, &overrides).unwrap();
+    assert!(report.counts.is_empty());
+}
+
+#[test]
+fn test_fast_prompt_applies_user_name_and_branch_color_overrides() {
+    let (_dir, repo) = common::create_test_repo();
+
+    let prompt = git::build_prompt_fast(&repo);
+    assert_eq!(prompt.u_name, "Test User");
+    assert_eq!(prompt.branch_color, "");
+
+    set(&repo, "slick.hideUserName", "true");
+    set(&repo, "slick.branchColor", "42");
+    let prompt = git::build_prompt_fast(&repo);
+    assert_eq!(prompt.u_name, "");
+    assert_eq!(prompt.branch_color, "42");
+}
+
+#[test]
+fn test_repository_shows_user_name_hidden_by_the_environment() {
+    let (dir, repo) = common::create_test_repo();
+    set(&repo, "slick.hideUserName", "false");
+
+    let precmd = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir.path())
+        .env("SLICK_PROMPT_NO_GIT_UNAME", "1")
+        .output()
+        .expect("slick precmd should run");
+    assert!(precmd.status.success());
+    let stdout = String::from_utf8_lossy(&precmd.stdout);
+    let data = stdout
+        .lines()
+        .next()
+        .expect("slick precmd should print data");
+    let phase: Value = serde_json::from_str(data).unwrap();
+    assert_eq!(phase["u_name"], "Test User");
+
+    let prompt = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args(["prompt", "-e", "0", "-r", "0", "-k", "main", "-d", data])
+        .env("SLICK_PROMPT_NO_GIT_UNAME", "1")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .output()
+        .expect("slick prompt should run");
+    assert!(prompt.status.success());
+    let rendered = String::from_utf8_lossy(&prompt.stdout);
+    assert!(rendered.contains("Test User"), "prompt: {rendered:?}");
+}
+
+#[test]
+fn test_precmd_honors_disable() {
+    let (dir, repo) = common::create_test_repo();
+    set(&repo, "slick.disable", "true");
+    let cache = TempDir::new().unwrap();
+
+    let phases = precmd_phases(dir.path(), cache.path());
+
+    assert_eq!(phases.len(), 1, "{phases:?}");
+    assert_eq!(phases[0]["branch"], "");
+    assert_eq!(phases[0]["u_name"], "");
+}
+
+#[test]
+fn test_precmd_honors_fetch_override() {
+    let (dir, repo) = common::create_test_repo();
+    let cache = TempDir::new().unwrap();
+
+    set(&repo, "slick.fetch", "false");
+    precmd_phases(dir.path(), cache.path());
+    assert!(
+        !fetch_recorded(cache.path()),
+        "slick.fetch=false must skip the background fetch"
+    );
+
+    set(&repo, "slick.fetch", "true");
+    precmd_phases(dir.path(), cache.path());
+    assert!(
+        fetch_recorded(cache.path()),
+        "the fetch should run and record its outcome"
+    );
+}
+
+#[test]
+fn test_prompt_renders_repository_branch_color() {
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args([
+            "prompt",
+            "-e",
+            "0",
+            "-r",
+            "0",
+            "-k",
+            "main",
+            "-d",
+            r#"{"schema":2,"branch":"main","branch_color":"42"}"#,
+        ])
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("%F{42}main"), "prompt: {stdout:?}");
+}
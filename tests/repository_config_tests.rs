@@ -2,6 +2,8 @@ const CARGO_MANIFEST: &str = include_str!("../Cargo.toml");
 const CARGO_LOCK: &str = include_str!("../Cargo.lock");
 const LOAD_ZSH: &str = include_str!("../load.zsh");
 const README: &str = include_str!("../README.md");
+const SLICK_BASH: &str = include_str!("../slick.bash");
+const SLICK_FISH: &str = include_str!("../slick.fish");
 const SLICK_ZSH: &str = include_str!("../slick.zsh");
 const TEST_INTERACTIVE_ZSH: &str = include_str!("../test_interactive.zsh");
 const TEST_WORKFLOW: &str = include_str!("../.github/workflows/test.yml");
@@ -67,6 +69,8 @@ fn test_zsh_entrypoints_use_portable_shebangs() {
     for script in [SLICK_ZSH, LOAD_ZSH, TEST_INTERACTIVE_ZSH] {
         assert!(script.starts_with("#!/usr/bin/env zsh\n"));
     }
+    assert!(SLICK_BASH.starts_with("#!/usr/bin/env bash\n"));
+    assert!(SLICK_FISH.starts_with("#!/usr/bin/env fish\n"));
 }
 
 #[test]
@@ -85,6 +89,9 @@ fn test_platform_cargo_test_jobs_run_zsh_regressions() {
     assert!(matrix.contains("run: cargo build --release --locked"));
     assert!(matrix.contains(r#"zsh -dfi -c "source tests/slick_zsh_regression_test.zsh""#));
     assert!(matrix.contains(r#"zsh -dfi -c "source tests/load_zsh_regression_test.zsh""#));
+    assert!(matrix.contains(
+        r#"bash --norc --noprofile -i -c "source tests/slick_bash_regression_test.bash""#
+    ));
 
     let before_coverage_test = COVERAGE_WORKFLOW
         .split_once("run: cargo test")
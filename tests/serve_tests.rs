@@ -0,0 +1,191 @@
+//! Tests for the `slick serve` line protocol.
+
+#![allow(clippy::expect_used)]
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    process::{Command, Stdio},
+};
+use tempfile::TempDir;
+
+const ARGS: [&str; 6] = [
+    "-r",
+    "1",
+    "-d",
+    r#"{"schema":2,"branch":"main"}"#,
+    "-k",
+    "vicmd",
+];
+
+fn slick(config_home: &TempDir) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_slick"));
+    command
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "dynamic");
+    command
+}
+
+/// Reads one NUL-terminated answer.
+fn answer(reader: &mut impl BufRead) -> String {
+    let mut answer = Vec::new();
+    reader
+        .read_until(0, &mut answer)
+        .expect("slick serve should answer");
+    assert_eq!(answer.pop(), Some(0), "answers end with a NUL byte");
+    String::from_utf8(answer).expect("answers should be UTF-8")
+}
+
+#[test]
+fn test_serve_answers_requests_like_slick_prompt() {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let expected = slick(&config_home)
+        .arg("prompt")
+        .args(ARGS)
+        .output()
+        .expect("slick prompt should run");
+
+    let mut child = slick(&config_home)
+        .arg("serve")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("slick serve should start");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+
+    let greeting = answer(&mut stdout);
+    assert!(greeting.split(' ').any(|name| name == "PWD"), "{greeting}");
+    assert!(
+        greeting.split(' ').any(|name| name == "VIRTUAL_ENV"),
+        "{greeting}"
+    );
+
+    let mut request = String::new();
+    for arg in ARGS {
+        request.push_str("arg ");
+        request.push_str(arg);
+        request.push('\n');
+    }
+    request.push('\n');
+    stdin
+        .write_all(request.as_bytes())
+        .expect("request should be sent");
+    assert_eq!(
+        answer(&mut stdout),
+        String::from_utf8_lossy(&expected.stdout)
+    );
+
+    // Environment lines apply to this and later requests
+    stdin
+        .write_all(b"arg --backend\narg ansi\nenv PWD=/tmp\nenv VIRTUAL_ENV=/srv/venv\n\n")
+        .expect("request should be sent");
+    let ansi = answer(&mut stdout);
+    assert!(ansi.contains("/tmp"), "{ansi:?}");
+    assert!(ansi.contains("venv"), "{ansi:?}");
+
+    stdin
+        .write_all(b"arg --backend\narg ansi\nunset VIRTUAL_ENV\n\n")
+        .expect("request should be sent");
+    assert!(!answer(&mut stdout).contains("venv"));
+
+    // Both sides come back as two NUL-terminated parts; the default right side is empty
+    stdin
+        .write_all(b"arg --with-right\narg --backend\narg ansi\n\n")
+        .expect("request should be sent");
+    assert!(answer(&mut stdout).contains("/tmp"));
+    assert_eq!(answer(&mut stdout), "");
+
+    // Arguments `slick prompt` rejects get an empty answer
+    stdin
+        .write_all(b"arg --no-such-flag\n\n")
+        .expect("request should be sent");
+    assert_eq!(answer(&mut stdout), "");
+
+    drop(stdin);
+    let status = child.wait().expect("slick serve should exit");
+    assert!(
+        status.success(),
+        "slick serve should exit when stdin closes"
+    );
+}
+
+#[test]
+fn test_serve_matches_slick_prompt_for_the_shell_environment() {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let home = TempDir::new().expect("tempdir should be created");
+    let work = home.path().join("work");
+    fs::create_dir(&work).expect("work directory should be created");
+    let toolboxenv = home.path().join(".toolboxenv");
+    let containerenv = home.path().join(".containerenv");
+    fs::write(&toolboxenv, "").expect("toolboxenv should be written");
+    fs::write(&containerenv, "name=\"dev\"\n").expect("containerenv should be written");
+
+    // What the shell has exported by the time of the request, none of which the
+    // server saw when it started
+    let shell_env = [
+        ("PWD", work.to_str().expect("path is UTF-8")),
+        ("HOME", home.path().to_str().expect("path is UTF-8")),
+        ("USER", "dev"),
+        ("SSH_CONNECTION", "10.0.0.1 50000 10.0.0.2 22"),
+        ("COLORTERM", "truecolor"),
+        (
+            "SLICK_TEST_TOOLBOXENV_PATH",
+            toolboxenv.to_str().expect("path is UTF-8"),
+        ),
+        (
+            "SLICK_TEST_CONTAINERENV_PATH",
+            containerenv.to_str().expect("path is UTF-8"),
+        ),
+    ];
+
+    let mut child = slick(&config_home)
+        .arg("serve")
+        .env("SLICK_PROMPT_PATH_COLOR", "#ff8800")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("slick serve should start");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let greeting = answer(&mut stdout);
+
+    for backend in ["zsh", "ansi"] {
+        let expected = slick(&config_home)
+            .env("SLICK_PROMPT_PATH_COLOR", "#ff8800")
+            .envs(shell_env)
+            .current_dir(&work)
+            .args(["prompt", "--backend", backend])
+            .args(ARGS)
+            .output()
+            .expect("slick prompt should run");
+        let expected = String::from_utf8_lossy(&expected.stdout);
+        assert!(expected.contains("dev)"), "{expected}");
+        assert!(expected.contains('@'), "{expected}");
+
+        // The request carries what the zsh integration sends: every listed variable
+        let mut lines: Vec<String> = ["--backend", backend]
+            .iter()
+            .chain(&ARGS)
+            .map(|arg| format!("arg {arg}"))
+            .collect();
+        for name in greeting.split(' ') {
+            lines.push(
+                match shell_env.iter().find(|(exported, _)| *exported == name) {
+                    Some((_, value)) => format!("env {name}={value}"),
+                    None => format!("unset {name}"),
+                },
+            );
+        }
+        let request = lines.join("\n") + "\n\n";
+        stdin
+            .write_all(request.as_bytes())
+            .expect("request should be sent");
+        assert_eq!(answer(&mut stdout), expected, "{backend}");
+    }
+
+    drop(stdin);
+    child.wait().expect("slick serve should exit");
+}
@@ -0,0 +1,119 @@
+#!/usr/bin/env bash
+
+set -eo pipefail
+
+ROOT_DIR=$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)
+cd "$ROOT_DIR"
+
+if [[ -n "$CARGO_TARGET_DIR" ]]; then
+    SLICK_BINARY="$CARGO_TARGET_DIR/release/slick"
+else
+    SLICK_BINARY="./target/release/slick"
+fi
+
+if [[ ! -x "$SLICK_BINARY" ]]; then
+    echo "error: expected $SLICK_BINARY to exist" >&2
+    exit 1
+fi
+
+die() {
+    echo "error: $1" >&2
+    exit 1
+}
+
+TEST_DIR=$(mktemp -d)
+trap 'rm -rf -- "$TEST_DIR"' EXIT
+
+export SLICK_PATH="$SLICK_BINARY"
+export SLICK_PROMPT_CURSOR_SHAPE=''
+export SLICK_PROMPT_GIT_BRANCH_SYMBOL=''
+
+PROMPT_COMMAND='user_prompt_command'
+USER_PROMPT_COMMAND_STATUS=''
+user_prompt_command() {
+    USER_PROMPT_COMMAND_STATUS=$?
+}
+
+SOURCE_OUTPUT=$(source ./slick.bash)
+[[ -z "$SOURCE_OUTPUT" ]] || die "slick.bash should be quiet when sourced"
+source ./slick.bash
+source ./slick.bash
+
+[[ "$PROMPT_COMMAND" == "slick_prompt_precmd;user_prompt_command" ]] ||
+    die "slick.bash should run first and keep the existing PROMPT_COMMAND once: $PROMPT_COMMAND"
+[[ "$PS0" == *slick_prompt_timestamp* ]] || die "slick.bash should record the start time in PS0"
+[[ "${PS0//slick_prompt_timestamp/}" != *slick_prompt_timestamp*slick_prompt_timestamp* ]] ||
+    die "re-sourcing should not add PS0 twice"
+
+# PS0 records the start time without printing anything
+slick_prompt_timestamp=''
+PS0_OUTPUT=${PS0@P}
+[[ -z "$PS0_OUTPUT" ]] || die "PS0 should print nothing: $PS0_OUTPUT"
+[[ "$slick_prompt_timestamp" == "$SECONDS" ]] || die "PS0 should record SECONDS"
+
+# Literal text must stay literal with promptvars on and off
+PROMPT_LITERAL='branch-$(id)-%n-`id`-\literal'
+slick_prompt_data='{"schema":2,"branch":"branch-$(id)-%n-`id`-\\literal"}'
+shopt -s promptvars
+PS1=$(slick_prompt_render 0)
+PROMPTVARS_ON_OUTPUT=${PS1@P}
+[[ "$PROMPTVARS_ON_OUTPUT" == *"$PROMPT_LITERAL"* ]] || die "promptvars-on rendering should preserve exact branch text"
+[[ "$PROMPTVARS_ON_OUTPUT" != *uid=* ]] || die "promptvars-on rendering executed branch command substitution"
+
+shopt -u promptvars
+PS1=$(slick_prompt_render 0)
+PROMPTVARS_OFF_OUTPUT=${PS1@P}
+[[ "$PROMPTVARS_OFF_OUTPUT" == *"$PROMPT_LITERAL"* ]] || die "promptvars-off rendering should preserve exact branch text"
+shopt -s promptvars
+
+# A mock binary shows which phases and arguments reach the prompt
+MOCK_SLICK="$TEST_DIR/slick"
+cat > "$MOCK_SLICK" <<'EOF'
+#!/usr/bin/env bash
+case "$1" in
+    precmd)
+        if [[ -e "$MOCK_DIR/ran" ]]; then
+            sleep 1
+        fi
+        touch "$MOCK_DIR/ran" 2>/dev/null
+        echo 'phase-1'
+        sleep 0.5
+        echo 'phase-2'
+        ;;
+    prompt)
+        shift
+        while (( $# )); do
+            case "$1" in
+                -d|-e|-r|--backend) printf '%s=%s ' "$1" "$2"; shift ;;
+            esac
+            shift
+        done
+        ;;
+esac
+EOF
+chmod +x "$MOCK_SLICK"
+export MOCK_DIR="$TEST_DIR"
+SLICK_PATH="$MOCK_SLICK"
+
+cd "$TEST_DIR"
+slick_prompt_timestamp=$(( SECONDS - 5 ))
+START=${EPOCHREALTIME/[.,]/}
+(exit 3) || slick_prompt_precmd || true
+WAITED=$(( ${EPOCHREALTIME/[.,]/} - START ))
+(( WAITED < 250000 )) || die "the prompt should not wait for phases after the first: ${WAITED}us"
+[[ "$PS1" == "--backend=bash -r=3 -d=phase-1 -e=5 " ]] || die "first prompt should show the first phase, exit status and elapsed time: $PS1"
+[[ -z "$slick_prompt_timestamp" ]] || die "the start time should be cleared once it is used"
+
+sleep 0.6
+slick_prompt_precmd
+[[ "$PS1" == "--backend=bash -r=0 -d=phase-2 " ]] || die "a phase that arrived late should reach the next prompt: $PS1"
+
+cd "$ROOT_DIR"
+slick_prompt_precmd
+[[ "$PS1" == "--backend=bash -r=0 -d= " ]] || die "late phases should not follow a directory change: $PS1"
+slick_prompt_close_fd
+
+# PROMPT_COMMAND entries after slick still see the exit status
+(exit 4) || eval "$PROMPT_COMMAND" || true
+[[ "$USER_PROMPT_COMMAND_STATUS" == 4 ]] || die "later PROMPT_COMMAND entries should see the exit status"
+slick_prompt_close_fd
@@ -0,0 +1,196 @@
+//! Tests for the status summary `slick precmd` reuses while the repository is unchanged.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use serde_json::Value;
+use std::{fs, path::Path, path::PathBuf, process::Command, thread, time::Duration};
+use tempfile::TempDir;
+
+/// Runs `slick precmd` in `dir` and returns the counts it printed last.
+fn status(dir: &Path, cache: &TempDir, env: &[(&str, &str)]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .envs(env.iter().copied())
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let line: Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    common::counts_summary(&line)
+}
+
+fn saved_file(cache: &TempDir) -> Option<PathBuf> {
+    fs::read_dir(cache.path().join("slick"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().contains("status_"))
+}
+
+/// Replaces the saved counts, so a prompt showing `conflicted=99` must have read them
+/// back.
+fn tamper(cache: &TempDir) {
+    let path = saved_file(cache).expect("the status should be saved");
+    let mut saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    *saved.pointer_mut("/report/counts/conflicted").unwrap() = Value::from(99);
+    fs::write(path, saved.to_string()).unwrap();
+}
+
+/// A repository with a committed file in a subdirectory, left alone long enough for
+/// its status to be saved.
+fn settled_repo(cache: &TempDir) -> (TempDir, git2::Repository) {
+    let (dir, repo) = common::create_test_repo();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src").join("lib.rs"), "one").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("src/lib.rs")).unwrap();
+    index.write().unwrap();
+    common::create_commit(&repo, "initial");
+
+    // Results stamped within a second of the scan are not kept
+    thread::sleep(Duration::from_millis(2100));
+    assert_eq!(status(dir.path(), cache, &[]), "");
+    (dir, repo)
+}
+
+#[test]
+fn test_unchanged_repository_reuses_the_saved_status() {
+    let cache = TempDir::new().unwrap();
+    let (dir, _repo) = settled_repo(&cache);
+
+    tamper(&cache);
+    assert_eq!(status(dir.path(), &cache, &[]), "conflicted=99");
+
+    // Turned off, the work tree is scanned again
+    assert_eq!(
+        status(
+            dir.path(),
+            &cache,
+            &[("SLICK_PROMPT_GIT_STATUS_CACHE", "0")]
+        ),
+        ""
+    );
+}
+
+#[test]
+fn test_saved_status_is_dropped_when_the_repository_changes() {
+    let cache = TempDir::new().unwrap();
+    let (dir, repo) = settled_repo(&cache);
+
+    // An edit that keeps the size of a tracked file
+    tamper(&cache);
+    fs::write(dir.path().join("src").join("lib.rs"), "two").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "unstaged.modified=1");
+
+    // A new file next to a tracked one
+    tamper(&cache);
+    fs::write(dir.path().join("src").join("new.rs"), "new").unwrap();
+    assert_eq!(
+        status(dir.path(), &cache, &[]),
+        "unstaged.modified=1 untracked=1"
+    );
+
+    // Staging
+    tamper(&cache);
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("src/lib.rs")).unwrap();
+    index.write().unwrap();
+    assert_eq!(
+        status(dir.path(), &cache, &[]),
+        "staged.modified=1 untracked=1"
+    );
+}
+
+#[test]
+fn test_saved_status_notices_files_in_untracked_directories() {
+    let cache = TempDir::new().unwrap();
+    let (dir, repo) = settled_repo(&cache);
+    repo.config()
+        .unwrap()
+        .set_str("status.showUntrackedFiles", "all")
+        .unwrap();
+    let untracked = dir.path().join("notes").join("2026");
+    fs::create_dir_all(&untracked).unwrap();
+    fs::write(untracked.join("one.md"), "1").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+
+    thread::sleep(Duration::from_millis(2100));
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+    tamper(&cache);
+    assert_eq!(status(dir.path(), &cache, &[]), "conflicted=99 untracked=1");
+
+    fs::write(untracked.join("two.md"), "2").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=2");
+}
+
+#[test]
+fn test_saved_status_notices_files_in_empty_and_ignored_only_directories() {
+    let cache = TempDir::new().unwrap();
+    let (dir, _repo) = common::create_test_repo();
+    fs::write(
+        dir.path().join(".git").join("info").join("exclude"),
+        "*.o\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("empty")).unwrap();
+    fs::create_dir(dir.path().join("build")).unwrap();
+    fs::write(dir.path().join("build").join("main.o"), "").unwrap();
+    fs::create_dir_all(dir.path().join("empty").join("deeper")).unwrap();
+
+    thread::sleep(Duration::from_millis(2100));
+    assert_eq!(status(dir.path(), &cache, &[]), "");
+    assert!(saved_file(&cache).is_some());
+
+    fs::write(dir.path().join("empty").join("deeper").join("new"), "").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+
+    fs::write(dir.path().join("build").join("main.c"), "").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=2");
+}
+
+#[test]
+fn test_saved_status_is_dropped_when_the_global_excludes_change() {
+    let cache = TempDir::new().unwrap();
+    let (dir, repo) = common::create_test_repo();
+    let excludes_dir = TempDir::new().unwrap();
+    let excludes = excludes_dir.path().join("ignore");
+    fs::write(&excludes, "*.log\n").unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("core.excludesFile", excludes.to_str().unwrap())
+        .unwrap();
+    fs::write(dir.path().join("debug.log"), "").unwrap();
+
+    thread::sleep(Duration::from_millis(2100));
+    assert_eq!(status(dir.path(), &cache, &[]), "");
+    assert!(saved_file(&cache).is_some());
+
+    fs::write(&excludes, "*.tmp\n").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+}
+
+#[test]
+fn test_saved_status_notices_edits_to_an_untracked_gitignore() {
+    let cache = TempDir::new().unwrap();
+    let (dir, _repo) = settled_repo(&cache);
+    let gitignore = dir.path().join("src").join(".gitignore");
+    fs::write(&gitignore, "*.tmp\n").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+
+    thread::sleep(Duration::from_millis(2100));
+    assert_eq!(status(dir.path(), &cache, &[]), "untracked=1");
+    tamper(&cache);
+    assert_eq!(status(dir.path(), &cache, &[]), "conflicted=99 untracked=1");
+
+    // Rewriting the file leaves its directory untouched
+    fs::write(&gitignore, ".gitignore\n").unwrap();
+    assert_eq!(status(dir.path(), &cache, &[]), "");
+}
@@ -0,0 +1,161 @@
+//! Tests for the staged and unstaged status counts.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use git2::Repository;
+use slick::git::{self, ChangeCounts, RepoOverrides, StatusCounts};
+use std::{fs, path::Path, process::Command};
+use tempfile::TempDir;
+
+/// The counts of `repo`, checked against what `slick precmd` reports for it.
+fn counts(repo: &Repository) -> StatusCounts {
+    let report = git::get_status_report(repo, &RepoOverrides::read(repo)).unwrap();
+    assert_eq!(common::precmd_report(repo.workdir().unwrap(), &[]), report);
+    report.counts
+}
+
+fn stage(repo: &Repository, path: &str) {
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+}
+
+#[test]
+fn test_counts_split_index_and_work_tree_changes() {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("a.txt"), "one").unwrap();
+    fs::write(dir.path().join("b.txt"), "two").unwrap();
+    stage(&repo, "a.txt");
+    stage(&repo, "b.txt");
+    common::create_commit(&repo, "initial");
+    assert_eq!(counts(&repo), StatusCounts::default());
+    assert!(counts(&repo).is_empty());
+
+    // A staged edit with another edit on top counts on both sides
+    fs::write(dir.path().join("a.txt"), "one two").unwrap();
+    stage(&repo, "a.txt");
+    fs::write(dir.path().join("a.txt"), "one two three").unwrap();
+    // A new staged file, a deleted one and an untracked one
+    fs::write(dir.path().join("c.txt"), "three").unwrap();
+    stage(&repo, "c.txt");
+    fs::remove_file(dir.path().join("b.txt")).unwrap();
+    fs::write(dir.path().join("d.txt"), "four").unwrap();
+
+    assert_eq!(
+        counts(&repo),
+        StatusCounts {
+            staged: ChangeCounts {
+                added: 1,
+                modified: 1,
+                ..ChangeCounts::default()
+            },
+            unstaged: ChangeCounts {
+                modified: 1,
+                deleted: 1,
+                ..ChangeCounts::default()
+            },
+            untracked: 1,
+            conflicted: 0,
+        }
+    );
+}
+
+#[test]
+fn test_precmd_json_carries_both_sides() {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("a.txt"), "one").unwrap();
+    stage(&repo, "a.txt");
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("a.txt"), "two").unwrap();
+    stage(&repo, "a.txt");
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+
+    let expected = StatusCounts {
+        staged: ChangeCounts {
+            modified: 1,
+            ..ChangeCounts::default()
+        },
+        untracked: 1,
+        ..StatusCounts::default()
+    };
+    for cache in ["1", "0"] {
+        let report = common::precmd_report(dir.path(), &[("SLICK_PROMPT_GIT_STATUS_CACHE", cache)]);
+        assert!(!report.dirty);
+        assert_eq!(report.counts, expected, "status.cache={cache}");
+    }
+}
+
+fn render(data: &str) -> String {
+    render_with(data, &[])
+}
+
+fn render_with(data: &str, env: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args(["prompt", "-r", "0", "-d", data])
+        .env_clear()
+        .envs(env.iter().copied())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .output()
+        .expect("slick prompt should run");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_staged_and_unstaged_counts_render_in_their_own_colors() {
+    let stdout = render(
+        r#"{"schema":2,"branch":"main","staged":true,"counts":{"staged":{"modified":3},"unstaged":{"modified":1},"untracked":3,"conflicted":1}}"#,
+    );
+    assert!(stdout.contains("%F{5}[UU 1 M 1 ?? 3]"), "{stdout}");
+    assert!(stdout.contains("%F{7}[M 3]"), "{stdout}");
+    assert!(!stdout.contains("[staged]"), "{stdout}");
+
+    // Only staged changes leave the status segment empty
+    let stdout =
+        render(r#"{"schema":2,"branch":"main","staged":true,"counts":{"staged":{"added":1}}}"#);
+    assert!(stdout.contains("%F{7}[A 1]"), "{stdout}");
+    assert!(!stdout.contains("%F{5}["), "{stdout}");
+
+    // Without staged counts, as in the first phase, the marker stays
+    let stdout = render(
+        r#"{"schema":2,"branch":"main","counts":{"unstaged":{"modified":1}},"staged":true}"#,
+    );
+    assert!(stdout.contains("%F{5}[M 1]"), "{stdout}");
+    assert!(stdout.contains("[staged]"), "{stdout}");
+}
+
+#[test]
+fn test_status_formats_restyle_the_counts() {
+    let data = r#"{"schema":2,"branch":"main","counts":{"staged":{"added":2},"unstaged":{"modified":1,"deleted":4},"untracked":3}}"#;
+    let stdout = render_with(
+        data,
+        &[
+            (
+                "SLICK_PROMPT_GIT_STATUS_FORMAT",
+                "={conflicted} !{modified} ?{untracked}",
+            ),
+            ("SLICK_PROMPT_GIT_STAGED_FORMAT", "+{added}"),
+            ("SLICK_PROMPT_GIT_STATUS_UNTRACKED_COLOR", "3"),
+        ],
+    );
+    // Deleted files have no placeholder, and each count can take its own color
+    assert!(stdout.contains("%F{5}[!1 %F{3}?3%F{5}]"), "{stdout}");
+    assert!(stdout.contains("%F{7}[+2]"), "{stdout}");
+
+    // Each segment only counts its own side
+    let stdout = render_with(
+        data,
+        &[
+            ("SLICK_PROMPT_GIT_STATUS_FORMAT", "+{added}"),
+            ("SLICK_PROMPT_GIT_STAGED_FORMAT", "?{untracked}"),
+        ],
+    );
+    assert!(!stdout.contains("[+"), "{stdout}");
+    assert!(!stdout.contains("[?"), "{stdout}");
+    assert!(!stdout.contains("[staged]"), "{stdout}");
+}
@@ -0,0 +1,162 @@
+//! Tests for the `git status` deadline and the cheaper scan after a missed one.
+
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use serde_json::Value;
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+/// Runs `slick precmd` in `dir` and returns its last line.
+fn precmd(dir: &Path, cache: &TempDir, env: &[(&str, &str)]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .envs(env.iter().copied())
+        .output()
+        .expect("slick precmd should run");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    serde_json::from_str(stdout.lines().last().unwrap()).unwrap()
+}
+
+fn timed_out(line: &Value) -> bool {
+    line.get("status_timed_out").and_then(Value::as_bool) == Some(true)
+}
+
+fn status(line: &Value) -> String {
+    common::counts_summary(line)
+}
+
+/// A repository with one modified and one untracked file.
+fn dirty_repo() -> (TempDir, git2::Repository) {
+    let (dir, repo) = common::create_test_repo();
+    fs::write(dir.path().join("tracked.txt"), "one").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("tracked.txt")).unwrap();
+    index.write().unwrap();
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("tracked.txt"), "two").unwrap();
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+    (dir, repo)
+}
+
+#[test]
+fn test_status_past_the_deadline_is_marked_unknown() {
+    let (dir, _repo) = dirty_repo();
+    let cache = TempDir::new().unwrap();
+
+    let started = Instant::now();
+    let line = precmd(
+        dir.path(),
+        &cache,
+        &[
+            ("SLICK_TEST_DELAY", "5"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT", "1"),
+        ],
+    );
+    assert!(
+        started.elapsed() < Duration::from_secs(4),
+        "slick precmd should not wait for the scan: {:?}",
+        started.elapsed()
+    );
+    assert!(timed_out(&line), "{line}");
+    assert_eq!(line.get("phase").and_then(Value::as_str), Some("status"));
+    assert_eq!(status(&line), "");
+
+    // Nothing is remembered without SLICK_PROMPT_GIT_STATUS_FALLBACK
+    let line = precmd(
+        dir.path(),
+        &cache,
+        &[("SLICK_PROMPT_GIT_STATUS_FALLBACK", "1")],
+    );
+    assert!(!timed_out(&line));
+    assert_eq!(status(&line), "unstaged.modified=1 untracked=1");
+}
+
+#[test]
+fn test_missed_deadline_switches_the_repository_to_the_cheaper_scan() {
+    let (dir, repo) = dirty_repo();
+    let cache = TempDir::new().unwrap();
+    let fallback = ("SLICK_PROMPT_GIT_STATUS_FALLBACK", "1");
+
+    precmd(
+        dir.path(),
+        &cache,
+        &[
+            ("SLICK_TEST_DELAY", "5"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT", "1"),
+            fallback,
+        ],
+    );
+
+    // Untracked files are skipped from now on
+    assert_eq!(
+        status(&precmd(dir.path(), &cache, &[fallback])),
+        "unstaged.modified=1"
+    );
+
+    // unless the repository asks for them explicitly
+    repo.config()
+        .unwrap()
+        .set_bool("slick.showUntracked", true)
+        .unwrap();
+    assert_eq!(
+        status(&precmd(dir.path(), &cache, &[fallback])),
+        "unstaged.modified=1 untracked=1"
+    );
+}
+
+#[test]
+fn test_repository_can_turn_the_deadline_off() {
+    let (dir, repo) = dirty_repo();
+    let cache = TempDir::new().unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("slick.statusTimeout", "0")
+        .unwrap();
+
+    let line = precmd(
+        dir.path(),
+        &cache,
+        &[
+            ("SLICK_TEST_DELAY", "2"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT", "1"),
+        ],
+    );
+    assert!(!timed_out(&line));
+    assert_eq!(status(&line), "unstaged.modified=1 untracked=1");
+}
+
+#[test]
+fn test_unknown_status_renders_the_timeout_symbol() {
+    let config_home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .args([
+            "prompt",
+            "-r",
+            "0",
+            "-d",
+            r#"{"schema":2,"branch":"main","status_timed_out":true}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("HOME", "/nonexistent")
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .output()
+        .expect("slick prompt should run");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("%F{5}[\u{2026}]"), "{stdout}");
+}
@@ -0,0 +1,82 @@
+//! Tests for style specs such as `bold #ff8800 on 236` in the `*_COLOR` settings.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+/// Renders the full prompt with a clean environment and an empty config directory.
+fn prompt(envs: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .args([
+            "prompt",
+            "-e",
+            "0",
+            "-r",
+            "0",
+            "-k",
+            "main",
+            "-d",
+            r#"{"schema":2,"branch":"feature","counts":{"unstaged":{"modified":1}}}"#,
+        ])
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+#[test]
+fn test_plain_colors_render_unchanged() {
+    let stdout = prompt(&[("SLICK_PROMPT_GIT_STATUS_COLOR", "5")]);
+    assert!(stdout.contains("%F{5}[M 1]\n"), "{stdout:?}");
+}
+
+#[test]
+fn test_attributes_and_background_are_reset_after_the_segment() {
+    let stdout = prompt(&[
+        ("SLICK_PROMPT_GIT_STATUS_COLOR", "bold underline 5 on 236"),
+        ("SLICK_PROMPT_GIT_BRANCH_COLOR", "italic cyan"),
+    ]);
+
+    assert!(
+        stdout.contains("%F{5}%K{236}%B%U[M 1]%u%b%k\n"),
+        "{stdout:?}"
+    );
+    assert!(
+        stdout.contains("%F{cyan}%{\x1b[3m%}feature%{\x1b[23m%} "),
+        "{stdout:?}"
+    );
+}
+
+#[test]
+fn test_hex_colors_downgrade_without_truecolor() {
+    let style = [("SLICK_PROMPT_PATH_COLOR", "#ff8800")];
+
+    let downgraded = prompt(&style);
+    assert!(downgraded.contains("%F{208}%~ "), "{downgraded:?}");
+
+    for colorterm in ["truecolor", "24bit"] {
+        let truecolor = prompt(&[style[0], ("COLORTERM", colorterm)]);
+        assert!(truecolor.contains("%F{#ff8800}%~ "), "{truecolor:?}");
+    }
+}
+
+#[test]
+fn test_invalid_style_falls_back_to_the_default() {
+    let stdout = prompt(&[("SLICK_PROMPT_GIT_STATUS_COLOR", "bold on")]);
+    assert!(stdout.contains("%F{5}[M 1]\n"), "{stdout:?}");
+}
@@ -0,0 +1,111 @@
+//! Tests for the built-in themes selected with `SLICK_PROMPT_THEME`.
+
+#![allow(clippy::expect_used)]
+
+use std::process::Command;
+use tempfile::TempDir;
+
+fn get_slick_binary() -> String {
+    env!("CARGO_BIN_EXE_slick").to_string()
+}
+
+/// Runs slick with a clean environment and an empty config directory.
+fn slick(args: &[&str], envs: &[(&str, &str)]) -> String {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .args(args)
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_CURSOR_SHAPE", "")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(
+        output.status.success(),
+        "slick failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+}
+
+fn prompt(envs: &[(&str, &str)]) -> String {
+    slick(
+        &[
+            "prompt",
+            "-e",
+            "0",
+            "-r",
+            "0",
+            "-k",
+            "main",
+            "-d",
+            r#"{"schema":2,"branch":"feature","remote":["⇣1"]}"#,
+        ],
+        envs,
+    )
+}
+
+#[test]
+fn test_ascii_theme_drops_glyphs() {
+    let default = prompt(&[]);
+    assert!(
+        default.contains("%F{2}\u{e0a0} %F{3}feature"),
+        "{default:?}"
+    );
+
+    let ascii = prompt(&[("SLICK_PROMPT_THEME", "ascii")]);
+    assert!(ascii.contains(" %F{3}feature"), "{ascii:?}");
+    assert!(!ascii.contains('\u{e0a0}'), "{ascii:?}");
+}
+
+#[test]
+fn test_variables_override_the_theme() {
+    let themed = prompt(&[("SLICK_PROMPT_THEME", "high-contrast")]);
+    assert!(themed.contains("%F{51}%~"), "{themed:?}");
+    assert!(themed.contains("%F{226}feature"), "{themed:?}");
+
+    let overridden = prompt(&[
+        ("SLICK_PROMPT_THEME", "high-contrast"),
+        ("SLICK_PROMPT_PATH_COLOR", "74"),
+    ]);
+    assert!(overridden.contains("%F{74}%~"), "{overridden:?}");
+    assert!(overridden.contains("%F{226}feature"), "{overridden:?}");
+}
+
+#[test]
+fn test_config_reports_theme_sources() {
+    let config = slick(&["config"], &[("SLICK_PROMPT_THEME", "minimal")]);
+
+    assert!(
+        config.contains("SLICK_PROMPT_THEME=minimal  # env\n"),
+        "{config}"
+    );
+    assert!(
+        config.contains("SLICK_PROMPT_GIT_FETCH=0  # theme minimal\n"),
+        "{config}"
+    );
+    assert!(
+        config.contains("SLICK_PROMPT_PATH_COLOR=74  # default\n"),
+        "{config}"
+    );
+}
+
+#[test]
+fn test_unknown_theme_is_reported() {
+    let config_home = TempDir::new().expect("tempdir should be created");
+    let output = Command::new(get_slick_binary())
+        .arg("config")
+        .env_clear()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("SLICK_PROMPT_THEME", "solarized")
+        .output()
+        .expect("Failed to execute slick");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("SLICK_PROMPT_THEME: invalid value \"solarized\" (env), expected one of default, ascii, nerd, minimal, high-contrast"),
+        "{stderr}"
+    );
+}
@@ -10,14 +10,14 @@ fn get_slick_binary() -> String {
     env!("CARGO_BIN_EXE_slick").to_string()
 }
 
-fn prompt_data(branch: &str, status: &str) -> String {
+fn prompt_data(branch: &str, modified: u32) -> String {
     format!(
-        r#"{{"action":"","auth_failed":false,"branch":"{branch}","remote":[],"staged":false,"status":"{status}","u_name":""}}"#,
+        r#"{{"schema":2,"action":"","auth_failed":false,"branch":"{branch}","remote":[],"staged":false,"counts":{{"unstaged":{{"modified":{modified}}}}},"u_name":""}}"#,
     )
 }
 
 fn prompt_data_with_branch(branch: &str) -> String {
-    prompt_data(branch, "")
+    prompt_data(branch, 0)
 }
 
 fn write_toolbox_metadata() -> (tempfile::TempDir, String, String) {
@@ -492,7 +492,7 @@ fn test_transient_prompt_renders_single_line_with_timestamp() {
             "-k",
             "main",
             "-d",
-            &prompt_data("feature/test", "M 10"),
+            &prompt_data("feature/test", 10),
         ])
         .output()
         .expect("Failed to execute slick");
@@ -522,7 +522,7 @@ fn test_transient_prompt_renders_single_line_with_short_timestamp() {
             "-k",
             "main",
             "-d",
-            &prompt_data("feature/test", "M 10"),
+            &prompt_data("feature/test", 10),
         ])
         .output()
         .expect("Failed to execute slick");
@@ -0,0 +1,225 @@
+//! Tests for `slick precmd` watch mode (`SLICK_PROMPT_GIT_WATCH=1`).
+#![cfg(target_os = "linux")]
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use serde_json::Value;
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+/// A watching `slick precmd` and the lines it prints.
+struct Watch {
+    child: Child,
+    lines: Receiver<Value>,
+    _cache: TempDir,
+}
+
+/// `slick precmd` in watch mode, writing the auth cache under `cache`.
+fn precmd(dir: &Path, timeout: &str, cache: &TempDir) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_slick"));
+    command
+        .arg("precmd")
+        .current_dir(dir)
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env("SLICK_PROMPT_GIT_WATCH", "1")
+        .env("SLICK_PROMPT_GIT_WATCH_TIMEOUT", timeout)
+        .env("SLICK_TEST_AUTH_CACHE_DIR", cache.path())
+        .env_remove("XDG_RUNTIME_DIR")
+        .stdout(Stdio::piped());
+    command
+}
+
+/// Waits up to `wait` for `child` to exit on its own.
+fn exits_within(child: &mut Child, wait: Duration) -> bool {
+    let deadline = Instant::now() + wait;
+    while Instant::now() < deadline {
+        if child.try_wait().unwrap().is_some() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+impl Watch {
+    fn start(dir: &Path, timeout: &str) -> Self {
+        Self::start_with(dir, timeout, &[])
+    }
+
+    fn start_with(dir: &Path, timeout: &str, env: &[(&str, &str)]) -> Self {
+        let cache = TempDir::new().unwrap();
+        let mut child = precmd(dir, timeout, &cache)
+            .envs(env.iter().copied())
+            .spawn()
+            .expect("slick precmd should start");
+
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = line.unwrap();
+                if sender.send(serde_json::from_str(&line).unwrap()).is_err() {
+                    return;
+                }
+            }
+        });
+        Self {
+            child,
+            lines,
+            _cache: cache,
+        }
+    }
+
+    fn next(&self) -> Option<Value> {
+        self.lines.recv_timeout(Duration::from_secs(5)).ok()
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn field<'a>(line: &'a Value, name: &str) -> &'a str {
+    line.get(name).and_then(Value::as_str).unwrap()
+}
+
+#[test]
+fn test_watch_reemits_the_status_when_the_repository_changes() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+    fs::write(dir.path().join("one.txt"), "1").unwrap();
+
+    let watch = Watch::start(dir.path(), "60");
+    assert_eq!(field(&watch.next().unwrap(), "phase"), "local");
+    assert_eq!(
+        common::counts_summary(&watch.next().unwrap()),
+        "untracked=1"
+    );
+
+    // A work-tree edit
+    fs::write(dir.path().join("two.txt"), "2").unwrap();
+    let line = watch.next().expect("an edit should be picked up");
+    assert_eq!(field(&line, "phase"), "status");
+    assert_eq!(common::counts_summary(&line), "untracked=2");
+
+    // A branch switch made from another terminal
+    repo.set_head("refs/heads/other").unwrap();
+    let line = watch.next().expect("a new HEAD should be picked up");
+    assert_eq!(field(&line, "branch"), "other");
+}
+
+#[test]
+fn test_watch_refreshes_while_files_keep_changing() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+
+    let watch = Watch::start(dir.path(), "60");
+    assert!(watch.next().is_some());
+    assert!(watch.next().is_some());
+
+    // A build writing a file without pause, for longer than `next` waits
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let (path, stop) = (dir.path().join("busy.log"), Arc::clone(&stop));
+        thread::spawn(move || {
+            let started = Instant::now();
+            while !stop.load(Ordering::Relaxed) && started.elapsed() < Duration::from_secs(10) {
+                fs::write(&path, "busy").unwrap();
+            }
+        })
+    };
+    let line = watch.next();
+    stop.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+
+    let line = line.expect("the refresh should not wait for the writes to stop");
+    assert_eq!(common::counts_summary(&line), "untracked=1");
+}
+
+#[test]
+fn test_watch_stops_when_the_shell_closes_the_pipe() {
+    let (dir, _repo) = common::create_test_repo();
+    let cache = TempDir::new().unwrap();
+    let mut child = precmd(dir.path(), "60", &cache)
+        .spawn()
+        .expect("slick precmd should start");
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    stdout.read_line(&mut line).unwrap();
+    assert!(!exits_within(&mut child, Duration::from_millis(300)));
+
+    drop(stdout);
+    let exited = exits_within(&mut child, Duration::from_secs(5));
+    let _ = child.kill();
+    let _ = child.wait();
+    assert!(exited, "the watch should end once nobody reads its output");
+}
+
+#[test]
+fn test_watch_stops_after_the_idle_timeout() {
+    let (dir, _repo) = common::create_test_repo();
+
+    let mut watch = Watch::start(dir.path(), "1");
+    assert!(watch.next().is_some());
+    assert!(watch.next().is_some());
+    assert!(exits_within(&mut watch.child, Duration::from_secs(5)));
+}
+
+#[test]
+fn test_precmd_exits_after_its_phases_without_watch_mode() {
+    let (dir, _repo) = common::create_test_repo();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_slick"))
+        .arg("precmd")
+        .current_dir(dir.path())
+        .env("SLICK_PROMPT_GIT_FETCH", "0")
+        .env_remove("SLICK_PROMPT_GIT_WATCH")
+        .output()
+        .expect("slick precmd should run");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+}
+
+#[test]
+fn test_watch_runs_after_the_status_misses_its_deadline() {
+    let (dir, repo) = common::create_test_repo();
+    common::create_commit(&repo, "initial");
+
+    // Only the first scan is slowed down; the watcher's own scans are not
+    let watch = Watch::start_with(
+        dir.path(),
+        "60",
+        &[
+            ("SLICK_TEST_DELAY", "5"),
+            ("SLICK_PROMPT_GIT_STATUS_TIMEOUT", "1"),
+        ],
+    );
+    assert_eq!(field(&watch.next().unwrap(), "phase"), "local");
+    let line = watch.next().unwrap();
+    assert_eq!(line.get("status_timed_out"), Some(&Value::Bool(true)));
+
+    fs::write(dir.path().join("new.txt"), "new").unwrap();
+    let line = watch.next().expect("the watcher should still run");
+    assert_eq!(line.get("status_timed_out"), Some(&Value::Bool(false)));
+    assert_eq!(common::counts_summary(&line), "untracked=1");
+}